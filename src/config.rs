@@ -0,0 +1,791 @@
+//! Declarative prompt descriptions in [TOML].
+//!
+//! A [`PromptConfig`] describes the segments of a prompt, their order, symbols and styles.
+//! It is [built][`PromptConfig::build_left`] into [`Prompt`]s from the information in a
+//! [`Context`], which then render through any [`StyledWrite`].
+//!
+//! # Examples
+//!
+//! ```toml
+//! separator = " "
+//! suffix = { text = " ❯ ", style = { bold = true } }
+//!
+//! [[segment]]
+//! kind = "path"
+//! mode = "short"
+//! replacement = "…"
+//! style = "bold fg:bright-blue"
+//!
+//! [[segment]]
+//! kind = "git"
+//! show_status = true
+//! status_timeout_ms = 200
+//! style = { foreground = [255, 136, 0] }
+//!
+//! [right]
+//! separator = " "
+//!
+//! [[right.segment]]
+//! kind = "duration"
+//! format = "skip_high_and_low_zeros"
+//! precision = "seconds"
+//! threshold_ms = 2000
+//!
+//! [[right.segment]]
+//! kind = "exit_code"
+//! show_code = "on_error"
+//! ```
+//!
+//! [TOML]: https://toml.io
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    env::{
+        command_result::{CommandResult, ExitCode},
+        git::{self, Head, StatusLimits, UntrackedFiles, Upstream},
+        jj,
+        path::{abbreviate_home, AbbreviateHomeResult},
+    },
+    fmt::command_result::When,
+    segments::{
+        command_result::{CommandResultSegment, ExitCodeSegment, Symbols},
+        duration::{DurationFormat, DurationSegment, Precision},
+        git::{DiffStatsSegment, HeadSegment, OperationSegment, StatusSummarySegment},
+        jj::WorkingCopySegment,
+        path::{PathFormat, PathSegment},
+        Prompt, PromptBuilder, Segment, StyledText,
+    },
+    styling::{Change, Color, StyleChange, StyledWrite},
+};
+use serde::{de, Deserialize, Deserializer};
+use std::{
+    convert::TryFrom,
+    error, fmt, fs, io,
+    path::{self, Path, PathBuf},
+    time::Duration,
+};
+
+/// The information about the environment shown by a prompt.
+///
+/// Segments whose information is missing are left out of the built [`Prompt`]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Context {
+    /// The exit code of the last command.
+    pub exit_code: Option<ExitCode>,
+    /// How long the last command took.
+    pub duration: Option<Duration>,
+    /// The current directory, which is also where git repositories are searched from.
+    pub current_dir: Option<PathBuf>,
+}
+
+/// A prompt description, with a left part and an optional right part.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptConfig {
+    /// Text written before the segments of the left part.
+    #[serde(default)]
+    pub prefix: TextConfig,
+    /// Text written between the segments of the left part.
+    #[serde(default)]
+    pub separator: TextConfig,
+    /// Text written after the segments of the left part.
+    #[serde(default)]
+    pub suffix: TextConfig,
+    /// The segments of the left part, in order.
+    #[serde(default, rename = "segment")]
+    pub segments: Vec<SegmentConfig>,
+    /// The right part.
+    #[serde(default)]
+    pub right: Option<PartConfig>,
+    /// How the git segments query repositories.
+    #[serde(default)]
+    pub git_backend: GitBackend,
+}
+impl PromptConfig {
+    /// Parses a [TOML] prompt description.
+    ///
+    /// # Errors
+    ///
+    /// When `source` is not a valid description, returns [`ConfigError::Parse`]
+    /// locating the problem in `source`.
+    ///
+    /// [TOML]: https://toml.io
+    pub fn from_toml(source: &str) -> Result<Self, ConfigError> {
+        toml::from_str(source).map_err(|err| ConfigError::Parse(ParseError::new(source, err)))
+    }
+
+    /// Reads and parses a [TOML] prompt description file.
+    ///
+    /// # Errors
+    ///
+    /// When the file cannot be read, returns [`ConfigError::Io`].
+    /// When it is not a valid description, returns [`ConfigError::Parse`].
+    ///
+    /// [TOML]: https://toml.io
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        Self::from_toml(&fs::read_to_string(path).map_err(ConfigError::Io)?)
+    }
+
+    /// Builds the left part of the prompt.
+    pub fn build_left(&self, context: &Context) -> Prompt {
+        build(
+            &self.prefix,
+            &self.separator,
+            &self.suffix,
+            &self.segments,
+            self.git_backend,
+            context,
+        )
+    }
+
+    /// Builds the right part of the prompt, if it is described.
+    pub fn build_right(&self, context: &Context) -> Option<Prompt> {
+        self.right.as_ref().map(|right| {
+            build(
+                &right.prefix,
+                &right.separator,
+                &right.suffix,
+                &right.segments,
+                self.git_backend,
+                context,
+            )
+        })
+    }
+
+    /// [Builds][`Self::build_left`] and renders the left part of the prompt.
+    pub fn render_left(&self, writer: &mut dyn StyledWrite, context: &Context) -> io::Result<()> {
+        self.build_left(context).render(writer)
+    }
+}
+
+/// A part of a prompt other than the left one.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartConfig {
+    /// Text written before the segments.
+    #[serde(default)]
+    pub prefix: TextConfig,
+    /// Text written between the segments.
+    #[serde(default)]
+    pub separator: TextConfig,
+    /// Text written after the segments.
+    #[serde(default)]
+    pub suffix: TextConfig,
+    /// The segments, in order.
+    #[serde(default, rename = "segment")]
+    pub segments: Vec<SegmentConfig>,
+}
+
+fn build(
+    prefix: &TextConfig,
+    separator: &TextConfig,
+    suffix: &TextConfig,
+    segments: &[SegmentConfig],
+    git_backend: GitBackend,
+    context: &Context,
+) -> Prompt {
+    let mut builder = PromptBuilder::new()
+        .prefix(prefix.to_styled_text())
+        .separator(separator.to_styled_text())
+        .suffix(suffix.to_styled_text());
+    let mut repo = None;
+    for segment in segments {
+        if let Some((segment, style_change)) = segment.build(context, git_backend, &mut repo) {
+            builder = builder.boxed_segment(segment, style_change);
+        }
+    }
+    builder.build()
+}
+
+/// Text, either as a plain string or as a table with a style.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextConfig {
+    Plain(String),
+    Styled { text: String, style: StyleConfig },
+}
+impl<'de> Deserialize<'de> for TextConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Styled {
+            text: String,
+            #[serde(default)]
+            style: StyleConfig,
+        }
+
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = TextConfig;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a string or a table with `text` and `style`")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                Ok(TextConfig::Plain(text.to_owned()))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let Styled { text, style } =
+                    Styled::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(TextConfig::Styled { text, style })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+impl Default for TextConfig {
+    fn default() -> Self {
+        TextConfig::Plain(String::new())
+    }
+}
+impl TextConfig {
+    fn to_styled_text(&self) -> StyledText {
+        match self {
+            TextConfig::Plain(text) => StyledText::from(text.as_str()),
+            TextConfig::Styled { text, style } => StyledText::new(text.as_str(), style.into()),
+        }
+    }
+}
+
+/// A [`StyleChange`], where each absent field is [kept][`Change::Keep`].
+///
+/// It is written either as a table, such as `{ bold = true, foreground = "bright-red" }`,
+/// or as a [style specification][`StyleChange`'s `FromStr`], such as `"bold fg:bright-red"`.
+///
+/// [`StyleChange`'s `FromStr`]: `StyleChange#impl-FromStr-for-StyleChange`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields, remote = "Self")]
+pub struct StyleConfig {
+    pub foreground: Option<ColorConfig>,
+    pub background: Option<ColorConfig>,
+    pub bold: Option<bool>,
+    pub dim: Option<bool>,
+    pub underline: Option<bool>,
+    pub italic: Option<bool>,
+    pub blink: Option<bool>,
+    pub strike: Option<bool>,
+}
+impl<'de> Deserialize<'de> for StyleConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = StyleConfig;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a style table or a style specification string")
+            }
+
+            fn visit_str<E: de::Error>(self, spec: &str) -> Result<Self::Value, E> {
+                spec.parse::<StyleChange>()
+                    .map(|style_change| StyleConfig::from(&style_change))
+                    .map_err(E::custom)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                StyleConfig::deserialize(de::value::MapAccessDeserializer::new(map))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+impl From<&StyleConfig> for StyleChange {
+    fn from(style: &StyleConfig) -> Self {
+        fn change<T: Copy>(value: Option<T>) -> Change<T> {
+            value.map_or(Change::Keep, Change::SetTo)
+        }
+
+        StyleChange {
+            foreground: change(style.foreground.map(Color::from)),
+            background: change(style.background.map(Color::from)),
+            bold: change(style.bold),
+            dim: change(style.dim),
+            underline: change(style.underline),
+            italic: change(style.italic),
+            blink: change(style.blink),
+            strike: change(style.strike),
+        }
+    }
+}
+impl From<&StyleChange> for StyleConfig {
+    fn from(style_change: &StyleChange) -> Self {
+        fn value<T: Copy>(change: Change<T>) -> Option<T> {
+            match change {
+                Change::Keep => None,
+                Change::SetTo(value) => Some(value),
+            }
+        }
+
+        StyleConfig {
+            foreground: value(style_change.foreground).map(ColorConfig),
+            background: value(style_change.background).map(ColorConfig),
+            bold: value(style_change.bold),
+            dim: value(style_change.dim),
+            underline: value(style_change.underline),
+            italic: value(style_change.italic),
+            blink: value(style_change.blink),
+            strike: value(style_change.strike),
+        }
+    }
+}
+
+/// A [`Color`], written as a 256 colors palette index, an `[r, g, b]` array
+/// or any string accepted by [`Color`'s `FromStr`], such as `"bright-green"` or `"#ff8800"`.
+///
+/// [`Color`'s `FromStr`]: `Color#impl-FromStr-for-Color`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorConfig(pub Color);
+impl<'de> Deserialize<'de> for ColorConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ColorConfig;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a color index from 0 to 255, an [r, g, b] array or a color string"
+                )
+            }
+
+            fn visit_i64<E: de::Error>(self, index: i64) -> Result<Self::Value, E> {
+                u8::try_from(index)
+                    .map(|index| ColorConfig(Color::ANSI256(index)))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(index), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, index: u64) -> Result<Self::Value, E> {
+                u8::try_from(index)
+                    .map(|index| ColorConfig(Color::ANSI256(index)))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(index), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, color: &str) -> Result<Self::Value, E> {
+                color.parse().map(ColorConfig).map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                let [r, g, b] = <[u8; 3]>::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+                Ok(ColorConfig(Color::RGB(r, g, b)))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+impl From<ColorConfig> for Color {
+    fn from(color: ColorConfig) -> Self {
+        color.0
+    }
+}
+
+/// The description of a segment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SegmentConfig {
+    /// Fixed text.
+    Text {
+        text: String,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+    /// An [`ExitCodeSegment`] for [`Context::exit_code`].
+    ExitCode {
+        #[serde(flatten)]
+        symbols: SymbolsConfig,
+        #[serde(default = "default_show_code")]
+        show_code: When,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+    /// A [`CommandResultSegment`] for [`Context::exit_code`].
+    CommandResult {
+        #[serde(flatten)]
+        symbols: SymbolsConfig,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+    /// A [`DurationSegment`] for [`Context::duration`].
+    Duration {
+        #[serde(default = "default_duration_format")]
+        format: DurationFormat,
+        #[serde(default = "default_precision")]
+        precision: Precision,
+        /// Durations shorter than this number of milliseconds are not shown.
+        #[serde(default)]
+        threshold_ms: u64,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+    /// A [`PathSegment`] for [`Context::current_dir`].
+    Path {
+        #[serde(default)]
+        mode: PathMode,
+        /// The replacement of hidden folders, for the `short` and `middle_hidden` modes.
+        #[serde(default = "default_replacement")]
+        replacement: String,
+        #[serde(default = "default_path_separator")]
+        separator: String,
+        #[serde(default)]
+        root_separator: String,
+        root_dir: Option<String>,
+        /// Whether to [abbreviate the home directory][`abbreviate_home`] as `~`.
+        #[serde(default = "default_true")]
+        abbreviate_home: bool,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+    /// The [`HeadSegment`] and [`StatusSummarySegment`] of the git repository
+    /// containing [`Context::current_dir`].
+    Git {
+        #[serde(default = "default_true")]
+        show_head: bool,
+        #[serde(default = "default_true")]
+        show_ahead_behind: bool,
+        /// Whether to describe a detached HEAD by a tag or branch name instead of its hash.
+        #[serde(default = "default_true")]
+        describe_detached_head: bool,
+        /// Whether to show the working-copy commit of a [Jujutsu] workspace instead of the head,
+        /// which Jujutsu leaves detached, when the repository is colocated with the workspace.
+        ///
+        /// [Jujutsu]: https://jj-vcs.github.io/jj/
+        #[serde(default = "default_true")]
+        prefer_jj: bool,
+        /// Whether to show the operation in progress (such as a rebase or a merge), if any.
+        #[serde(default = "default_true")]
+        show_operation: bool,
+        #[serde(default = "default_true")]
+        show_status: bool,
+        /// How long to wait for the status before showing it as unknown.
+        status_timeout_ms: Option<u64>,
+        /// How many changed files to count before showing the counts as incomplete.
+        status_max_files: Option<usize>,
+        #[serde(default)]
+        untracked_files: UntrackedFiles,
+        /// Whether to show how many lines were inserted and deleted, as `git diff --shortstat` does.
+        #[serde(default)]
+        show_diff_stats: bool,
+        /// The size in bytes above which files are left out of the line counts.
+        diff_max_size: Option<u64>,
+        /// Text written between the head, the operation, the status and the line counts.
+        #[serde(default = "default_git_separator")]
+        separator: String,
+        #[serde(default)]
+        style: StyleConfig,
+    },
+}
+impl SegmentConfig {
+    fn build(
+        &self,
+        context: &Context,
+        git_backend: GitBackend,
+        repo: &mut Option<Option<Box<dyn git::Repository>>>,
+    ) -> Option<(Box<dyn Segment>, StyleChange)> {
+        match self {
+            SegmentConfig::Text { text, style } => {
+                Some((Box::new(StyledText::from(text.as_str())), style.into()))
+            }
+            SegmentConfig::ExitCode {
+                symbols,
+                show_code,
+                style,
+            } => {
+                let segment = ExitCodeSegment {
+                    exit_code: context.exit_code?,
+                    symbols: symbols.into(),
+                    show_code_when: show_code.clone(),
+                };
+                Some((Box::new(segment), style.into()))
+            }
+            SegmentConfig::CommandResult { symbols, style } => {
+                let segment = CommandResultSegment {
+                    command_result: CommandResult::from_success(context.exit_code?.is_success()),
+                    symbols: symbols.into(),
+                };
+                Some((Box::new(segment), style.into()))
+            }
+            SegmentConfig::Duration {
+                format,
+                precision,
+                threshold_ms,
+                style,
+            } => {
+                let segment = DurationSegment {
+                    duration: context.duration?,
+                    format: *format,
+                    precision: *precision,
+                    threshold: Duration::from_millis(*threshold_ms),
+                };
+                Some((Box::new(segment), style.into()))
+            }
+            SegmentConfig::Path {
+                mode,
+                replacement,
+                separator,
+                root_separator,
+                root_dir,
+                abbreviate_home: should_abbreviate_home,
+                style,
+            } => {
+                let path = context.current_dir.clone()?;
+                let path = if *should_abbreviate_home {
+                    match abbreviate_home(path) {
+                        AbbreviateHomeResult::Abbreviated(path) => path,
+                        AbbreviateHomeResult::HomeNotAnAcestor { path }
+                        | AbbreviateHomeResult::NoHome { path } => path,
+                    }
+                } else {
+                    path
+                };
+                let replacement = replacement.clone();
+                let segment = PathSegment {
+                    path,
+                    format: match mode {
+                        PathMode::Full => PathFormat::Full,
+                        PathMode::Short => PathFormat::Short { replacement },
+                        PathMode::MiddleHidden => PathFormat::MiddleHidden { replacement },
+                    },
+                    separator: separator.clone(),
+                    root_separator: root_separator.clone(),
+                    root_dir_override: root_dir.clone(),
+                };
+                Some((Box::new(segment), style.into()))
+            }
+            SegmentConfig::Git {
+                show_head,
+                show_ahead_behind,
+                describe_detached_head,
+                prefer_jj,
+                show_operation,
+                show_status,
+                status_timeout_ms,
+                status_max_files,
+                untracked_files,
+                show_diff_stats,
+                diff_max_size,
+                separator,
+                style,
+            } => {
+                let repo = repo
+                    .get_or_insert_with(|| git_backend.open_repo(context.current_dir.as_ref()?))
+                    .as_deref()?;
+                let mut builder = PromptBuilder::new().separator(separator.as_str());
+                let working_copy = match (show_head, prefer_jj, &context.current_dir) {
+                    (true, true, Some(dir)) => colocated_workspace(repo, dir)
+                        .and_then(|workspace| jj::query_working_copy(&workspace).ok()),
+                    _ => None,
+                };
+                if let Some(working_copy) = working_copy {
+                    builder = builder.segment(WorkingCopySegment::new(working_copy));
+                } else if *show_head {
+                    if let Ok(mut head) = repo.query_head() {
+                        if let (false, Head::Branch { upstream, .. }) =
+                            (show_ahead_behind, &mut head)
+                        {
+                            *upstream = Ok(Upstream::NotConfigured);
+                        }
+                        let mut segment = HeadSegment::new(head);
+                        if let (true, Head::Commit(id)) = (describe_detached_head, &segment.head) {
+                            segment.commit_description = repo.query_commit_description(*id).ok();
+                        }
+                        builder = builder.segment(segment);
+                    }
+                }
+                if *show_operation {
+                    if let Some(operation) = repo.query_operation() {
+                        builder = builder.segment(OperationSegment::new(operation));
+                    }
+                }
+                if *show_status {
+                    let limits = StatusLimits {
+                        time_budget: status_timeout_ms.map(Duration::from_millis),
+                        max_files: *status_max_files,
+                        untracked_files: *untracked_files,
+                    };
+                    if let Ok(status) = repo.query_status_summary(&limits) {
+                        builder = builder.segment(StatusSummarySegment::new(status));
+                    }
+                }
+                if *show_diff_stats {
+                    if let Ok(stats) = repo.query_diff_stats(*diff_max_size) {
+                        builder = builder.segment(DiffStatsSegment::new(stats));
+                    }
+                }
+                Some((Box::new(builder.build()), style.into()))
+            }
+        }
+    }
+}
+
+/// Finds the Jujutsu workspace containing `dir` whose root is the working tree of `repo`,
+/// leaving out a workspace the repository is merely nested in.
+fn colocated_workspace(repo: &dyn git::Repository, dir: &Path) -> Option<jj::Workspace> {
+    let workspace = jj::open_workspace(dir).ok()?;
+    let work_tree = fs::canonicalize(repo.work_dir()?).ok()?;
+    (fs::canonicalize(workspace.root()).ok()? == work_tree).then_some(workspace)
+}
+
+/// The backend with which the [git segments][`SegmentConfig::Git`] query repositories.
+///
+/// The default is libgit2 when the `env-git-git2` feature is enabled, and the git CLI otherwise,
+/// so that the `config` feature itself does not depend on C libraries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitBackend {
+    /// [`env::git::git2`][`crate::env::git::git2`]
+    #[cfg(feature = "env-git-git2")]
+    #[default]
+    Libgit2,
+    /// [`env::git::cli`][`crate::env::git::cli`]
+    #[cfg_attr(not(feature = "env-git-git2"), default)]
+    Cli,
+    /// [`env::git::gix`][`crate::env::git::gix`]
+    #[cfg(feature = "env-git-gix")]
+    Gix,
+}
+impl GitBackend {
+    fn open_repo(self, dir: &Path) -> Option<Box<dyn git::Repository>> {
+        match self {
+            #[cfg(feature = "env-git-git2")]
+            GitBackend::Libgit2 => Some(Box::new(git::git2::open_repo(dir).ok()?)),
+            GitBackend::Cli => Some(Box::new(git::cli::open_repo(dir).ok()?)),
+            #[cfg(feature = "env-git-gix")]
+            GitBackend::Gix => Some(Box::new(git::gix::open_repo(dir).ok()?)),
+        }
+    }
+}
+
+/// How a [path segment][`SegmentConfig::Path`] is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathMode {
+    /// [`write_full`][`crate::fmt::path::write_full`]
+    #[default]
+    Full,
+    /// [`write_short`][`crate::fmt::path::write_short`]
+    Short,
+    /// [`write_with_middle_hidden`][`crate::fmt::path::write_with_middle_hidden`]
+    MiddleHidden,
+}
+
+/// The [`Symbols`] of a segment, where each absent field takes its [default][`Symbols::default`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct SymbolsConfig {
+    pub success_symbol: Option<String>,
+    pub success_style: Option<StyleConfig>,
+    pub error_symbol: Option<String>,
+    pub error_style: Option<StyleConfig>,
+}
+impl From<&SymbolsConfig> for Symbols {
+    fn from(symbols: &SymbolsConfig) -> Self {
+        let default = Symbols::default();
+        Symbols {
+            success_symbol: symbols
+                .success_symbol
+                .clone()
+                .unwrap_or(default.success_symbol),
+            success_style_change: symbols
+                .success_style
+                .as_ref()
+                .map_or(default.success_style_change, StyleChange::from),
+            error_symbol: symbols.error_symbol.clone().unwrap_or(default.error_symbol),
+            error_style_change: symbols
+                .error_style
+                .as_ref()
+                .map_or(default.error_style_change, StyleChange::from),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_show_code() -> When {
+    When::OnError
+}
+
+fn default_duration_format() -> DurationFormat {
+    DurationFormat::SkipHighAndLowZeros
+}
+
+fn default_precision() -> Precision {
+    Precision::Milliseconds
+}
+
+fn default_replacement() -> String {
+    String::from("…")
+}
+
+fn default_path_separator() -> String {
+    path::MAIN_SEPARATOR.to_string()
+}
+
+fn default_git_separator() -> String {
+    String::from(" ")
+}
+
+/// Error of [`PromptConfig::from_toml`] and [`PromptConfig::from_file`].
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The description could not be read.
+    Io(io::Error),
+    /// The description is not valid.
+    Parse(ParseError),
+}
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Parse(err) => Some(err),
+        }
+    }
+}
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read the prompt description: {}", err),
+            ConfigError::Parse(err) => write!(f, "invalid prompt description: {}", err),
+        }
+    }
+}
+
+/// A problem found while parsing a prompt description.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The description of the problem.
+    pub message: String,
+    /// The 1-based line and column where the problem was found, if known.
+    pub location: Option<(usize, usize)>,
+}
+impl ParseError {
+    fn new(source: &str, err: toml::de::Error) -> Self {
+        let location = err.span().map(|span| {
+            let before = &source[..span.start.min(source.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before
+                .rsplit('\n')
+                .next()
+                .map_or(0, |line| line.chars().count())
+                + 1;
+            (line, column)
+        });
+        Self {
+            message: String::from(err.message()),
+            location,
+        }
+    }
+}
+impl error::Error for ParseError {}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
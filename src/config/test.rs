@@ -0,0 +1,188 @@
+#[cfg(all(test, feature = "layout", feature = "not_styled_writer"))]
+mod prompt_config {
+
+    use crate::{
+        config::{ConfigError, Context, PromptConfig},
+        env::command_result::ExitCode,
+        layout::Rendered,
+    };
+    use std::{path::PathBuf, time::Duration};
+
+    fn render(config: &PromptConfig, context: &Context) -> (String, Option<String>) {
+        fn text(rendered: Rendered) -> String {
+            let mut writer = crate::writers::not_styled::NotStyledWriter::new(Vec::new());
+            rendered.write_to(&mut writer).unwrap();
+            String::from_utf8(writer.writer).unwrap()
+        }
+
+        let left = Rendered::render(|writer| config.build_left(context).render(writer));
+        let right = config
+            .build_right(context)
+            .map(|right| Rendered::render(|writer| right.render(writer)).unwrap());
+        (text(left.unwrap()), right.map(text))
+    }
+
+    #[test]
+    fn segments_are_built_from_the_context() {
+        let config = PromptConfig::from_toml(
+            r#"
+            separator = " "
+            suffix = { text = " $ ", style = { bold = true, foreground = 2 } }
+
+            [[segment]]
+            kind = "path"
+            mode = "short"
+            separator = "/"
+            abbreviate_home = false
+
+            [[segment]]
+            kind = "exit_code"
+            error_symbol = "x"
+            show_code = "always"
+            style = { background = [1, 2, 3] }
+
+            [right]
+            prefix = "["
+            suffix = "]"
+
+            [[right.segment]]
+            kind = "duration"
+            precision = "seconds"
+            threshold_ms = 1000
+            "#,
+        )
+        .unwrap();
+
+        let context = Context {
+            exit_code: Some(ExitCode(2)),
+            duration: Some(Duration::from_millis(62_500)),
+            current_dir: Some(PathBuf::from("a/b/c/d")),
+        };
+        assert_eq!(
+            render(&config, &context),
+            (String::from("a/…/d x 2 $ "), Some(String::from("[1m 2s]"))),
+        );
+
+        let context = Context {
+            duration: Some(Duration::from_millis(500)),
+            ..Context::default()
+        };
+        assert_eq!(
+            render(&config, &context),
+            (String::from(" $ "), Some(String::from("[]"))),
+        );
+    }
+
+    #[test]
+    fn parse_errors_are_located() {
+        let err = PromptConfig::from_toml("separator = \" \"\n\n[[segment]]\nkind = \"nope\"\n")
+            .unwrap_err();
+        match err {
+            ConfigError::Parse(err) => assert_eq!(err.location.map(|(line, _)| line), Some(4)),
+            err => panic!("unexpected error: {}", err),
+        }
+
+        let err = PromptConfig::from_toml("separator = \" \"\nsufix = \"$\"\n").unwrap_err();
+        match err {
+            ConfigError::Parse(err) => assert_eq!(err.location, Some((2, 1))),
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn styles_and_colors_accept_strings() {
+        use crate::{
+            config::StyleConfig,
+            styling::{Color, Color4Bit, StyleChange},
+        };
+
+        #[derive(serde::Deserialize)]
+        struct Styles {
+            table: StyleConfig,
+            spec: StyleConfig,
+        }
+
+        let styles: Styles = toml::from_str(
+            r##"
+            table = { foreground = "bright-green", background = "#102030", bold = true }
+            spec = "bold fg:bright-green bg:rgb(16, 32, 48)"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(styles.table, styles.spec);
+        assert_eq!(
+            StyleChange::from(&styles.spec),
+            crate::style_change! {
+                foreground: Color::Color4Bit(Color4Bit::BRIGHT_GREEN),
+                background: Color::RGB(16, 32, 48),
+                bold: true,
+            }
+        );
+
+        let err =
+            PromptConfig::from_toml("separator = { text = \"|\", style = \"bold fg:nope\" }\n")
+                .unwrap_err();
+        assert!(err.to_string().contains("unknown color `nope`"), "{}", err);
+    }
+}
+
+mod colocated_workspace {
+    use crate::{
+        config::colocated_workspace,
+        env::git::{cli::open_repo, test::git},
+    };
+    use std::fs;
+
+    #[test]
+    fn leaves_out_outer_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".jj")).unwrap();
+        fs::create_dir_all(root.join("nested/src")).unwrap();
+        for repo in [root, &root.join("nested")] {
+            git(repo, &["init", "--quiet"]);
+        }
+
+        let colocated = open_repo(root).unwrap();
+        let workspace = colocated_workspace(&colocated, root).unwrap();
+        assert_eq!(workspace.root(), root);
+
+        // A plain repository under the workspace, as in a home directory managed with jj.
+        let nested = open_repo(&root.join("nested/src")).unwrap();
+        assert!(colocated_workspace(&nested, &root.join("nested/src")).is_none());
+    }
+
+    #[test]
+    fn compares_against_the_work_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        // The git directory is elsewhere, and `.git` is a file pointing to it.
+        let separate = root.join("separate");
+        fs::create_dir_all(separate.join(".jj")).unwrap();
+        git(
+            root,
+            &[
+                "init",
+                "--quiet",
+                "--separate-git-dir",
+                "separate.git",
+                "separate",
+            ],
+        );
+        let repo = open_repo(&separate).unwrap();
+        let workspace = colocated_workspace(&repo, &separate).unwrap();
+        assert_eq!(workspace.root(), separate);
+
+        let linked = root.join("linked");
+        git(
+            &separate,
+            &["commit", "--allow-empty", "--quiet", "-m", "first"],
+        );
+        git(&separate, &["worktree", "add", "--quiet", "../linked"]);
+        fs::create_dir_all(linked.join(".jj")).unwrap();
+        let repo = open_repo(&linked).unwrap();
+        let workspace = colocated_workspace(&repo, &linked).unwrap();
+        assert_eq!(workspace.root(), linked);
+    }
+}
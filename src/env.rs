@@ -6,9 +6,23 @@ pub mod access_rights;
 #[cfg(feature = "env-command_result")]
 pub mod command_result;
 
-#[cfg(feature = "env-git")]
+#[cfg(any(
+    feature = "env-git-types",
+    feature = "env-hg",
+    feature = "env-jj",
+    feature = "env-vcs",
+))]
+pub mod error;
+
+#[cfg(feature = "env-git-types")]
 pub mod git;
 
+#[cfg(feature = "env-hg")]
+pub mod hg;
+
+#[cfg(feature = "env-jj")]
+pub mod jj;
+
 #[cfg(feature = "env-path")]
 pub mod path;
 
@@ -17,3 +31,6 @@ pub mod python;
 
 #[cfg(feature = "env-session")]
 pub mod session;
+
+#[cfg(feature = "env-vcs")]
+pub mod vcs;
@@ -0,0 +1,36 @@
+//! The [`Error`] type of the modules querying version control systems.
+
+use std::{error, fmt};
+
+/// An error that occurred while querying a repository, categorized by a kind `K`
+/// specific to each module, such as `git::ErrorKind`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Error<K> {
+    kind: K,
+    message: String,
+}
+impl<K: Copy> Error<K> {
+    /// Creates an error of the category `kind`, described by `message`.
+    pub fn new(kind: K, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// The category of this error.
+    pub fn kind(&self) -> K {
+        self.kind
+    }
+
+    /// The description of this error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl<K> fmt::Display for Error<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+impl<K: fmt::Debug> error::Error for Error<K> {}
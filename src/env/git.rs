@@ -1,44 +1,296 @@
 //! Utilities for querying and representing information about a [git] repository.
 //!
+//! The information is represented by types independent of how it is queried.
+//! The queries are implemented by backends, each behind its own feature,
+//! and every backend's repository type implements the [`Repository`] trait:
+//!
+//! - [`git2`] (feature `env-git-git2`) uses [libgit2] in-process;
+//! - [`cli`] (feature `env-git-cli`) runs the [`git`][git] executable and parses its output,
+//!   and so supports everything the installed git does, such as the [file system monitor];
+//! - [`gix`] (feature `env-git-gix`) uses [gitoxide] in-process, without any C dependencies.
+//!
 //! [git]: https://git-scm.com/
+//! [libgit2]: https://libgit2.org/
+//! [gitoxide]: https://github.com/GitoxideLabs/gitoxide
+//! [file system monitor]: https://git-scm.com/docs/git-config#Documentation/git-config.txt-corefsmonitor
+
+#[cfg(test)]
+pub(crate) mod test;
+
+#[cfg(feature = "env-git-cli")]
+pub mod cli;
+
+#[cfg(feature = "env-git-git2")]
+pub mod git2;
+
+#[cfg(feature = "env-git-gix")]
+pub mod gix;
 
-use git2::{
-    Branch, Error, ErrorClass, ErrorCode, Oid, Repository, RepositoryOpenFlags, Status,
-    StatusOptions,
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use std::{path::Path, str};
 
-/// Finds and [opens][`Repository::open`] a repository.
+/// A git repository whose information can be queried.
+pub trait Repository {
+    /// The repository's [git directory][`$GIT_DIR`].
+    ///
+    /// [`$GIT_DIR`]: https://git-scm.com/docs/gitglossary#def_git_directory
+    fn git_dir(&self) -> &Path;
+
+    /// The repository's [working tree], or [`None`] if the repository is bare.
+    ///
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    fn work_dir(&self) -> Option<&Path>;
+
+    /// Gets the information about the repository's [HEAD].
+    ///
+    /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+    fn query_head(&self) -> Result<Head, Error>;
+
+    /// Gets the branch that `branch` is pushed to by `git push`, as in `branch@{push}`,
+    /// which differs from its upstream when pushing to another remote
+    /// with `branch.<name>.pushRemote` or `remote.pushDefault`.
+    ///
+    /// `branch` is the short name of a local branch, as in `main`.
+    fn query_push_target(&self, branch: &str) -> Result<Upstream, Error>;
+
+    /// Gets the fetch URL of the [remote] named `remote`,
+    /// or [`None`] if there is no such remote.
+    ///
+    /// [remote]: https://git-scm.com/docs/gitglossary#def_remote
+    fn query_remote_url(&self, remote: &str) -> Result<Option<RemoteUrl>, Error>;
+
+    /// Gets the information about the commit [HEAD] points to,
+    /// or [`None`] if HEAD is unborn.
+    ///
+    /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+    fn query_last_commit(&self) -> Result<Option<LastCommit>, Error>;
+
+    /// Gets the repository's [stashes][`Stash`], the most recent first, as in `git stash list`.
+    fn query_stashes(&self) -> Result<Vec<Stash>, Error>;
+
+    /// Gets the number of the repository's [stashes][`Stash`],
+    /// only counting those created on `branch` if it is given.
+    ///
+    /// `branch` is the short name of a local branch, as in `main`.
+    fn query_stash_count(&self, branch: Option<&str>) -> Result<usize, Error> {
+        Ok(count_stashes(&self.query_stashes()?, branch))
+    }
+
+    /// Gets the names by which a commit can be described, usually a [detached HEAD]'s target.
+    ///
+    /// Finding the [nearest branch][`CommitDescription::nearest_branch`] walks the history
+    /// of every local branch that contains the commit,
+    /// so it can be slow in repositories with many branches.
+    ///
+    /// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+    fn query_commit_description(&self, id: ObjectId) -> Result<CommitDescription, Error>;
+
+    /// Gets the [summary][`StatusSummary`] of the repository's [status]
+    /// within some [`StatusLimits`].
+    ///
+    /// When the [time budget][`StatusLimits::time_budget`] runs out,
+    /// an empty and [incomplete][`StatusSummary::incomplete`] summary is returned.
+    /// When the [file count limit][`StatusLimits::max_files`] is hit,
+    /// the files counted so far are returned in an incomplete summary.
+    ///
+    /// [status]: https://git-scm.com/docs/git-status
+    fn query_status_summary(&self, limits: &StatusLimits) -> Result<StatusSummary, Error>;
+
+    /// Gets a [detailed summary][`DetailedStatusSummary`] of the repository's [status],
+    /// which, unlike [`query_status_summary`][`Self::query_status_summary`],
+    /// tells apart renamed, copied and typechanged files and untracked and ignored files.
+    ///
+    /// [status]: https://git-scm.com/docs/git-status
+    fn query_detailed_status_summary(
+        &self,
+        options: &DetailedStatusOptions,
+    ) -> Result<DetailedStatusSummary, Error>;
+
+    /// Gets the [line counts][`DiffStats`] of the changes in the repository's
+    /// [staging area] and [working tree], like those of `git diff --shortstat`.
+    ///
+    /// Binary files and files larger than `max_size` bytes are left out,
+    /// so that huge or generated files do not stall the query,
+    /// and so are untracked files.
+    ///
+    /// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    fn query_diff_stats(&self, max_size: Option<u64>) -> Result<DiffStats, Error>;
+
+    /// Gets the [superproject] of the repository, if it is a [submodule].
+    ///
+    /// The repository is only taken as a submodule when the repository containing
+    /// its working tree records it as a submodule, so nested repositories that are
+    /// not submodules are left out.
+    ///
+    /// [superproject]: https://git-scm.com/docs/gitglossary#def_superproject
+    /// [submodule]: https://git-scm.com/docs/gitglossary#def_submodule
+    fn query_superproject(&self) -> Result<Option<Superproject>, Error>;
+
+    /// Gets the [summary][`SubmoduleSummary`] of the changes in the repository's [submodules].
+    ///
+    /// Each submodule is inspected as if by `git status`, which can be slow
+    /// when there are many of them.
+    ///
+    /// [submodules]: https://git-scm.com/docs/gitglossary#def_submodule
+    fn query_submodule_summary(&self) -> Result<SubmoduleSummary, Error>;
+
+    /// Gets the mode of the [sparse checkout] of the repository's [working tree],
+    /// or [`None`] if the whole tree is checked out.
+    ///
+    /// [sparse checkout]: https://git-scm.com/docs/git-sparse-checkout
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    fn query_sparse_checkout(&self) -> Result<Option<SparseCheckout>, Error>;
+
+    /// Gets the [summary][`LfsSummary`] of the files managed by [Git LFS]
+    /// in the repository's [working tree].
+    ///
+    /// The attributes of every tracked file are looked up,
+    /// then the files with the `filter=lfs` or `lockable` attributes are looked at
+    /// and the small ones of the former read.
+    ///
+    /// [Git LFS]: https://git-lfs.com/
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    fn query_lfs_summary(&self) -> Result<LfsSummary, Error>;
+
+    /// Gets the [boundary][`Shallow`] of the repository's history if it is a [shallow] clone.
+    ///
+    /// See [`Shallow::from_git_dir`].
+    ///
+    /// [shallow]: https://git-scm.com/docs/gitglossary#def_shallow_repository
+    fn query_shallow(&self) -> Option<Shallow> {
+        Shallow::from_git_dir(self.git_dir())
+    }
+
+    /// Gets the information about the repository's [working tree] if it is a linked one.
+    ///
+    /// See [`LinkedWorktree::from_git_dir`].
+    ///
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    fn query_linked_worktree(&self) -> Option<LinkedWorktree> {
+        LinkedWorktree::from_git_dir(self.git_dir())
+    }
+
+    /// Gets the [operation][`Operation`] in progress in the repository, if any.
+    ///
+    /// See [`Operation::from_git_dir`].
+    fn query_operation(&self) -> Option<Operation> {
+        Operation::from_git_dir(self.git_dir())
+    }
+}
+
+/// Finds and opens a repository with [libgit2].
 ///
-/// The search is done as git would from `dir`.
-pub fn open_repo(dir: &Path) -> Result<Repository, Error> {
-    Repository::open_ext(dir, RepositoryOpenFlags::FROM_ENV, None::<&Path>)
+/// [libgit2]: https://libgit2.org/
+#[cfg(feature = "env-git-git2")]
+#[deprecated(note = "use `env::git::git2::open_repo` instead")]
+pub fn open_repo(dir: &Path) -> Result<::git2::Repository, Error> {
+    git2::open_repo(dir)
 }
 
 /// Gets the information about a repository's [HEAD].
 ///
 /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
-pub fn query_head(repo: &Repository) -> Result<Head, Error> {
-    Head::from_repo(repo)
+#[cfg(feature = "env-git-git2")]
+#[deprecated(note = "use `env::git::git2::query_head` instead")]
+pub fn query_head(repo: &::git2::Repository) -> Result<Head, Error> {
+    git2::query_head(repo)
 }
 
 /// Gets the [summary][`StatusSummary`] of a repository's [status].
 ///
 /// [status]: https://git-scm.com/docs/git-status
-pub fn query_status_summary(repo: &Repository) -> Result<StatusSummary, Error> {
-    StatusSummary::from_repo(repo)
+#[cfg(feature = "env-git-git2")]
+#[deprecated(note = "use `env::git::git2::query_status_summary` instead")]
+pub fn query_status_summary(repo: &::git2::Repository) -> Result<StatusSummary, Error> {
+    git2::query_status_summary(repo)
 }
 
-/// Gets the number of [stashes][stash] in a repository's.
+/// Gets the number of [stashes][stash] in a repository.
 ///
 /// [stash]: https://git-scm.com/docs/gitglossary#def_stash
-pub fn query_stash_count(repo: &mut Repository) -> Result<usize, Error> {
-    let mut count = 0;
-    repo.stash_foreach(|_, _, _| {
-        count += 1;
-        true
-    })?;
-    Ok(count)
+#[cfg(feature = "env-git-git2")]
+#[deprecated(note = "use `env::git::git2::query_stash_count` instead")]
+pub fn query_stash_count(repo: &mut ::git2::Repository) -> Result<usize, Error> {
+    git2::query_stash_count(repo, None)
+}
+
+/// The name of a git object: a SHA-1 or SHA-256 hash.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId {
+    bytes: [u8; 32],
+    len: u8,
+}
+impl ObjectId {
+    /// Creates an [`ObjectId`] from the 20 bytes of a SHA-1 or the 32 bytes of a SHA-256 hash.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 20 && bytes.len() != 32 {
+            return None;
+        }
+        let mut id = Self {
+            bytes: [0; 32],
+            len: bytes.len() as u8,
+        };
+        id.bytes[..bytes.len()].copy_from_slice(bytes);
+        Some(id)
+    }
+
+    /// Creates an [`ObjectId`] from the hexadecimal representation of a hash.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.len().is_multiple_of(2) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// The bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Debug for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObjectId({})", self)
+    }
+}
+
+/// An error that occurred while querying a repository.
+pub type Error = super::error::Error<ErrorKind>;
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::new(ErrorKind::Io, err.to_string())
+    }
+}
+
+/// The category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The repository or an object in it does not exist.
+    NotFound,
+    /// An I/O operation failed.
+    Io,
+    /// A git command exited unsuccessfully.
+    Command,
+    /// A git command wrote something that could not be understood.
+    InvalidOutput,
+    /// The backend failed for another reason.
+    Backend,
 }
 
 /// Information about a repository's [HEAD].
@@ -52,20 +304,18 @@ pub enum Head {
     Branch {
         /// The name of the branch.
         name: String,
-        /// The count of how many commits the branch is ahead and behind its
-        /// [upstream][upstream branch].
+        /// The branch's [upstream][upstream branch].
         ///
-        /// This is [`None`] if the branch has no upstream,
-        /// and [`Err`] if an error occurs while getting the information.
+        /// This is [`Err`] if an error occurs while getting the information.
         ///
         /// [upstream branch]: https://git-scm.com/docs/gitglossary#def_upstream_branch
-        upstream: Result<Option<AheadBehind>, Error>,
+        upstream: Result<Upstream, Error>,
     },
     /// HEAD is in [detached][detached HEAD] state.
     ///
     /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
     /// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
-    Commit(Oid),
+    Commit(ObjectId),
     /// [HEAD] points to a nonexisting target.
     ///
     /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
@@ -80,46 +330,170 @@ pub enum Head {
         target: String,
     },
 }
+
+#[cfg(feature = "env-git-git2")]
 impl Head {
     /// Gets the information about a repository's [HEAD].
     ///
     /// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
-    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
-        match repo.find_reference("HEAD") {
-            Ok(head) => match head.symbolic_target_bytes() {
-                Some(target) => match str::from_utf8(target) {
-                    Ok(target) => match repo.find_reference(target) {
-                        Ok(reference) => {
-                            let branch = Branch::wrap(reference);
-                            Ok(Head::Branch {
-                                name: String::from_utf8_lossy(branch.name_bytes()?).into_owned(),
-                                upstream: AheadBehind::from_branch(repo, branch),
-                            })
-                        }
-                        Err(err) if err.code() == ErrorCode::NotFound => Ok(Head::Unborn {
-                            target: String::from(target),
-                        }),
-                        Err(err) => Err(err),
-                    },
-                    Err(_) => Err(Error::new(
-                        ErrorCode::GenericError,
-                        ErrorClass::Reference,
-                        "could not decode HEAD's symbolic target.",
-                    )),
-                },
-                None => match head.target() {
-                    Some(target) => {
-                        let commit = repo.find_commit(target)?;
-                        Ok(Head::Commit(commit.id()))
-                    }
-                    None => Err(Error::new(
-                        ErrorCode::GenericError,
-                        ErrorClass::Reference,
-                        "HEAD is neither direct nor symbolic.",
-                    )),
-                },
-            },
-            Err(err) => Err(err),
+    #[deprecated(note = "use `env::git::git2::query_head` instead")]
+    pub fn from_repo(repo: &::git2::Repository) -> Result<Self, Error> {
+        git2::query_head(repo)
+    }
+}
+
+/// The information about the last commit of a branch, usually the one [HEAD] points to.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LastCommit {
+    /// The commit's hash.
+    pub id: ObjectId,
+    /// The time the commit was committed,
+    /// which, unlike the time it was authored, changes when it is amended or rebased.
+    pub time: SystemTime,
+    /// The name of the commit's author.
+    pub author: String,
+    /// The email of the commit's author.
+    pub author_email: String,
+    /// The first paragraph of the commit message, joined into a single line.
+    pub summary: String,
+}
+
+/// A [stash], as read from the `refs/stash` [reflog].
+///
+/// [stash]: https://git-scm.com/docs/gitglossary#def_stash
+/// [reflog]: https://git-scm.com/docs/gitglossary#def_reflog
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stash {
+    /// The stash's position in the stash list, as in `stash@{index}`,
+    /// where `0` is the most recent stash.
+    pub index: usize,
+    /// The hash of the stash's commit.
+    pub id: ObjectId,
+    /// The stash's message, as in `WIP on main: 1234567 Commit summary`.
+    pub message: String,
+    /// The short name of the branch the stash was created on,
+    /// or [`None`] if HEAD was detached.
+    pub branch: Option<String>,
+    /// The time the stash was created.
+    pub time: SystemTime,
+}
+
+impl Stash {
+    /// Gets the branch a stash was created on from its message,
+    /// which `git stash` writes as `WIP on <branch>: ...` or `On <branch>: ...`,
+    /// with `(no branch)` in place of a detached HEAD.
+    fn branch_from_message(message: &str) -> Option<String> {
+        let rest = message
+            .strip_prefix("WIP on ")
+            .or_else(|| message.strip_prefix("On "))?;
+        let (branch, _) = rest.split_once(": ").unwrap_or((rest, ""));
+        (branch != "(no branch)").then(|| branch.to_owned())
+    }
+
+    /// Creates a stash from the parts of its entry in the `refs/stash` reflog,
+    /// whose time is a count of seconds since the Unix epoch, as git stores it.
+    ///
+    /// The [branch][`Stash::branch`] is read from the message.
+    pub fn from_reflog(index: usize, id: ObjectId, message: String, seconds: i64) -> Stash {
+        Stash {
+            index,
+            id,
+            branch: Stash::branch_from_message(&message),
+            message,
+            time: unix_time(seconds),
+        }
+    }
+}
+
+/// Counts the stashes created on `branch`, or all of them if it is [`None`].
+fn count_stashes(stashes: &[Stash], branch: Option<&str>) -> usize {
+    stashes
+        .iter()
+        .filter(|stash| branch.is_none_or(|branch| stash.branch.as_deref() == Some(branch)))
+        .count()
+}
+
+/// Converts a count of seconds since the [Unix epoch][`UNIX_EPOCH`], as git stores times.
+fn unix_time(seconds: i64) -> SystemTime {
+    let duration = Duration::from_secs(seconds.unsigned_abs());
+    if seconds < 0 {
+        UNIX_EPOCH - duration
+    } else {
+        UNIX_EPOCH + duration
+    }
+}
+
+/// The names by which a commit can be described, from the most to the least specific.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommitDescription {
+    /// The described commit.
+    pub id: ObjectId,
+    /// A [tag] pointing at the commit, if any.
+    ///
+    /// [tag]: https://git-scm.com/docs/gitglossary#def_tag
+    pub tag: Option<String>,
+    /// The commit described relative to the most recent tag reachable from it,
+    /// as in `v1.2.3-4-gabcdef0`, or [`None`] if no tag is reachable from it.
+    ///
+    /// See [`git describe --tags`](https://git-scm.com/docs/git-describe).
+    pub describe: Option<String>,
+    /// The local branch containing the commit closest to its tip, if any.
+    pub nearest_branch: Option<NearestBranch>,
+}
+
+/// A branch containing a commit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NearestBranch {
+    /// The name of the branch.
+    pub name: String,
+    /// The number of commits between the commit and the branch's tip.
+    pub distance: usize,
+}
+
+/// The state of a branch's [upstream][upstream branch],
+/// or of the branch it is pushed to, which is usually the same.
+///
+/// [upstream branch]: https://git-scm.com/docs/gitglossary#def_upstream_branch
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Upstream {
+    /// The branch has no upstream.
+    NotConfigured,
+    /// The upstream is configured, but its [remote-tracking branch] does not exist,
+    /// usually because it was deleted on the remote and then pruned.
+    ///
+    /// [remote-tracking branch]: https://git-scm.com/docs/gitglossary#def_remote_tracking_branch
+    Gone {
+        /// The short name of the upstream's remote-tracking branch, as in `origin/main`.
+        name: String,
+    },
+    /// The upstream's [remote-tracking branch] exists.
+    ///
+    /// [remote-tracking branch]: https://git-scm.com/docs/gitglossary#def_remote_tracking_branch
+    Present {
+        /// The short name of the upstream's remote-tracking branch, as in `origin/main`.
+        name: String,
+        /// The count of how many commits the branch is ahead and behind the upstream.
+        ahead_behind: AheadBehind,
+    },
+}
+
+impl Upstream {
+    /// The short name of the upstream's remote-tracking branch, if it is configured.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Upstream::NotConfigured => None,
+            Upstream::Gone { name } | Upstream::Present { name, .. } => Some(name),
+        }
+    }
+
+    /// The count of how many commits the branch is ahead and behind the upstream,
+    /// if it is [present][`Upstream::Present`].
+    pub fn ahead_behind(&self) -> Option<&AheadBehind> {
+        match self {
+            Upstream::Present { ahead_behind, .. } => Some(ahead_behind),
+            _ => None,
         }
     }
 }
@@ -132,22 +506,122 @@ pub struct AheadBehind {
     pub ahead: usize,
     pub behind: usize,
 }
+
+#[cfg(feature = "env-git-git2")]
 impl AheadBehind {
     /// Gets the count of how many commits a branch is ahead and behind its
-    /// [upstream][upstream branch].
+    /// [upstream][upstream branch], or [`None`] if it has none or it is gone.
     ///
     /// [upstream branch]: https://git-scm.com/docs/gitglossary#def_upstream_branch
-    pub fn from_branch(repo: &Repository, branch: Branch) -> Result<Option<AheadBehind>, Error> {
-        match branch.upstream() {
-            Ok(upstream) => {
-                let (ahead, behind) = repo.graph_ahead_behind(
-                    branch.get().peel_to_commit()?.id(),
-                    upstream.get().peel_to_commit()?.id(),
-                )?;
-                Ok(Some(AheadBehind { ahead, behind }))
+    #[deprecated(note = "use `env::git::git2::query_upstream` instead")]
+    pub fn from_branch(
+        repo: &::git2::Repository,
+        branch: ::git2::Branch,
+    ) -> Result<Option<AheadBehind>, Error> {
+        match git2::query_upstream(repo, &branch)? {
+            Upstream::Present { ahead_behind, .. } => Ok(Some(ahead_behind)),
+            Upstream::NotConfigured | Upstream::Gone { .. } => Ok(None),
+        }
+    }
+}
+
+/// The URL of a [remote], split into its host and path.
+///
+/// [remote]: https://git-scm.com/docs/gitglossary#def_remote
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteUrl {
+    /// The URL as configured.
+    pub url: String,
+    /// The host name, in lowercase, without the user name or port.
+    ///
+    /// This is [`None`] for local paths and `file://` URLs.
+    pub host: Option<String>,
+    /// The path of the repository on the host, without its leading `/`.
+    pub path: String,
+    /// The kind of the host.
+    pub host_kind: HostKind,
+}
+
+impl RemoteUrl {
+    /// Parses a URL in any of the [forms git accepts][urls]:
+    /// `scheme://[user@]host[:port]/path`, the scp-like `[user@]host:path`, and local paths.
+    ///
+    /// [urls]: https://git-scm.com/docs/git-clone#_git_urls
+    pub fn parse(url: &str) -> Self {
+        let (host, path) = match url.split_once("://") {
+            Some(("file", path)) => (None, path),
+            Some((_, rest)) => {
+                let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+                (Some(strip_port(strip_user(authority))), path)
             }
-            Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
-            Err(err) => Err(err),
+            // The scp-like syntax is only recognized when there is no slash before the colon,
+            // and a single letter before it is a Windows drive instead.
+            None => match url.split_once(':') {
+                Some((host, path)) if !host.contains('/') && host.len() > 1 => {
+                    (Some(strip_user(host)), path)
+                }
+                _ => (None, url),
+            },
+        };
+        let host = host.map(str::to_ascii_lowercase);
+        Self {
+            url: url.to_owned(),
+            host_kind: match host.as_deref() {
+                None => HostKind::Local,
+                Some(host) => HostKind::from_host(host),
+            },
+            path: match host {
+                Some(_) => path.trim_start_matches('/').to_owned(),
+                None => path.to_owned(),
+            },
+            host,
+        }
+    }
+}
+
+fn strip_user(authority: &str) -> &str {
+    authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host)
+}
+
+fn strip_port(authority: &str) -> &str {
+    match authority.strip_prefix('[') {
+        // An IPv6 address, as in `[::1]:22`.
+        Some(address) => address.split(']').next().unwrap_or(address),
+        None => authority
+            .split_once(':')
+            .map_or(authority, |(host, _)| host),
+    }
+}
+
+/// The kind of host of a [`RemoteUrl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HostKind {
+    /// [GitHub](https://github.com/).
+    GitHub,
+    /// [GitLab](https://gitlab.com/), including self-hosted instances on a `gitlab.` subdomain.
+    GitLab,
+    /// [Bitbucket](https://bitbucket.org/).
+    Bitbucket,
+    /// Any other host.
+    SelfHosted,
+    /// A repository on the local file system.
+    Local,
+}
+
+impl HostKind {
+    /// Classifies a host name.
+    pub fn from_host(host: &str) -> Self {
+        let is = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+        if is("github.com") {
+            HostKind::GitHub
+        } else if is("gitlab.com") || host.starts_with("gitlab.") {
+            HostKind::GitLab
+        } else if is("bitbucket.org") {
+            HostKind::Bitbucket
+        } else {
+            HostKind::SelfHosted
         }
     }
 }
@@ -169,68 +643,62 @@ pub struct StatusSummary {
     ///
     /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
     pub conflicted: usize,
+    /// Whether a [limit][`StatusLimits`] was hit before all files were counted,
+    /// in which case the counts are lower bounds.
+    ///
+    /// An incomplete summary [without changes][`Self::any_changes`] means the status is unknown.
+    pub incomplete: bool,
 }
+
 impl StatusSummary {
+    /// Tell if the status summary indicates the presence of changes, staged or not.
+    pub fn any_changes(&self) -> bool {
+        self.conflicted != 0 || self.working_tree.any_changes() || self.staging.any_changes()
+    }
+
     /// Gets the [summary][`StatusSummary`] of a repository's [status].
     ///
     /// [status]: https://git-scm.com/docs/git-status
-    pub fn from_repo(repo: &Repository) -> Result<Self, Error> {
-        let mut working_tree = ChangeSummary::default();
-        let mut staging = ChangeSummary::default();
-        let mut conflicted = 0;
-
-        for status in repo
-            .statuses(Some(
-                StatusOptions::new()
-                    .include_untracked(true)
-                    .renames_from_rewrites(false)
-                    .renames_head_to_index(false)
-                    .renames_index_to_workdir(false),
-            ))?
-            .iter()
-        {
-            let status = status.status();
-
-            if status.is_empty() {
-                continue;
-            }
-
-            if status.is_index_new() {
-                staging.added += 1;
-            } else if status.is_index_deleted() {
-                staging.deleted += 1;
-            } else if (Status::INDEX_MODIFIED | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE)
-                .intersects(status)
-            {
-                staging.modified += 1;
-            }
-
-            if status.is_wt_new() {
-                working_tree.added += 1;
-            } else if status.is_wt_deleted() {
-                working_tree.deleted += 1;
-            } else if (Status::WT_MODIFIED | Status::WT_RENAMED | Status::WT_TYPECHANGE)
-                .intersects(status)
-            {
-                working_tree.modified += 1;
-            }
-
-            if status.is_conflicted() {
-                conflicted += 1;
-            }
-        }
-
-        Ok(Self {
-            working_tree,
-            staging,
-            conflicted,
-        })
+    #[cfg(feature = "env-git-git2")]
+    #[deprecated(note = "use `env::git::git2::query_status_summary` instead")]
+    pub fn from_repo(repo: &::git2::Repository) -> Result<Self, Error> {
+        git2::query_status_summary(repo)
     }
+}
 
-    /// Tell if the status summary indicates the presence of changes, staged or not.
-    pub fn any_changes(&self) -> bool {
-        self.conflicted != 0 || self.working_tree.any_changes() || self.staging.any_changes()
-    }
+/// Limits to the work done by [`Repository::query_status_summary`].
+///
+/// The [default][`Default`] limits impose no limit
+/// and take from the repository's configuration whether to count untracked files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusLimits {
+    /// The maximum time to wait for the status.
+    pub time_budget: Option<Duration>,
+    /// The maximum number of changed files to count.
+    pub max_files: Option<usize>,
+    /// Which untracked files to count.
+    pub untracked_files: UntrackedFiles,
+}
+
+/// Which untracked files to count in a [status].
+///
+/// [status]: https://git-scm.com/docs/git-status
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum UntrackedFiles {
+    /// As told by the `status.showUntrackedFiles` configuration, defaulting to [`Normal`][`Self::Normal`].
+    #[default]
+    FromConfig,
+    /// None, which avoids scanning the working tree for untracked files.
+    No,
+    /// Untracked files, counting each untracked directory as a single file.
+    Normal,
+    /// Untracked files, including those inside untracked directories.
+    All,
 }
 
 /// A summary of the changes in either a [working tree] or a [staging area].
@@ -252,3 +720,485 @@ impl ChangeSummary {
         self.added != 0 || self.modified != 0 || self.deleted != 0
     }
 }
+
+/// A detailed summary of a repository's [status].
+///
+/// [status]: https://git-scm.com/docs/git-status
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DetailedStatusSummary {
+    /// The [summary][`DetailedChangeSummary`] of the changes in the [working tree]
+    /// to files in the [staging area].
+    ///
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    /// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+    pub working_tree: DetailedChangeSummary,
+    /// The [summary][`DetailedChangeSummary`] of the changes in the [staging area].
+    ///
+    /// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+    pub staging: DetailedChangeSummary,
+    /// The number of untracked files, as told by [`DetailedStatusOptions::untracked_files`].
+    pub untracked: usize,
+    /// The number of ignored files, when [`DetailedStatusOptions::ignored_files`] is set,
+    /// counting each ignored directory as a single file.
+    pub ignored: usize,
+    /// The number of files in the [working tree] with merge conflicts.
+    ///
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    pub conflicted: usize,
+}
+impl DetailedStatusSummary {
+    /// Tell if the summary indicates the presence of changes, staged or not,
+    /// or of untracked files.
+    ///
+    /// Ignored files are not changes.
+    pub fn any_changes(&self) -> bool {
+        self.untracked != 0
+            || self.conflicted != 0
+            || self.working_tree.any_changes()
+            || self.staging.any_changes()
+    }
+}
+impl From<&DetailedStatusSummary> for StatusSummary {
+    /// Folds the detailed counts into those of a [`StatusSummary`],
+    /// as [`Repository::query_status_summary`] would count them.
+    fn from(detailed: &DetailedStatusSummary) -> Self {
+        let mut summary = StatusSummary {
+            working_tree: ChangeSummary::from(&detailed.working_tree),
+            staging: ChangeSummary::from(&detailed.staging),
+            conflicted: detailed.conflicted,
+            incomplete: false,
+        };
+        summary.working_tree.added += detailed.untracked;
+        summary
+    }
+}
+
+/// A detailed summary of the changes in either a [working tree] or a [staging area].
+///
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+/// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DetailedChangeSummary {
+    /// The number of added files.
+    pub added: usize,
+    /// The number of files whose content or mode was modified.
+    pub modified: usize,
+    /// The number of deleted files.
+    pub deleted: usize,
+    /// The number of renamed files.
+    pub renamed: usize,
+    /// The number of copied files.
+    pub copied: usize,
+    /// The number of files whose type changed, such as from a regular file to a symbolic link.
+    pub typechanged: usize,
+}
+impl DetailedChangeSummary {
+    /// Tell if the summary indicates the presence of changes.
+    pub fn any_changes(&self) -> bool {
+        self.added != 0
+            || self.modified != 0
+            || self.deleted != 0
+            || self.renamed != 0
+            || self.copied != 0
+            || self.typechanged != 0
+    }
+}
+impl From<&DetailedChangeSummary> for ChangeSummary {
+    /// Counts renamed, copied and typechanged files as modified.
+    fn from(detailed: &DetailedChangeSummary) -> Self {
+        ChangeSummary {
+            added: detailed.added,
+            modified: detailed.modified + detailed.renamed + detailed.copied + detailed.typechanged,
+            deleted: detailed.deleted,
+        }
+    }
+}
+
+/// Options of [`Repository::query_detailed_status_summary`].
+///
+/// The [default][`Default`] options detect renames but not copies,
+/// take from the repository's configuration whether to count untracked files and
+/// how to compare submodules, and do not count ignored files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DetailedStatusOptions {
+    /// Which untracked files to count.
+    pub untracked_files: UntrackedFiles,
+    /// Whether to count ignored files.
+    pub ignored_files: bool,
+    /// How to detect renames and copies, or [`None`] not to.
+    ///
+    /// Without detection, a renamed file counts as a deleted file and an added one.
+    pub renames: Option<RenameDetection>,
+    /// How to compare submodules.
+    pub submodules: Submodules,
+}
+impl Default for DetailedStatusOptions {
+    fn default() -> Self {
+        Self {
+            untracked_files: UntrackedFiles::default(),
+            ignored_files: false,
+            renames: Some(RenameDetection::default()),
+            submodules: Submodules::default(),
+        }
+    }
+}
+
+/// How renamed and copied files are detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenameDetection {
+    /// The minimum similarity, as a percentage, for a deleted and an added file
+    /// to count as renamed, or an added file to count as a copy of another.
+    pub threshold: u8,
+    /// Whether to also detect copies.
+    pub copies: bool,
+}
+impl Default for RenameDetection {
+    /// Detects renames, but not copies, with git's default threshold of 50%.
+    fn default() -> Self {
+        Self {
+            threshold: 50,
+            copies: false,
+        }
+    }
+}
+
+/// How submodules are compared in a [status].
+///
+/// [status]: https://git-scm.com/docs/git-status
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Submodules {
+    /// As told by the `diff.ignoreSubmodules` configuration and each submodule's `ignore` setting.
+    #[default]
+    AsConfigured,
+    /// Not at all, as with `git status --ignore-submodules=all`, which saves scanning them.
+    Ignore,
+}
+
+/// The line counts of the changes in a repository,
+/// as [queried][`Repository::query_diff_stats`] from its [staging area] and [working tree].
+///
+/// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiffStats {
+    /// The changes between the [working tree] and the [staging area].
+    ///
+    /// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+    /// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+    pub working_tree: LineChanges,
+    /// The changes between HEAD and the [staging area].
+    ///
+    /// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+    pub staging: LineChanges,
+}
+impl DiffStats {
+    /// Tell if the stats indicate the presence of changes, staged or not.
+    pub fn any_changes(&self) -> bool {
+        self.working_tree.any_changes() || self.staging.any_changes()
+    }
+}
+
+/// The number of changed lines between two versions of some files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineChanges {
+    /// The number of inserted lines.
+    pub insertions: usize,
+    /// The number of deleted lines.
+    pub deletions: usize,
+}
+impl LineChanges {
+    /// Tell if any line was inserted or deleted.
+    pub fn any_changes(&self) -> bool {
+        self.insertions != 0 || self.deletions != 0
+    }
+}
+
+/// A [working tree] linked to a repository by [`git worktree add`],
+/// as opposed to the main working tree.
+///
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+/// [`git worktree add`]: https://git-scm.com/docs/git-worktree
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LinkedWorktree {
+    /// The name by which git refers to the working tree,
+    /// by default the name of its directory.
+    pub name: String,
+    /// The git directory shared by all the working trees of the repository.
+    pub common_dir: PathBuf,
+    /// The path of the main working tree,
+    /// or [`None`] if the repository is [bare](https://git-scm.com/docs/gitglossary#def_bare_repository).
+    pub main_worktree: Option<PathBuf>,
+}
+
+impl LinkedWorktree {
+    /// Gets the information about a linked working tree from its [git directory],
+    /// the same way git does.
+    ///
+    /// A linked working tree's git directory has a `commondir` file
+    /// pointing to the shared git directory.
+    /// The main working tree is taken to be the parent of the shared git directory
+    /// when this one is named `.git`, as it is in non-bare repositories.
+    ///
+    /// [git directory]: https://git-scm.com/docs/gitglossary#def_git_directory
+    pub fn from_git_dir(git_dir: &Path) -> Option<Self> {
+        let common_dir = fs::read_to_string(git_dir.join("commondir")).ok()?;
+        let common_dir = git_dir.join(common_dir.trim());
+        let common_dir = fs::canonicalize(&common_dir).unwrap_or(common_dir);
+        let main_worktree = match common_dir.file_name() {
+            Some(name) if name == ".git" => common_dir.parent().map(Path::to_owned),
+            _ => None,
+        };
+        Some(Self {
+            name: git_dir.file_name()?.to_string_lossy().into_owned(),
+            common_dir,
+            main_worktree,
+        })
+    }
+}
+
+/// The [superproject] of a [submodule].
+///
+/// [superproject]: https://git-scm.com/docs/gitglossary#def_superproject
+/// [submodule]: https://git-scm.com/docs/gitglossary#def_submodule
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Superproject {
+    /// The path of the superproject's working tree.
+    pub worktree: PathBuf,
+    /// The path of the submodule relative to the superproject's working tree.
+    pub path: PathBuf,
+    /// Whether the submodule's HEAD is not at the commit recorded in the superproject's
+    /// [staging area](https://git-scm.com/docs/gitglossary#def_index).
+    pub out_of_date: bool,
+}
+
+/// A summary of the changes in a repository's [submodules].
+///
+/// [submodules]: https://git-scm.com/docs/gitglossary#def_submodule
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubmoduleSummary {
+    /// The count of submodules whose HEAD is not at the commit recorded in the
+    /// [staging area](https://git-scm.com/docs/gitglossary#def_index).
+    pub out_of_date: usize,
+    /// The count of submodules with modified or untracked files.
+    pub modified: usize,
+}
+
+impl SubmoduleSummary {
+    /// Tell if any submodule is out of date or modified.
+    pub fn any_changes(&self) -> bool {
+        self.out_of_date != 0 || self.modified != 0
+    }
+}
+
+/// The boundary of a [shallow repository]'s history,
+/// past which the commits were left out when it was cloned or fetched.
+///
+/// [shallow repository]: https://git-scm.com/docs/gitglossary#def_shallow_repository
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shallow {
+    /// The commits that are present without their parents.
+    pub boundary: Vec<ObjectId>,
+}
+
+impl Shallow {
+    /// Reads the boundary from the `shallow` file of a [git directory],
+    /// or gets [`None`] if the repository's history is complete.
+    ///
+    /// For a [linked worktree][`LinkedWorktree`], the file is read from the shared git directory.
+    ///
+    /// [git directory]: https://git-scm.com/docs/gitglossary#def_git_directory
+    pub fn from_git_dir(git_dir: &Path) -> Option<Self> {
+        let common_dir = LinkedWorktree::from_git_dir(git_dir)
+            .map_or_else(|| git_dir.to_owned(), |worktree| worktree.common_dir);
+        let content = fs::read_to_string(common_dir.join("shallow")).ok()?;
+        let boundary = content
+            .lines()
+            .filter_map(|line| ObjectId::from_hex(line.trim()))
+            .collect::<Vec<_>>();
+        (!boundary.is_empty()).then_some(Self { boundary })
+    }
+}
+
+/// The mode of a [sparse checkout], in which only some of the tracked files
+/// are present in the working tree.
+///
+/// [sparse checkout]: https://git-scm.com/docs/git-sparse-checkout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SparseCheckout {
+    /// The checked out files are those in a set of directories,
+    /// as set by `git sparse-checkout set` by default.
+    Cone,
+    /// The checked out files are those matching a set of patterns
+    /// in the style of [gitignore](https://git-scm.com/docs/gitignore).
+    NonCone,
+}
+
+impl SparseCheckout {
+    /// Gets the mode from the values of `core.sparseCheckout` and `core.sparseCheckoutCone`.
+    pub fn from_config(enabled: bool, cone: bool) -> Option<Self> {
+        match (enabled, cone) {
+            (false, _) => None,
+            (true, true) => Some(SparseCheckout::Cone),
+            (true, false) => Some(SparseCheckout::NonCone),
+        }
+    }
+}
+
+/// The size under which every [Git LFS] pointer file is, as per its specification.
+///
+/// [Git LFS]: https://git-lfs.com/
+const LFS_POINTER_MAX_SIZE: u64 = 1024;
+
+/// A summary of the files managed by [Git LFS] in a working tree.
+///
+/// [Git LFS]: https://git-lfs.com/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LfsSummary {
+    /// The count of files whose content was not downloaded,
+    /// so that the working tree holds their pointer files instead.
+    pub pointers: usize,
+    /// The count of read-only files, which Git LFS makes of the lockable files
+    /// that are not locked by the user, who has to `git lfs lock` them before editing.
+    pub read_only: usize,
+}
+
+impl LfsSummary {
+    /// Tell if any file is a pointer file or read-only.
+    pub fn any_files(&self) -> bool {
+        self.pointers != 0 || self.read_only != 0
+    }
+
+    /// Looks at the tracked files, relative to `worktree` and expected to be regular files,
+    /// at `lfs_paths`, which have the `filter=lfs` attribute,
+    /// and at `lockable_paths`, which have the `lockable` attribute.
+    ///
+    /// The files missing from the working tree, as with a sparse checkout, are skipped.
+    pub fn from_tracked_files<P: AsRef<Path>, L: AsRef<Path>>(
+        worktree: &Path,
+        lfs_paths: impl IntoIterator<Item = P>,
+        lockable_paths: impl IntoIterator<Item = L>,
+    ) -> Self {
+        let metadata = |path: &Path| match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_file() => Some(metadata),
+            _ => None,
+        };
+
+        let mut summary = LfsSummary::default();
+        for path in lfs_paths {
+            let path = worktree.join(path);
+            if metadata(&path).is_some_and(|metadata| metadata.len() < LFS_POINTER_MAX_SIZE)
+                && fs::read(&path).is_ok_and(|content| {
+                    content.starts_with(b"version https://git-lfs.github.com/spec/")
+                })
+            {
+                summary.pointers += 1;
+            }
+        }
+        for path in lockable_paths {
+            if metadata(&worktree.join(path))
+                .is_some_and(|metadata| metadata.permissions().readonly())
+            {
+                summary.read_only += 1;
+            }
+        }
+        summary
+    }
+}
+
+/// An operation that stopped midway, waiting for the user to resolve conflicts,
+/// edit commits or mark commits as good or bad.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// A [merge](https://git-scm.com/docs/git-merge).
+    Merge,
+    /// A [rebase](https://git-scm.com/docs/git-rebase).
+    Rebase {
+        /// Whether the rebase is interactive (`git rebase -i`).
+        interactive: bool,
+        /// The commits applied so far, out of the commits to apply.
+        progress: Option<Progress>,
+        /// The name of the branch being rebased,
+        /// or [`None`] if the rebase started from a detached HEAD.
+        branch: Option<String>,
+    },
+    /// An [application of patches from a mailbox](https://git-scm.com/docs/git-am).
+    ApplyMailbox {
+        /// The patches applied so far, out of the patches to apply.
+        progress: Option<Progress>,
+    },
+    /// A [cherry-pick](https://git-scm.com/docs/git-cherry-pick).
+    CherryPick,
+    /// A [revert](https://git-scm.com/docs/git-revert).
+    Revert,
+    /// A [bisection](https://git-scm.com/docs/git-bisect).
+    Bisect,
+}
+
+impl Operation {
+    /// Gets the operation in progress in a repository from the files in its [git directory],
+    /// the same way git does.
+    ///
+    /// The details of the operation (its progress and the branch being rebased)
+    /// are read on a best-effort basis, and are left out when the files cannot be read.
+    ///
+    /// [git directory]: https://git-scm.com/docs/gitglossary#def_git_directory
+    pub fn from_git_dir(git_dir: &Path) -> Option<Self> {
+        let exists = |path: &str| git_dir.join(path).exists();
+        let read = |path: &str| {
+            fs::read_to_string(git_dir.join(path))
+                .ok()
+                .map(|content| content.trim().to_owned())
+        };
+        let progress = |current: &str, total: &str| {
+            Some(Progress {
+                current: read(current)?.parse().ok()?,
+                total: read(total)?.parse().ok()?,
+            })
+        };
+        let branch = |path: &str| {
+            read(path)
+                .filter(|head_name| head_name != "detached HEAD")
+                .map(|head_name| match head_name.strip_prefix("refs/heads/") {
+                    Some(name) => name.to_owned(),
+                    None => head_name,
+                })
+        };
+
+        if exists("rebase-merge") {
+            Some(Operation::Rebase {
+                interactive: exists("rebase-merge/interactive"),
+                progress: progress("rebase-merge/msgnum", "rebase-merge/end"),
+                branch: branch("rebase-merge/head-name"),
+            })
+        } else if exists("rebase-apply/rebasing") {
+            Some(Operation::Rebase {
+                interactive: false,
+                progress: progress("rebase-apply/next", "rebase-apply/last"),
+                branch: branch("rebase-apply/head-name"),
+            })
+        } else if exists("rebase-apply") {
+            Some(Operation::ApplyMailbox {
+                progress: progress("rebase-apply/next", "rebase-apply/last"),
+            })
+        } else if exists("MERGE_HEAD") {
+            Some(Operation::Merge)
+        } else if exists("CHERRY_PICK_HEAD") {
+            Some(Operation::CherryPick)
+        } else if exists("REVERT_HEAD") {
+            Some(Operation::Revert)
+        } else if exists("BISECT_LOG") {
+            Some(Operation::Bisect)
+        } else {
+            None
+        }
+    }
+}
+
+/// The progress of a multi-step [`Operation`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Progress {
+    /// The number of the current step, starting from 1.
+    pub current: usize,
+    /// The total number of steps.
+    pub total: usize,
+}
@@ -0,0 +1,1001 @@
+//! Queries implemented by running the [`git`][git] executable and parsing its output.
+//!
+//! Every command is run with `GIT_OPTIONAL_LOCKS=0`,
+//! so that querying a repository never blocks the user's own git commands.
+//!
+//! [git]: https://git-scm.com/docs/git
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    unix_time, AheadBehind, ChangeSummary, CommitDescription, DetailedChangeSummary,
+    DetailedStatusOptions, DetailedStatusSummary, DiffStats, Error, ErrorKind, Head, LastCommit,
+    LfsSummary, LineChanges, NearestBranch, ObjectId, RemoteUrl, SparseCheckout, Stash,
+    StatusLimits, StatusSummary, SubmoduleSummary, Submodules, Superproject, UntrackedFiles,
+    Upstream,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    io::Read,
+    panic,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// A repository queried through the `git` executable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Repository {
+    dir: PathBuf,
+    git_dir: PathBuf,
+    work_dir: Option<PathBuf>,
+}
+impl Repository {
+    /// The directory the repository was [opened][`open_repo`] from,
+    /// where every git command is run.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn git<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new("git");
+        command
+            .current_dir(&self.dir)
+            .env("GIT_OPTIONAL_LOCKS", "0")
+            .args(args);
+        command
+    }
+}
+
+/// Finds a repository with [`git rev-parse`](https://git-scm.com/docs/git-rev-parse).
+///
+/// The search is done by git from `dir`.
+pub fn open_repo(dir: &Path) -> Result<Repository, Error> {
+    let mut repo = Repository {
+        dir: dir.to_owned(),
+        git_dir: PathBuf::new(),
+        work_dir: None,
+    };
+    // `--show-toplevel` fails in bare repositories and in the git directory.
+    if let Ok(output) = run(repo.git(["rev-parse", "--absolute-git-dir", "--show-toplevel"])) {
+        let mut lines = lines(&output)?;
+        if let (Some(git_dir), Some(work_dir)) = (lines.next(), lines.next()) {
+            repo.git_dir = PathBuf::from(git_dir);
+            repo.work_dir = Some(PathBuf::from(work_dir));
+            return Ok(repo);
+        }
+    }
+    let output = run(repo.git(["rev-parse", "--absolute-git-dir"]))
+        .map_err(|err| Error::new(ErrorKind::NotFound, err.message()))?;
+    repo.git_dir = PathBuf::from(first_line(&output)?);
+    Ok(repo)
+}
+
+/// Gets the information about a repository's [HEAD] with `git symbolic-ref`,
+/// then `git for-each-ref` or `git rev-parse`, without looking at the files.
+///
+/// Use [`query_head_and_status_summary`] when the status is needed too.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_head(repo: &Repository) -> Result<Head, Error> {
+    let mut command = repo.git(["symbolic-ref", "-q", "HEAD"]);
+    let output = command.stdin(Stdio::null()).output()?;
+    match output.status.code() {
+        Some(0) => {
+            let target = first_line(&output.stdout)?.to_owned();
+            let output = run(repo.git([
+                "for-each-ref",
+                "--format=%(refname)%00%(upstream:short)%00%(upstream:track,nobracket)",
+                &target,
+            ]))?;
+            parse_head_ref(&output, target)
+        }
+        // git exits with 1 when HEAD is detached.
+        Some(1) => {
+            let output = run(repo.git(["rev-parse", "--verify", "HEAD"]))?;
+            let oid = first_line(&output)?;
+            Ok(Head::Commit(
+                ObjectId::from_hex(oid).ok_or_else(|| invalid_output("rev-parse", oid))?,
+            ))
+        }
+        _ => Err(command_failed(&command, &output.stderr)),
+    }
+}
+
+/// Gets the information about a repository's [HEAD] and the [summary][`StatusSummary`]
+/// of its [status] with a single `git status`.
+///
+/// If the [time budget][`StatusLimits::time_budget`] runs out, `git status` is killed
+/// and [`None`] is returned.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_head_and_status_summary(
+    repo: &Repository,
+    limits: &StatusLimits,
+) -> Result<Option<(Head, StatusSummary)>, Error> {
+    let command = repo.git(status_args(limits.untracked_files));
+    let output = match limits.time_budget {
+        Some(time_budget) => run_with_timeout(command, time_budget)?,
+        None => Some(run(command)?),
+    };
+    output
+        .map(|output| parse_status(&output, limits.max_files))
+        .transpose()
+}
+
+/// Gets the [summary][`StatusSummary`] of a repository's [status] within some [`StatusLimits`].
+///
+/// See [`Repository::query_status_summary`][`super::Repository::query_status_summary`].
+/// Unlike libgit2, git honors `core.untrackedCache` and `core.fsmonitor`.
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_status_summary(
+    repo: &Repository,
+    limits: &StatusLimits,
+) -> Result<StatusSummary, Error> {
+    Ok(match query_head_and_status_summary(repo, limits)? {
+        Some((_, status)) => status,
+        None => StatusSummary {
+            incomplete: true,
+            ..StatusSummary::default()
+        },
+    })
+}
+
+/// Gets the branch that a branch is pushed to by `git push`, as in `branch@{push}`,
+/// with `git for-each-ref`.
+///
+/// See [`Repository::query_push_target`][`super::Repository::query_push_target`].
+pub fn query_push_target(repo: &Repository, branch: &str) -> Result<Upstream, Error> {
+    let output = run(repo.git([
+        "for-each-ref",
+        "--format=%(push:short)%00%(push:track,nobracket)",
+        &format!("refs/heads/{}", branch),
+    ]))?;
+    parse_push_target(&output)
+}
+
+/// Gets the fetch URL of a [remote] with `git remote get-url`,
+/// or [`None`] if there is no such remote.
+///
+/// [remote]: https://git-scm.com/docs/gitglossary#def_remote
+pub fn query_remote_url(repo: &Repository, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+    let mut command = repo.git(["remote", "get-url", "--", remote]);
+    let output = command.stdin(Stdio::null()).output()?;
+    match output.status.code() {
+        Some(0) => Ok(Some(RemoteUrl::parse(first_line(&output.stdout)?))),
+        // git exits with 2 when there is no such remote.
+        Some(2) => Ok(None),
+        _ => Err(command_failed(&command, &output.stderr)),
+    }
+}
+
+/// Gets the information about the commit [HEAD] points to, or [`None`] if HEAD is unborn,
+/// with `git log`.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_last_commit(repo: &Repository) -> Result<Option<LastCommit>, Error> {
+    // `--ignore-missing` makes an unborn HEAD yield nothing instead of failing.
+    let output = run(repo.git([
+        "log",
+        "-1",
+        "--ignore-missing",
+        "--format=%H%x00%ct%x00%an%x00%ae%x00%s",
+        "HEAD",
+        "--",
+    ]))?;
+    let line = match lines(&output)?.next() {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    let mut fields = line.split('\0');
+    let mut field = || fields.next().ok_or_else(|| invalid_output("log", line));
+    Ok(Some(LastCommit {
+        id: ObjectId::from_hex(field()?).ok_or_else(|| invalid_output("log", line))?,
+        time: unix_time(field()?.parse().map_err(|_| invalid_output("log", line))?),
+        author: field()?.to_owned(),
+        author_email: field()?.to_owned(),
+        summary: field()?.to_owned(),
+    }))
+}
+
+/// Gets a repository's [stashes][`Stash`], the most recent first.
+///
+/// See [`Repository::query_stashes`][`super::Repository::query_stashes`].
+pub fn query_stashes(repo: &Repository) -> Result<Vec<Stash>, Error> {
+    // `%gs` is the reflog message, which names the branch the stash was created on.
+    let output = run(repo.git(["stash", "list", "--format=%H%x00%ct%x00%gs"]))?;
+    let stashes = lines(&output)?
+        .enumerate()
+        .map(|(index, line)| {
+            let mut fields = line.split('\0');
+            let mut field = || {
+                fields
+                    .next()
+                    .ok_or_else(|| invalid_output("stash list", line))
+            };
+            let id =
+                ObjectId::from_hex(field()?).ok_or_else(|| invalid_output("stash list", line))?;
+            let time = field()?
+                .parse()
+                .map_err(|_| invalid_output("stash list", line))?;
+            Ok(Stash::from_reflog(index, id, field()?.to_owned(), time))
+        })
+        .collect();
+    stashes
+}
+
+/// Gets the mode of the [sparse checkout] of a repository's working tree, if any,
+/// with `git config`.
+///
+/// See [`Repository::query_sparse_checkout`][`super::Repository::query_sparse_checkout`].
+///
+/// [sparse checkout]: https://git-scm.com/docs/git-sparse-checkout
+pub fn query_sparse_checkout(repo: &Repository) -> Result<Option<SparseCheckout>, Error> {
+    let mut command = repo.git([
+        "config",
+        "-z",
+        "--type=bool",
+        "--get-regexp",
+        r"^core\.sparsecheckout(cone)?$",
+    ]);
+    let output = command.stdin(Stdio::null()).output()?;
+    match output.status.code() {
+        Some(0) => parse_sparse_checkout(&output.stdout),
+        // git exits with 1 when neither setting is set.
+        Some(1) => Ok(None),
+        _ => Err(command_failed(&command, &output.stderr)),
+    }
+}
+
+/// Gets the [summary][`LfsSummary`] of the files managed by [Git LFS]
+/// in a repository's working tree, listing the tracked files with the attributes of Git LFS
+/// with `git ls-files`.
+///
+/// See [`Repository::query_lfs_summary`][`super::Repository::query_lfs_summary`].
+///
+/// [Git LFS]: https://git-lfs.com/
+pub fn query_lfs_summary(repo: &Repository) -> Result<LfsSummary, Error> {
+    // `top` lists the files of the whole working tree, by paths relative to `repo.dir`.
+    let ls_files = |attribute: &str| {
+        run(repo.git([
+            "ls-files",
+            "-z",
+            "--stage",
+            "--",
+            &format!(":(top,attr:{})", attribute),
+        ]))
+    };
+    let lfs_output = ls_files("filter=lfs")?;
+    let lockable_output = ls_files("lockable")?;
+    Ok(LfsSummary::from_tracked_files(
+        &repo.dir,
+        parse_regular_files(&lfs_output)?,
+        parse_regular_files(&lockable_output)?,
+    ))
+}
+
+/// Gets the names by which a commit can be described, usually a [detached HEAD]'s target.
+///
+/// See [`Repository::query_commit_description`][`super::Repository::query_commit_description`].
+///
+/// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_commit_description(
+    repo: &Repository,
+    id: ObjectId,
+) -> Result<CommitDescription, Error> {
+    let id_hex = id.to_string();
+    // `git describe` fails when there is no tag to describe the commit with.
+    let describe = |args: &[&str]| {
+        run(repo.git(["describe", "--tags"].iter().chain(args)))
+            .ok()
+            .and_then(|output| first_line(&output).ok().map(str::to_owned))
+    };
+
+    let tag = describe(&["--exact-match", &id_hex]);
+    let describe = match tag {
+        Some(_) => None,
+        None => describe(&["--abbrev=7", &id_hex]),
+    };
+
+    let mut nearest_branch: Option<NearestBranch> = None;
+    let branches = run(repo.git([
+        "for-each-ref",
+        "--contains",
+        &id_hex,
+        "--format=%(refname:short)",
+        "refs/heads/",
+    ]))?;
+    for name in lines(&branches)? {
+        let range = format!("{}..refs/heads/{}", id_hex, name);
+        let output = run(repo.git(["rev-list", "--count", &range]))?;
+        let distance = first_line(&output)?;
+        let distance = distance
+            .parse()
+            .map_err(|_| invalid_output("rev-list --count", distance))?;
+        let is_nearer = match &nearest_branch {
+            Some(nearest) => distance < nearest.distance,
+            None => true,
+        };
+        if is_nearer {
+            nearest_branch = Some(NearestBranch {
+                name: name.to_owned(),
+                distance,
+            });
+        }
+    }
+
+    Ok(CommitDescription {
+        id,
+        tag,
+        describe,
+        nearest_branch,
+    })
+}
+
+/// Gets a [detailed summary][`DetailedStatusSummary`] of a repository's [status].
+///
+/// See [`Repository::query_detailed_status_summary`][`super::Repository::query_detailed_status_summary`].
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_detailed_status_summary(
+    repo: &Repository,
+    options: &DetailedStatusOptions,
+) -> Result<DetailedStatusSummary, Error> {
+    let mut args = Vec::new();
+    if options.renames.is_some_and(|renames| renames.copies) {
+        args.extend([String::from("-c"), String::from("status.renames=copies")]);
+    }
+    args.extend(
+        ["status", "--porcelain=v2", "-z"]
+            .iter()
+            .map(|arg| String::from(*arg)),
+    );
+    args.push(match options.renames {
+        Some(renames) => format!("--find-renames={}%", renames.threshold),
+        None => String::from("--no-renames"),
+    });
+    args.extend(untracked_files_arg(options.untracked_files).map(String::from));
+    if options.ignored_files {
+        args.push(String::from("--ignored"));
+    }
+    if options.submodules == Submodules::Ignore {
+        args.push(String::from("--ignore-submodules=all"));
+    }
+    parse_detailed_status(&run(repo.git(args))?)
+}
+
+/// Gets the [line counts][`DiffStats`] of the changes in a repository's
+/// [staging area] and [working tree] with `git diff --numstat`.
+///
+/// See [`Repository::query_diff_stats`][`super::Repository::query_diff_stats`].
+/// `max_size` is passed to git as `core.bigFileThreshold`, above which blobs are treated as binary.
+/// Since git reads working tree files whatever their size,
+/// those larger than `max_size` are also excluded from the working tree diff by name.
+///
+/// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+pub fn query_diff_stats(repo: &Repository, max_size: Option<u64>) -> Result<DiffStats, Error> {
+    let diff = |options: &[&str], pathspecs: &[String]| {
+        let mut args = Vec::new();
+        if let Some(max_size) = max_size {
+            args.push(String::from("-c"));
+            args.push(format!("core.bigFileThreshold={}", max_size));
+        }
+        args.extend(
+            ["diff", "--numstat", "-z", "--no-renames", "--no-ext-diff"]
+                .iter()
+                .chain(options)
+                .chain(&["--"])
+                .map(|arg| String::from(*arg)),
+        );
+        args.extend(pathspecs.iter().cloned());
+        parse_numstat(&run(repo.git(args))?)
+    };
+
+    let mut exclusions = Vec::new();
+    if let Some(max_size) = max_size {
+        let output = run(repo.git(["rev-parse", "--show-toplevel"]))?;
+        let top_level = Path::new(first_line(&output)?);
+        let names = run(repo.git(["diff", "--name-only", "-z", "--no-renames"]))?;
+        for name in names.split(|&byte| byte == b'\0') {
+            let name = String::from_utf8_lossy(name);
+            let is_large = !name.is_empty()
+                && fs::metadata(top_level.join(&*name))
+                    .is_ok_and(|metadata| metadata.len() > max_size);
+            if is_large {
+                exclusions.push(format!(":(top,exclude,literal){}", name));
+            }
+        }
+    }
+
+    Ok(DiffStats {
+        working_tree: diff(&[], &exclusions)?,
+        staging: diff(&["--cached"], &[])?,
+    })
+}
+
+/// Gets the [superproject] of a repository, if it is a [submodule],
+/// with `git rev-parse --show-superproject-working-tree`.
+///
+/// See [`Repository::query_superproject`][`super::Repository::query_superproject`].
+///
+/// [superproject]: https://git-scm.com/docs/gitglossary#def_superproject
+/// [submodule]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_superproject(repo: &Repository) -> Result<Option<Superproject>, Error> {
+    let output = run(repo.git(["rev-parse", "--show-superproject-working-tree"]))?;
+    let worktree = match lines(&output)?.next() {
+        Some(worktree) => PathBuf::from(worktree),
+        None => return Ok(None),
+    };
+    let output = run(repo.git(["rev-parse", "--show-toplevel"]))?;
+    let path = match Path::new(first_line(&output)?).strip_prefix(&worktree) {
+        Ok(path) => path.to_owned(),
+        Err(_) => return Ok(None),
+    };
+
+    // The superproject's index entry reads as `<mode> <id> <stage>\t<path>`.
+    let mut literal_path = OsString::from(":(literal)");
+    literal_path.push(&path);
+    let mut command = repo.git(["ls-files", "--stage", "--"]);
+    command.arg(literal_path).current_dir(&worktree);
+    let output = run(command)?;
+    let recorded = first_line(&output)?
+        .split(' ')
+        .nth(1)
+        .and_then(ObjectId::from_hex)
+        .ok_or_else(|| invalid_output("ls-files --stage", &output))?;
+    // `git rev-parse --verify` fails when HEAD is unborn.
+    let head = run(repo.git(["rev-parse", "--verify", "--quiet", "HEAD"]))
+        .ok()
+        .and_then(|output| first_line(&output).ok().and_then(ObjectId::from_hex));
+
+    Ok(Some(Superproject {
+        worktree,
+        path,
+        out_of_date: head != Some(recorded),
+    }))
+}
+
+/// Gets the [summary][`SubmoduleSummary`] of the changes in a repository's [submodules]
+/// with `git status`.
+///
+/// Submodules' untracked files are looked for according to `status.showUntrackedFiles`,
+/// and each submodule's `ignore` setting is respected.
+///
+/// [submodules]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_submodule_summary(repo: &Repository) -> Result<SubmoduleSummary, Error> {
+    parse_submodule_summary(&run(repo.git(["status", "--porcelain=v2", "-z"]))?)
+}
+
+impl super::Repository for Repository {
+    fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    fn work_dir(&self) -> Option<&Path> {
+        self.work_dir.as_deref()
+    }
+
+    fn query_head(&self) -> Result<Head, Error> {
+        query_head(self)
+    }
+
+    fn query_push_target(&self, branch: &str) -> Result<Upstream, Error> {
+        query_push_target(self, branch)
+    }
+
+    fn query_remote_url(&self, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+        query_remote_url(self, remote)
+    }
+
+    fn query_last_commit(&self) -> Result<Option<LastCommit>, Error> {
+        query_last_commit(self)
+    }
+
+    fn query_stashes(&self) -> Result<Vec<Stash>, Error> {
+        query_stashes(self)
+    }
+
+    fn query_commit_description(&self, id: ObjectId) -> Result<CommitDescription, Error> {
+        query_commit_description(self, id)
+    }
+
+    fn query_status_summary(&self, limits: &StatusLimits) -> Result<StatusSummary, Error> {
+        query_status_summary(self, limits)
+    }
+
+    fn query_detailed_status_summary(
+        &self,
+        options: &DetailedStatusOptions,
+    ) -> Result<DetailedStatusSummary, Error> {
+        query_detailed_status_summary(self, options)
+    }
+
+    fn query_diff_stats(&self, max_size: Option<u64>) -> Result<DiffStats, Error> {
+        query_diff_stats(self, max_size)
+    }
+
+    fn query_superproject(&self) -> Result<Option<Superproject>, Error> {
+        query_superproject(self)
+    }
+
+    fn query_submodule_summary(&self) -> Result<SubmoduleSummary, Error> {
+        query_submodule_summary(self)
+    }
+
+    fn query_sparse_checkout(&self) -> Result<Option<SparseCheckout>, Error> {
+        query_sparse_checkout(self)
+    }
+
+    fn query_lfs_summary(&self) -> Result<LfsSummary, Error> {
+        query_lfs_summary(self)
+    }
+}
+
+fn status_args(untracked_files: UntrackedFiles) -> Vec<&'static str> {
+    let mut args = vec![
+        "status",
+        "--porcelain=v2",
+        "--branch",
+        "--ahead-behind",
+        "--no-renames",
+        "-z",
+    ];
+    args.extend(untracked_files_arg(untracked_files));
+    args
+}
+
+fn untracked_files_arg(untracked_files: UntrackedFiles) -> Option<&'static str> {
+    match untracked_files {
+        // git reads `status.showUntrackedFiles` on its own.
+        UntrackedFiles::FromConfig => None,
+        UntrackedFiles::No => Some("--untracked-files=no"),
+        UntrackedFiles::Normal => Some("--untracked-files=normal"),
+        UntrackedFiles::All => Some("--untracked-files=all"),
+    }
+}
+
+/// An entry of the output of `git status --porcelain=v2 -z`.
+///
+/// See <https://git-scm.com/docs/git-status#_porcelain_format_version_2>.
+enum StatusEntry<'a> {
+    /// A `# key value` header, such as `# branch.head main`.
+    Header(&'a str, &'a str),
+    /// A changed tracked file, with its staged and unstaged `XY` status codes
+    /// and its submodule state, such as `N...` or `SC.U`.
+    Changed {
+        staged: u8,
+        unstaged: u8,
+        submodule: &'a [u8],
+    },
+    Unmerged,
+    Untracked,
+    Ignored,
+}
+
+fn parse_status_entries(output: &[u8]) -> Result<Vec<StatusEntry<'_>>, Error> {
+    let mut entries = Vec::new();
+    let mut fields = output
+        .split(|&byte| byte == b'\0')
+        .filter(|field| !field.is_empty());
+    while let Some(field) = fields.next() {
+        entries.push(match field[0] {
+            b'#' => {
+                let header = field
+                    .strip_prefix(b"# ")
+                    .and_then(|header| str::from_utf8(header).ok())
+                    .ok_or_else(|| invalid_output("status", field))?;
+                let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                StatusEntry::Header(key, value)
+            }
+            kind @ (b'1' | b'2') => {
+                let (staged, unstaged) = match field.get(2..4) {
+                    Some(&[staged, unstaged]) => (staged, unstaged),
+                    _ => return Err(invalid_output("status", field)),
+                };
+                let submodule = field
+                    .get(5..9)
+                    .ok_or_else(|| invalid_output("status", field))?;
+                // Renamed and copied entries are followed by the original path.
+                if kind == b'2' {
+                    fields.next();
+                }
+                StatusEntry::Changed {
+                    staged,
+                    unstaged,
+                    submodule,
+                }
+            }
+            b'u' => StatusEntry::Unmerged,
+            b'?' => StatusEntry::Untracked,
+            b'!' => StatusEntry::Ignored,
+            _ => return Err(invalid_output("status", field)),
+        });
+    }
+    Ok(entries)
+}
+
+/// Parses the output of `git status --porcelain=v2 --branch -z`.
+fn parse_status(output: &[u8], max_files: Option<usize>) -> Result<(Head, StatusSummary), Error> {
+    let mut oid = None;
+    let mut head = None;
+    let mut upstream = None;
+    let mut ahead_behind = None;
+    let mut summary = StatusSummary::default();
+    let mut files = 0;
+
+    for entry in parse_status_entries(output)? {
+        match entry {
+            StatusEntry::Header(key, value) => match key {
+                "branch.oid" => oid = Some(value),
+                "branch.head" => head = Some(value),
+                "branch.upstream" => upstream = Some(value),
+                "branch.ab" => ahead_behind = Some(parse_ahead_behind(value)),
+                _ => {}
+            },
+            StatusEntry::Ignored => {}
+            entry => {
+                if max_files.is_some_and(|max_files| files >= max_files) {
+                    summary.incomplete = true;
+                    break;
+                }
+                files += 1;
+
+                match entry {
+                    StatusEntry::Changed {
+                        staged, unstaged, ..
+                    } => {
+                        count_change(&mut summary.staging, staged);
+                        count_change(&mut summary.working_tree, unstaged);
+                    }
+                    StatusEntry::Unmerged => summary.conflicted += 1,
+                    _ => summary.working_tree.added += 1,
+                }
+            }
+        }
+    }
+
+    let head = match (oid, head) {
+        (oid, Some("(detached)")) => {
+            let oid = oid.unwrap_or_default();
+            Head::Commit(ObjectId::from_hex(oid).ok_or_else(|| invalid_output("status", oid))?)
+        }
+        (Some("(initial)"), Some(name)) => Head::Unborn {
+            target: format!("refs/heads/{}", name),
+        },
+        (_, Some(name)) => Head::Branch {
+            name: name.to_owned(),
+            upstream: match (upstream, ahead_behind) {
+                (None, _) => Ok(Upstream::NotConfigured),
+                (Some(name), Some(ahead_behind)) => {
+                    ahead_behind.map(|ahead_behind| Upstream::Present {
+                        name: name.to_owned(),
+                        ahead_behind,
+                    })
+                }
+                // The upstream is configured, but it is gone.
+                (Some(name), None) => Ok(Upstream::Gone {
+                    name: name.to_owned(),
+                }),
+            },
+        },
+        (_, None) => return Err(invalid_output("status", "no branch.head header")),
+    };
+
+    Ok((head, summary))
+}
+
+/// Parses the output of `git diff --numstat -z --no-renames`.
+///
+/// Binary files, whose counts are written as `-`, are left out.
+fn parse_numstat(output: &[u8]) -> Result<LineChanges, Error> {
+    let mut changes = LineChanges::default();
+    for entry in output
+        .split(|&byte| byte == b'\0')
+        .filter(|entry| !entry.is_empty())
+    {
+        let mut fields = entry.splitn(3, |&byte| byte == b'\t');
+        let mut count = || {
+            let field = fields.next().ok_or_else(|| invalid_output("diff", entry))?;
+            match field {
+                b"-" => Ok(None),
+                _ => str::from_utf8(field)
+                    .ok()
+                    .and_then(|field| field.parse::<usize>().ok())
+                    .map(Some)
+                    .ok_or_else(|| invalid_output("diff", entry)),
+            }
+        };
+        if let (Some(insertions), Some(deletions)) = (count()?, count()?) {
+            changes.insertions += insertions;
+            changes.deletions += deletions;
+        }
+    }
+    Ok(changes)
+}
+
+/// Parses the output of `git status --porcelain=v2 -z`
+/// into a [`DetailedStatusSummary`].
+fn parse_detailed_status(output: &[u8]) -> Result<DetailedStatusSummary, Error> {
+    fn count_change(changes: &mut DetailedChangeSummary, code: u8) {
+        match code {
+            b'A' => changes.added += 1,
+            b'D' => changes.deleted += 1,
+            b'M' => changes.modified += 1,
+            b'R' => changes.renamed += 1,
+            b'C' => changes.copied += 1,
+            b'T' => changes.typechanged += 1,
+            _ => {}
+        }
+    }
+
+    let mut summary = DetailedStatusSummary::default();
+    for entry in parse_status_entries(output)? {
+        match entry {
+            StatusEntry::Header(..) => {}
+            StatusEntry::Changed {
+                staged, unstaged, ..
+            } => {
+                count_change(&mut summary.staging, staged);
+                count_change(&mut summary.working_tree, unstaged);
+            }
+            StatusEntry::Unmerged => summary.conflicted += 1,
+            StatusEntry::Untracked => summary.untracked += 1,
+            StatusEntry::Ignored => summary.ignored += 1,
+        }
+    }
+    Ok(summary)
+}
+
+/// Parses the output of `git status --porcelain=v2 -z`, counting only submodules.
+fn parse_submodule_summary(output: &[u8]) -> Result<SubmoduleSummary, Error> {
+    let mut summary = SubmoduleSummary::default();
+    for entry in parse_status_entries(output)? {
+        if let StatusEntry::Changed {
+            submodule: &[b'S', commit_changed, modified, untracked],
+            ..
+        } = entry
+        {
+            if commit_changed == b'C' {
+                summary.out_of_date += 1;
+            }
+            if modified == b'M' || untracked == b'U' {
+                summary.modified += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Parses the output of `git ls-files -z --stage` into the paths of the regular files.
+fn parse_regular_files(output: &[u8]) -> Result<Vec<&str>, Error> {
+    let mut paths = Vec::new();
+    for entry in output
+        .split(|&byte| byte == b'\0')
+        .filter(|entry| !entry.is_empty())
+    {
+        let entry = str::from_utf8(entry).map_err(|_| invalid_output("ls-files", entry))?;
+        let (info, path) = entry
+            .split_once('\t')
+            .ok_or_else(|| invalid_output("ls-files", entry))?;
+        let mut info = info.split(' ');
+        // Regular files, leaving out the entries of a conflict's sides.
+        if matches!(info.next(), Some("100644" | "100755")) && info.nth(1) == Some("0") {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Parses the output of `git for-each-ref`
+/// with `--format=%(refname)%00%(upstream:short)%00%(upstream:track,nobracket)`
+/// for the `target` of HEAD, which is unborn if it is not listed.
+fn parse_head_ref(output: &[u8], target: String) -> Result<Head, Error> {
+    // A pattern also lists the references under it, as `refs/heads/main/*` for `refs/heads/main`.
+    let upstream = lines(output)?.find_map(|line| {
+        line.split_once('\0')
+            .filter(|(name, _)| *name == target)
+            .map(|(_, upstream)| upstream)
+    });
+    Ok(match upstream {
+        Some(upstream) => Head::Branch {
+            name: target
+                .strip_prefix("refs/heads/")
+                .unwrap_or(&target)
+                .to_owned(),
+            upstream: parse_push_target(upstream.as_bytes()),
+        },
+        None => Head::Unborn { target },
+    })
+}
+
+/// Parses the output of `git for-each-ref --format=%(push:short)%00%(push:track,nobracket)`,
+/// or of the same format for `upstream`.
+///
+/// The tracking information is empty when the branches are even,
+/// `gone` when the push target does not exist,
+/// and, otherwise, like `ahead 1, behind 2`, leaving out the zero counts.
+fn parse_push_target(output: &[u8]) -> Result<Upstream, Error> {
+    let line = lines(output)?.next().unwrap_or_default();
+    let (name, track) = line.split_once('\0').unwrap_or((line, ""));
+    if name.is_empty() {
+        return Ok(Upstream::NotConfigured);
+    }
+    if track == "gone" {
+        return Ok(Upstream::Gone {
+            name: name.to_owned(),
+        });
+    }
+
+    let mut ahead_behind = AheadBehind::default();
+    for count in track.split(", ").filter(|count| !count.is_empty()) {
+        let (direction, count) = count
+            .split_once(' ')
+            .ok_or_else(|| invalid_output("for-each-ref", line))?;
+        let count = count
+            .parse()
+            .map_err(|_| invalid_output("for-each-ref", line))?;
+        match direction {
+            "ahead" => ahead_behind.ahead = count,
+            "behind" => ahead_behind.behind = count,
+            _ => return Err(invalid_output("for-each-ref", line)),
+        }
+    }
+    Ok(Upstream::Present {
+        name: name.to_owned(),
+        ahead_behind,
+    })
+}
+
+/// Parses the output of `git config -z --type=bool --get-regexp`
+/// for `core.sparseCheckout` and `core.sparseCheckoutCone`.
+fn parse_sparse_checkout(output: &[u8]) -> Result<Option<SparseCheckout>, Error> {
+    let (mut enabled, mut cone) = (false, false);
+    for entry in output
+        .split(|&byte| byte == b'\0')
+        .filter(|entry| !entry.is_empty())
+    {
+        let entry = str::from_utf8(entry).map_err(|_| invalid_output("config", entry))?;
+        let (key, value) = entry
+            .split_once('\n')
+            .ok_or_else(|| invalid_output("config", entry))?;
+        // The last value of a setting is the one in effect.
+        let value = value == "true";
+        match key {
+            "core.sparsecheckout" => enabled = value,
+            "core.sparsecheckoutcone" => cone = value,
+            _ => return Err(invalid_output("config", entry)),
+        }
+    }
+    Ok(SparseCheckout::from_config(enabled, cone))
+}
+
+fn parse_ahead_behind(value: &str) -> Result<AheadBehind, Error> {
+    let parse = |count: Option<&str>, sign: char| {
+        count
+            .and_then(|count| count.strip_prefix(sign))
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| invalid_output("status", value))
+    };
+    let mut counts = value.split(' ');
+    Ok(AheadBehind {
+        ahead: parse(counts.next(), '+')?,
+        behind: parse(counts.next(), '-')?,
+    })
+}
+
+fn count_change(changes: &mut ChangeSummary, code: u8) {
+    match code {
+        b'A' => changes.added += 1,
+        b'D' => changes.deleted += 1,
+        b'M' | b'T' | b'R' | b'C' => changes.modified += 1,
+        _ => {}
+    }
+}
+
+fn run(mut command: Command) -> Result<Vec<u8>, Error> {
+    let output = command.stdin(Stdio::null()).output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(command_failed(&command, &output.stderr))
+    }
+}
+
+/// Like [`run`], but kills the command and returns [`None`] if it takes longer than `timeout`.
+fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<Option<Vec<u8>>, Error> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // stderr is drained on its own thread, so that the command cannot block on a full pipe.
+        let stderr = thread::spawn(move || {
+            let mut output = Vec::new();
+            stderr.read_to_end(&mut output).map(|_| output)
+        });
+        let mut output = Vec::new();
+        let result = stdout.read_to_end(&mut output).and_then(|_| {
+            let stderr = stderr
+                .join()
+                .unwrap_or_else(|panic| panic::resume_unwind(panic));
+            Ok((output, stderr?))
+        });
+        // The receiver is gone when the command timed out.
+        let _ = sender.send(result);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(output) => {
+            let (stdout, stderr) = output?;
+            if child.wait()?.success() {
+                Ok(Some(stdout))
+            } else {
+                Err(command_failed(&command, &stderr))
+            }
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            Ok(None)
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(Error::new(
+            ErrorKind::Backend,
+            "the output reading thread panicked.",
+        )),
+    }
+}
+
+fn command_failed(command: &Command, stderr: &[u8]) -> Error {
+    let args = command
+        .get_args()
+        .map(OsStr::to_string_lossy)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Error::new(
+        ErrorKind::Command,
+        format!(
+            "`git {}` failed: {}",
+            args,
+            String::from_utf8_lossy(stderr).trim()
+        ),
+    )
+}
+
+fn invalid_output(command: &str, output: impl AsRef<[u8]>) -> Error {
+    Error::new(
+        ErrorKind::InvalidOutput,
+        format!(
+            "unexpected output from `git {}`: {:?}",
+            command,
+            String::from_utf8_lossy(output.as_ref())
+        ),
+    )
+}
+
+fn lines(output: &[u8]) -> Result<impl Iterator<Item = &str>, Error> {
+    let output = str::from_utf8(output).map_err(|_| invalid_output("", output))?;
+    Ok(output.lines().filter(|line| !line.is_empty()))
+}
+
+fn first_line(output: &[u8]) -> Result<&str, Error> {
+    lines(output)?
+        .next()
+        .ok_or_else(|| invalid_output("", output))
+}
@@ -0,0 +1,494 @@
+mod parse_status {
+    use crate::env::git::{
+        cli::{parse_detailed_status, parse_push_target, parse_status},
+        AheadBehind, ChangeSummary, DetailedChangeSummary, DetailedStatusSummary, Head,
+        StatusSummary, Upstream,
+    };
+
+    #[test]
+    fn reads_the_headers_and_entries() {
+        let output = b"# branch.oid 0123456789abcdef0123456789abcdef01234567\0\
+            # branch.head main\0\
+            # branch.upstream origin/main\0\
+            # branch.ab +2 -1\0\
+            1 M. N... 100644 100644 100644 0000 0000 a\0\
+            1 .D N... 100644 100644 000000 0000 0000 b\0\
+            2 RM N... 100644 100644 100644 0000 0000 R100 c\0d\0\
+            u UU N... 100644 100644 100644 100644 0000 0000 0000 e\0\
+            ? f\0\
+            ! g\0";
+        let (head, status) = parse_status(output, None).unwrap();
+        assert_eq!(
+            head,
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Present {
+                    name: String::from("origin/main"),
+                    ahead_behind: AheadBehind {
+                        ahead: 2,
+                        behind: 1
+                    },
+                }),
+            }
+        );
+        assert_eq!(
+            status,
+            StatusSummary {
+                staging: ChangeSummary {
+                    added: 0,
+                    modified: 2,
+                    deleted: 0,
+                },
+                working_tree: ChangeSummary {
+                    added: 1,
+                    modified: 1,
+                    deleted: 1,
+                },
+                conflicted: 1,
+                incomplete: false,
+            }
+        );
+
+        let (_, status) = parse_status(output, Some(2)).unwrap();
+        assert!(status.incomplete);
+        assert_eq!(status.staging.modified, 1);
+        assert_eq!(status.working_tree.deleted, 1);
+    }
+
+    #[test]
+    fn tells_gone_upstreams_apart() {
+        let (head, _) = parse_status(
+            b"# branch.oid 0123456789abcdef0123456789abcdef01234567\0\
+            # branch.head main\0\
+            # branch.upstream origin/main\0",
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            head,
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Gone {
+                    name: String::from("origin/main")
+                }),
+            }
+        );
+
+        assert_eq!(parse_push_target(b"\0\n").unwrap(), Upstream::NotConfigured);
+        assert_eq!(
+            parse_push_target(b"fork/main\0gone\n").unwrap(),
+            Upstream::Gone {
+                name: String::from("fork/main")
+            }
+        );
+        assert_eq!(
+            parse_push_target(b"fork/main\0ahead 1, behind 2\n").unwrap(),
+            Upstream::Present {
+                name: String::from("fork/main"),
+                ahead_behind: AheadBehind {
+                    ahead: 1,
+                    behind: 2
+                },
+            }
+        );
+        assert_eq!(
+            parse_push_target(b"fork/main\0\n").unwrap().ahead_behind(),
+            Some(&AheadBehind::default())
+        );
+    }
+
+    #[test]
+    fn separates_the_detailed_categories() {
+        let output = b"# branch.oid (initial)\0\
+            1 T. N... 100644 120000 120000 0000 0000 a\0\
+            1 AM N... 000000 100644 100644 0000 0000 b\0\
+            2 R. N... 100644 100644 100644 0000 0000 R100 c\0d\0\
+            2 C. N... 100644 100644 100644 0000 0000 C75 e\0f\0\
+            1 .T N... 100644 100644 120000 0000 0000 g\0\
+            u UU N... 100644 100644 100644 100644 0000 0000 0000 h\0\
+            ? i\0\
+            ! j\0\
+            ! k/\0";
+        let status = parse_detailed_status(output).unwrap();
+        assert_eq!(
+            status,
+            DetailedStatusSummary {
+                staging: DetailedChangeSummary {
+                    added: 1,
+                    renamed: 1,
+                    copied: 1,
+                    typechanged: 1,
+                    ..DetailedChangeSummary::default()
+                },
+                working_tree: DetailedChangeSummary {
+                    modified: 1,
+                    typechanged: 1,
+                    ..DetailedChangeSummary::default()
+                },
+                untracked: 1,
+                ignored: 2,
+                conflicted: 1,
+            }
+        );
+        assert_eq!(
+            StatusSummary::from(&status),
+            StatusSummary {
+                staging: ChangeSummary {
+                    added: 1,
+                    modified: 3,
+                    deleted: 0,
+                },
+                working_tree: ChangeSummary {
+                    added: 1,
+                    modified: 2,
+                    deleted: 0,
+                },
+                conflicted: 1,
+                incomplete: false,
+            }
+        );
+    }
+
+    #[test]
+    fn reads_unborn_and_detached_heads() {
+        let (head, _) =
+            parse_status(b"# branch.oid (initial)\0# branch.head main\0", None).unwrap();
+        assert_eq!(
+            head,
+            Head::Unborn {
+                target: String::from("refs/heads/main")
+            }
+        );
+
+        let (head, _) = parse_status(
+            b"# branch.oid 0123456789abcdef0123456789abcdef01234567\0# branch.head (detached)\0",
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            head,
+            Head::Commit(
+                crate::env::git::ObjectId::from_hex("0123456789abcdef0123456789abcdef01234567")
+                    .unwrap()
+            )
+        );
+    }
+}
+
+mod repository {
+    use crate::env::git::{
+        cli::{
+            open_repo, query_commit_description, query_diff_stats, query_head, query_last_commit,
+            query_lfs_summary, query_sparse_checkout, query_stashes, query_status_summary,
+            query_submodule_summary, query_superproject,
+        },
+        test::git,
+        AheadBehind, DiffStats, Head, LfsSummary, LineChanges, ObjectId, Repository,
+        SparseCheckout, StatusLimits, SubmoduleSummary, UntrackedFiles, Upstream,
+    };
+    use std::{fs, path::Path};
+
+    #[test]
+    fn queries_a_temporary_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(query_last_commit(&repo).unwrap(), None);
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Unborn {
+                target: String::from("refs/heads/main")
+            }
+        );
+
+        fs::write(dir.path().join("a"), "a").unwrap();
+        git(dir.path(), &["add", "a"]);
+        git(dir.path(), &["commit", "-m", "first"]);
+        git(dir.path(), &["tag", "v1.0.0"]);
+        fs::write(dir.path().join("a"), "b").unwrap();
+        git(dir.path(), &["commit", "-am", "second"]);
+        let second = git(dir.path(), &["rev-parse", "HEAD"]);
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::NotConfigured),
+            }
+        );
+        git(dir.path(), &["config", "branch.main.remote", "."]);
+        git(
+            dir.path(),
+            &["config", "branch.main.merge", "refs/heads/gone"],
+        );
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Gone {
+                    name: String::from("gone")
+                }),
+            }
+        );
+        git(dir.path(), &["branch", "up", "HEAD~1"]);
+        git(
+            dir.path(),
+            &["config", "branch.main.merge", "refs/heads/up"],
+        );
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Present {
+                    name: String::from("up"),
+                    ahead_behind: AheadBehind {
+                        ahead: 1,
+                        behind: 0
+                    },
+                }),
+            }
+        );
+        let last_commit = query_last_commit(&repo).unwrap().unwrap();
+        assert_eq!(last_commit.id, ObjectId::from_hex(&second).unwrap());
+        assert_eq!(last_commit.author, "test");
+        assert_eq!(last_commit.author_email, "test@example.com");
+        assert_eq!(last_commit.summary, "second");
+
+        fs::write(dir.path().join("a"), "c").unwrap();
+        fs::write(dir.path().join("b"), "b").unwrap();
+        let status = query_status_summary(
+            &repo,
+            &StatusLimits {
+                untracked_files: UntrackedFiles::Normal,
+                ..StatusLimits::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(status.working_tree.modified, 1);
+        assert_eq!(status.working_tree.added, 1);
+        assert!(!status.incomplete);
+
+        fs::write(dir.path().join("b"), "1\n2\n").unwrap();
+        git(dir.path(), &["add", "b"]);
+        assert_eq!(
+            query_diff_stats(&repo, None).unwrap(),
+            DiffStats {
+                working_tree: LineChanges {
+                    insertions: 1,
+                    deletions: 1,
+                },
+                staging: LineChanges {
+                    insertions: 2,
+                    deletions: 0,
+                },
+            }
+        );
+        assert!(!query_diff_stats(&repo, Some(0)).unwrap().any_changes());
+
+        assert_eq!(query_stashes(&repo).unwrap(), []);
+        git(dir.path(), &["stash"]);
+        git(dir.path(), &["checkout", "--detach", "HEAD~1"]);
+        fs::write(dir.path().join("a"), "d").unwrap();
+        git(dir.path(), &["stash", "push", "-m", "detached"]);
+        let stashes = query_stashes(&repo).unwrap();
+        assert_eq!(stashes.len(), 2);
+        assert_eq!(stashes[0].message, "On (no branch): detached");
+        assert_eq!(stashes[0].branch, None);
+        assert_eq!(stashes[1].index, 1);
+        assert_eq!(stashes[1].branch.as_deref(), Some("main"));
+        assert_eq!(repo.query_stash_count(Some("main")).unwrap(), 1);
+        assert_eq!(repo.query_stash_count(None).unwrap(), 2);
+
+        git(dir.path(), &["checkout", "--force", "--detach", "HEAD"]);
+        let id = match query_head(&repo).unwrap() {
+            Head::Commit(id) => id,
+            head => panic!("unexpected head: {:?}", head),
+        };
+        let description = query_commit_description(&repo, id).unwrap();
+        assert_eq!(description.tag.as_deref(), Some("v1.0.0"));
+        let description =
+            query_commit_description(&repo, ObjectId::from_hex(&second).unwrap()).unwrap();
+        assert_eq!(description.tag, None);
+        assert_eq!(
+            description
+                .nearest_branch
+                .map(|branch| (branch.name, branch.distance)),
+            Some((String::from("main"), 0))
+        );
+        assert_eq!(
+            description.describe,
+            Some(format!("v1.0.0-1-g{}", &second[..7]))
+        );
+    }
+
+    #[test]
+    fn finds_the_work_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet", "main"]);
+        git(dir.path(), &["init", "--quiet", "--bare", "bare.git"]);
+        let main = fs::canonicalize(dir.path().join("main")).unwrap();
+
+        let repo = open_repo(&main).unwrap();
+        assert_eq!(repo.git_dir(), main.join(".git"));
+        assert_eq!(repo.work_dir(), Some(main.as_path()));
+        let repo = open_repo(&dir.path().join("bare.git")).unwrap();
+        assert_eq!(repo.work_dir(), None);
+    }
+
+    #[test]
+    fn finds_worktrees_and_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let (main, lib) = (dir.path().join("main"), dir.path().join("lib"));
+        for path in [&main, &lib] {
+            git(dir.path(), &["init", path.to_str().unwrap()]);
+            git(path, &["commit", "--allow-empty", "-m", "first"]);
+        }
+        git(
+            &main,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "../lib",
+                "lib",
+            ],
+        );
+        git(&main, &["commit", "-m", "add lib"]);
+        git(&main, &["worktree", "add", "../feature"]);
+
+        let repo = open_repo(&main).unwrap();
+        assert_eq!(query_superproject(&repo).unwrap(), None);
+        assert_eq!(repo.query_linked_worktree(), None);
+        assert!(!query_submodule_summary(&repo).unwrap().any_changes());
+
+        let worktree = open_repo(&dir.path().join("feature"))
+            .unwrap()
+            .query_linked_worktree()
+            .unwrap();
+        assert_eq!(worktree.name, "feature");
+        assert_eq!(worktree.main_worktree, Some(main.canonicalize().unwrap()));
+
+        let submodule = open_repo(&main.join("lib")).unwrap();
+        let superproject = query_superproject(&submodule).unwrap().unwrap();
+        assert_eq!(superproject.path, Path::new("lib"));
+        assert!(!superproject.out_of_date);
+
+        git(
+            &main.join("lib"),
+            &["commit", "--allow-empty", "-m", "second"],
+        );
+        fs::write(main.join("lib/a"), "a").unwrap();
+        assert!(query_superproject(&submodule).unwrap().unwrap().out_of_date);
+        assert_eq!(
+            query_submodule_summary(&repo).unwrap(),
+            SubmoduleSummary {
+                out_of_date: 1,
+                modified: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn finds_shallow_sparse_and_lfs_states() {
+        let dir = tempfile::tempdir().unwrap();
+        let (origin, clone) = (dir.path().join("origin"), dir.path().join("clone"));
+        git(dir.path(), &["init", origin.to_str().unwrap()]);
+        fs::create_dir(origin.join("d")).unwrap();
+        fs::write(origin.join("d/a"), "a").unwrap();
+        fs::write(origin.join("d/c"), "c").unwrap();
+        let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:0\nsize 1\n";
+        fs::write(origin.join("b.bin"), pointer).unwrap();
+        // Only the files with the attributes of Git LFS are looked at.
+        fs::write(origin.join("c.txt"), pointer).unwrap();
+        fs::write(
+            origin.join(".gitattributes"),
+            "*.bin filter=lfs\nd/a lockable\n",
+        )
+        .unwrap();
+        git(&origin, &["add", "."]);
+        git(&origin, &["commit", "-m", "first"]);
+        git(&origin, &["commit", "--allow-empty", "-m", "second"]);
+        let url = format!("file://{}", origin.display());
+        git(
+            dir.path(),
+            &["clone", "--depth=1", &url, clone.to_str().unwrap()],
+        );
+
+        let repo = open_repo(&clone).unwrap();
+        let head = git(&clone, &["rev-parse", "HEAD"]);
+        assert_eq!(
+            repo.query_shallow().unwrap().boundary,
+            [ObjectId::from_hex(&head).unwrap()]
+        );
+        assert!(open_repo(&origin).unwrap().query_shallow().is_none());
+
+        assert_eq!(query_sparse_checkout(&repo).unwrap(), None);
+        assert_eq!(
+            query_lfs_summary(&repo).unwrap(),
+            LfsSummary {
+                pointers: 1,
+                read_only: 0,
+            }
+        );
+        git(&clone, &["sparse-checkout", "set", "--no-cone", "/b.bin"]);
+        assert_eq!(
+            query_sparse_checkout(&repo).unwrap(),
+            Some(SparseCheckout::NonCone)
+        );
+        git(&clone, &["sparse-checkout", "set", "--cone", "d"]);
+        assert_eq!(
+            query_sparse_checkout(&repo).unwrap(),
+            Some(SparseCheckout::Cone)
+        );
+        for path in ["d/a", "d/c"] {
+            let mut permissions = fs::metadata(clone.join(path)).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(clone.join(path), permissions).unwrap();
+        }
+        assert_eq!(
+            query_lfs_summary(&repo).unwrap(),
+            LfsSummary {
+                pointers: 1,
+                read_only: 1,
+            }
+        );
+    }
+}
+
+mod parse_numstat {
+    use crate::env::git::{cli::parse_numstat, LineChanges};
+
+    #[test]
+    fn sums_the_text_files() {
+        assert_eq!(
+            parse_numstat(b"120\t30\ta\0-\t-\tb\x000\t4\tc d\0").unwrap(),
+            LineChanges {
+                insertions: 120,
+                deletions: 34,
+            }
+        );
+        assert!(parse_numstat(b"x\t1\ta\0").is_err());
+    }
+}
+
+mod run_with_timeout {
+    use crate::env::git::cli::run_with_timeout;
+    use std::{process::Command, time::Duration};
+
+    #[test]
+    fn drains_stderr_while_reading_stdout() {
+        // More than a pipe buffer is written to stderr before stdout is closed.
+        let mut command = Command::new("sh");
+        command.args(["-c", "head -c 1000000 /dev/zero >&2; echo done"]);
+        let output = run_with_timeout(command, Duration::from_secs(10)).unwrap();
+        assert_eq!(output.as_deref(), Some(&b"done\n"[..]));
+    }
+
+    #[test]
+    fn kills_commands_that_take_too_long() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "exec sleep 10"]);
+        let output = run_with_timeout(command, Duration::from_millis(100)).unwrap();
+        assert_eq!(output, None);
+    }
+}
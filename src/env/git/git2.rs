@@ -0,0 +1,790 @@
+//! Queries implemented with [libgit2], through the [`git2`][::git2] crate.
+//!
+//! [libgit2]: https://libgit2.org/
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    count_stashes, unix_time, AheadBehind, ChangeSummary, CommitDescription, DetailedStatusOptions,
+    DetailedStatusSummary, DiffStats, Error, ErrorKind, Head, LastCommit, LfsSummary, LineChanges,
+    NearestBranch, ObjectId, Operation, RemoteUrl, SparseCheckout, Stash, StatusLimits,
+    StatusSummary, SubmoduleSummary, Submodules, Superproject, UntrackedFiles, Upstream,
+};
+use ::git2::{
+    AttrCheckFlags, AttrValue, Branch, BranchType, Config, Delta, DescribeFormatOptions,
+    DescribeOptions, Diff, DiffFindOptions, DiffOptions, Direction, ErrorCode, Oid, Repository,
+    RepositoryOpenFlags, Status, StatusOptions, SubmoduleIgnore, SubmoduleStatus, Tree,
+};
+use std::{
+    convert::TryFrom,
+    path::Path,
+    str,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+};
+
+/// Finds and [opens][`Repository::open`] a repository.
+///
+/// The search is done as git would from `dir`.
+pub fn open_repo(dir: &Path) -> Result<Repository, Error> {
+    Ok(Repository::open_ext(
+        dir,
+        RepositoryOpenFlags::FROM_ENV,
+        None::<&Path>,
+    )?)
+}
+
+/// Gets the information about a repository's [HEAD].
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_head(repo: &Repository) -> Result<Head, Error> {
+    let head = repo.find_reference("HEAD")?;
+    match head.symbolic_target_bytes() {
+        Some(target) => {
+            let target = str::from_utf8(target).map_err(|_| {
+                Error::new(
+                    ErrorKind::Backend,
+                    "could not decode HEAD's symbolic target.",
+                )
+            })?;
+            match repo.find_reference(target) {
+                Ok(reference) => {
+                    let branch = Branch::wrap(reference);
+                    Ok(Head::Branch {
+                        name: String::from_utf8_lossy(branch.name_bytes()?).into_owned(),
+                        upstream: query_upstream(repo, &branch),
+                    })
+                }
+                Err(err) if err.code() == ErrorCode::NotFound => Ok(Head::Unborn {
+                    target: String::from(target),
+                }),
+                Err(err) => Err(err.into()),
+            }
+        }
+        None => match head.target() {
+            Some(target) => {
+                let commit = repo.find_commit(target)?;
+                Ok(Head::Commit(commit.id().into()))
+            }
+            None => Err(Error::new(
+                ErrorKind::Backend,
+                "HEAD is neither direct nor symbolic.",
+            )),
+        },
+    }
+}
+
+/// Gets the state of a branch's [upstream][upstream branch].
+///
+/// [upstream branch]: https://git-scm.com/docs/gitglossary#def_upstream_branch
+pub fn query_upstream(repo: &Repository, branch: &Branch) -> Result<Upstream, Error> {
+    let name = branch
+        .get()
+        .name()
+        .ok_or_else(|| Error::new(ErrorKind::Backend, "could not decode the branch's name."))?;
+    match repo.branch_upstream_name(name) {
+        Ok(upstream) => {
+            let upstream = upstream.as_str().ok_or_else(|| {
+                Error::new(ErrorKind::Backend, "could not decode the upstream's name.")
+            })?;
+            query_tracking(repo, branch.get().peel_to_commit()?.id(), upstream)
+        }
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(Upstream::NotConfigured),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Gets the branch that a branch is pushed to by `git push`, as in `branch@{push}`.
+///
+/// See [`Repository::query_push_target`][`super::Repository::query_push_target`].
+/// The push remote is found and `push.default` is honored as git does,
+/// but the remote's `push` refspecs are not.
+pub fn query_push_target(repo: &Repository, branch: &str) -> Result<Upstream, Error> {
+    let config = repo.config()?;
+    let get = |key: &str| match config.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    let fetch_remote = get(&format!("branch.{}.remote", branch))?;
+    let remote = match get(&format!("branch.{}.pushRemote", branch))?
+        .or(get("remote.pushDefault")?)
+        .or_else(|| fetch_remote.clone())
+    {
+        Some(remote) => remote,
+        None => return Ok(Upstream::NotConfigured),
+    };
+    let local = format!("refs/heads/{}", branch);
+    let merge = get(&format!("branch.{}.merge", branch))?;
+    let is_triangular = fetch_remote.as_ref() != Some(&remote);
+    let remote_ref = match get("push.default")?.as_deref() {
+        Some("nothing") => None,
+        Some("current") | Some("matching") => Some(local.clone()),
+        Some("upstream") | Some("tracking") => merge.filter(|_| !is_triangular),
+        // `simple`, the default, pushes to the upstream only when it has the same name.
+        _ if is_triangular => Some(local.clone()),
+        _ => merge.filter(|merge| *merge == local),
+    };
+    let remote_ref = match remote_ref {
+        Some(remote_ref) => remote_ref,
+        None => return Ok(Upstream::NotConfigured),
+    };
+
+    let tracking = if remote == "." {
+        Some(remote_ref)
+    } else {
+        let remote = match repo.find_remote(&remote) {
+            Ok(remote) => remote,
+            Err(err) if err.code() == ErrorCode::NotFound => return Ok(Upstream::NotConfigured),
+            Err(err) => return Err(err.into()),
+        };
+        let tracking = remote
+            .refspecs()
+            .filter(|refspec| {
+                refspec.direction() == Direction::Fetch && refspec.src_matches(&remote_ref)
+            })
+            .find_map(|refspec| refspec.transform(&remote_ref).ok());
+        tracking.and_then(|tracking| tracking.as_str().map(str::to_owned))
+    };
+    match tracking {
+        Some(tracking) => query_tracking(
+            repo,
+            repo.find_reference(&local)?.peel_to_commit()?.id(),
+            &tracking,
+        ),
+        None => Ok(Upstream::NotConfigured),
+    }
+}
+
+/// Gets the [`Upstream`] whose remote-tracking branch is the reference named `tracking`.
+fn query_tracking(repo: &Repository, local: Oid, tracking: &str) -> Result<Upstream, Error> {
+    let name = tracking
+        .strip_prefix("refs/remotes/")
+        .or_else(|| tracking.strip_prefix("refs/heads/"))
+        .unwrap_or(tracking)
+        .to_owned();
+    match repo.find_reference(tracking) {
+        Ok(reference) => {
+            let (ahead, behind) =
+                repo.graph_ahead_behind(local, reference.peel_to_commit()?.id())?;
+            Ok(Upstream::Present {
+                name,
+                ahead_behind: AheadBehind { ahead, behind },
+            })
+        }
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(Upstream::Gone { name }),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Gets the fetch URL of a [remote], or [`None`] if there is no such remote.
+///
+/// [remote]: https://git-scm.com/docs/gitglossary#def_remote
+pub fn query_remote_url(repo: &Repository, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+    match repo.find_remote(remote) {
+        Ok(remote) => Ok(remote.url().map(RemoteUrl::parse)),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Gets the information about the commit [HEAD] points to, or [`None`] if HEAD is unborn.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_last_commit(repo: &Repository) -> Result<Option<LastCommit>, Error> {
+    let commit = match repo.head() {
+        Ok(head) => head.peel_to_commit()?,
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+            return Ok(None)
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let author = commit.author();
+    Ok(Some(LastCommit {
+        id: commit.id().into(),
+        time: unix_time(commit.time().seconds()),
+        author: String::from_utf8_lossy(author.name_bytes()).into_owned(),
+        author_email: String::from_utf8_lossy(author.email_bytes()).into_owned(),
+        summary: String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default()).into_owned(),
+    }))
+}
+
+/// Gets the names by which a commit can be described, usually a [detached HEAD]'s target.
+///
+/// See [`Repository::query_commit_description`][`super::Repository::query_commit_description`].
+///
+/// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_commit_description(
+    repo: &Repository,
+    id: ObjectId,
+) -> Result<CommitDescription, Error> {
+    let oid = Oid::from_bytes(id.as_bytes())?;
+    let object = repo.find_object(oid, None)?;
+    let describe = |options: &DescribeOptions| match object.describe(options) {
+        Ok(describe) => describe
+            .format(Some(DescribeFormatOptions::new().abbreviated_size(7)))
+            .map(Some),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    let tag = describe(
+        DescribeOptions::new()
+            .describe_tags()
+            .max_candidates_tags(0),
+    )?;
+    let describe = match tag {
+        Some(_) => None,
+        None => describe(DescribeOptions::new().describe_tags())?,
+    };
+
+    let mut nearest_branch: Option<NearestBranch> = None;
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        let tip = match branch.get().target() {
+            Some(tip) => tip,
+            None => continue,
+        };
+        if tip != oid && !repo.graph_descendant_of(tip, oid)? {
+            continue;
+        }
+        let (distance, _) = repo.graph_ahead_behind(tip, oid)?;
+        let is_nearer = match &nearest_branch {
+            Some(nearest) => distance < nearest.distance,
+            None => true,
+        };
+        if is_nearer {
+            nearest_branch = Some(NearestBranch {
+                name: String::from_utf8_lossy(branch.name_bytes()?).into_owned(),
+                distance,
+            });
+        }
+    }
+
+    Ok(CommitDescription {
+        id,
+        tag,
+        describe,
+        nearest_branch,
+    })
+}
+
+/// Gets the [summary][`StatusSummary`] of a repository's [status].
+///
+/// Every file is counted, including untracked files,
+/// except for those inside untracked directories, which are counted once per directory.
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_status_summary(repo: &Repository) -> Result<StatusSummary, Error> {
+    query_bounded_status_summary(
+        repo,
+        &StatusLimits {
+            untracked_files: UntrackedFiles::Normal,
+            ..StatusLimits::default()
+        },
+    )
+}
+
+/// Gets the [summary][`StatusSummary`] of a repository's [status] within some [`StatusLimits`].
+///
+/// See [`Repository::query_status_summary`][`super::Repository::query_status_summary`].
+/// When the time budget runs out,
+/// the status is left being computed in a background thread.
+///
+/// # Note
+///
+/// libgit2 cannot stop a status early: it compares every file before any is counted.
+/// So the limits bound the counts and the time waited, but not the scan itself:
+/// [`max_files`][`StatusLimits::max_files`] saves no work,
+/// and the background thread keeps scanning after the time budget ran out, until it is done.
+///
+/// libgit2 implements neither the [untracked cache] nor the [file system monitor],
+/// so `core.untrackedCache` and `core.fsmonitor` have no effect on this query.
+/// In repositories that rely on them, [skip untracked files][`UntrackedFiles::No`]
+/// and set a time budget, or use the [`cli`][`super::cli`] backend.
+///
+/// [status]: https://git-scm.com/docs/git-status
+/// [untracked cache]: https://git-scm.com/docs/git-update-index#_untracked_cache
+/// [file system monitor]: https://git-scm.com/docs/git-config#Documentation/git-config.txt-corefsmonitor
+pub fn query_bounded_status_summary(
+    repo: &Repository,
+    limits: &StatusLimits,
+) -> Result<StatusSummary, Error> {
+    let untracked_files = resolve_untracked_files(repo, limits.untracked_files)?;
+    let max_files = limits.max_files;
+
+    let time_budget = match limits.time_budget {
+        Some(time_budget) => time_budget,
+        None => return count_status(repo, untracked_files, max_files),
+    };
+
+    // `Repository` cannot be shared between threads, so the thread opens its own.
+    let path = repo.path().to_owned();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = Repository::open(&path)
+            .map_err(Error::from)
+            .and_then(|repo| count_status(&repo, untracked_files, max_files));
+        // The receiver is gone when the time budget ran out.
+        let _ = sender.send(result);
+    });
+    match receiver.recv_timeout(time_budget) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Ok(StatusSummary {
+            incomplete: true,
+            ..StatusSummary::default()
+        }),
+        Err(RecvTimeoutError::Disconnected) => Err(Error::new(
+            ErrorKind::Backend,
+            "the status thread panicked.",
+        )),
+    }
+}
+
+/// Gets a [detailed summary][`DetailedStatusSummary`] of a repository's [status].
+///
+/// See [`Repository::query_detailed_status_summary`][`super::Repository::query_detailed_status_summary`].
+/// The staging area is compared with HEAD and the working tree with the staging area,
+/// each diff looking for renames and copies as [`RenameDetection`][`super::RenameDetection`] tells.
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_detailed_status_summary(
+    repo: &Repository,
+    options: &DetailedStatusOptions,
+) -> Result<DetailedStatusSummary, Error> {
+    let untracked_files = resolve_untracked_files(repo, options.untracked_files)?;
+    let mut diff_options = DiffOptions::new();
+    diff_options
+        .include_typechange(true)
+        .include_untracked(untracked_files != UntrackedFiles::No)
+        .recurse_untracked_dirs(untracked_files == UntrackedFiles::All)
+        .include_ignored(options.ignored_files)
+        .recurse_ignored_dirs(false)
+        .ignore_submodules(options.submodules == Submodules::Ignore);
+
+    let mut staging =
+        repo.diff_tree_to_index(head_tree(repo)?.as_ref(), None, Some(&mut diff_options))?;
+    let mut working_tree = repo.diff_index_to_workdir(None, Some(&mut diff_options))?;
+    if let Some(renames) = options.renames {
+        let mut find_options = DiffFindOptions::new();
+        find_options
+            .renames(true)
+            .rename_threshold(renames.threshold.into())
+            .copies(renames.copies)
+            .copy_threshold(renames.threshold.into());
+        staging.find_similar(Some(&mut find_options))?;
+        // As in libgit2's status, untracked files can be the new names of deleted files.
+        working_tree.find_similar(Some(find_options.for_untracked(true)))?;
+    }
+
+    let mut summary = DetailedStatusSummary::default();
+    for delta in staging.deltas() {
+        let staging = &mut summary.staging;
+        match delta.status() {
+            Delta::Added => staging.added += 1,
+            Delta::Deleted => staging.deleted += 1,
+            Delta::Renamed => staging.renamed += 1,
+            Delta::Copied => staging.copied += 1,
+            Delta::Typechange => staging.typechanged += 1,
+            Delta::Modified => staging.modified += 1,
+            // The conflicts are counted once, from the working tree.
+            _ => {}
+        }
+    }
+    for delta in working_tree.deltas() {
+        let working_tree = &mut summary.working_tree;
+        match delta.status() {
+            Delta::Conflicted => summary.conflicted += 1,
+            Delta::Untracked => summary.untracked += 1,
+            Delta::Ignored => summary.ignored += 1,
+            Delta::Deleted => working_tree.deleted += 1,
+            Delta::Renamed => working_tree.renamed += 1,
+            Delta::Copied => working_tree.copied += 1,
+            Delta::Typechange => working_tree.typechanged += 1,
+            Delta::Modified => working_tree.modified += 1,
+            _ => {}
+        }
+    }
+
+    Ok(summary)
+}
+
+fn resolve_untracked_files(
+    repo: &Repository,
+    untracked_files: UntrackedFiles,
+) -> Result<UntrackedFiles, Error> {
+    if untracked_files != UntrackedFiles::FromConfig {
+        return Ok(untracked_files);
+    }
+    match repo.config()?.get_string("status.showUntrackedFiles") {
+        Ok(value) => Ok(match value.to_ascii_lowercase().as_str() {
+            "no" | "false" | "off" | "0" => UntrackedFiles::No,
+            "all" => UntrackedFiles::All,
+            _ => UntrackedFiles::Normal,
+        }),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(UntrackedFiles::Normal),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn count_status(
+    repo: &Repository,
+    untracked_files: UntrackedFiles,
+    max_files: Option<usize>,
+) -> Result<StatusSummary, Error> {
+    let mut summary = StatusSummary::default();
+    let mut files = 0;
+
+    // The whole status is computed first, the limit only applies to the counts.
+    for status in repo
+        .statuses(Some(
+            StatusOptions::new()
+                .include_untracked(untracked_files != UntrackedFiles::No)
+                .recurse_untracked_dirs(untracked_files == UntrackedFiles::All)
+                .renames_from_rewrites(false)
+                .renames_head_to_index(false)
+                .renames_index_to_workdir(false),
+        ))?
+        .iter()
+    {
+        let status = status.status();
+
+        if status.is_empty() {
+            continue;
+        }
+
+        if max_files.is_some_and(|max_files| files >= max_files) {
+            summary.incomplete = true;
+            break;
+        }
+        files += 1;
+
+        count_change(
+            &mut summary.staging,
+            status.is_index_new(),
+            status.is_index_deleted(),
+            (Status::INDEX_MODIFIED | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE)
+                .intersects(status),
+        );
+        count_change(
+            &mut summary.working_tree,
+            status.is_wt_new(),
+            status.is_wt_deleted(),
+            (Status::WT_MODIFIED | Status::WT_RENAMED | Status::WT_TYPECHANGE).intersects(status),
+        );
+
+        if status.is_conflicted() {
+            summary.conflicted += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn count_change(changes: &mut ChangeSummary, added: bool, deleted: bool, modified: bool) {
+    if added {
+        changes.added += 1;
+    } else if deleted {
+        changes.deleted += 1;
+    } else if modified {
+        changes.modified += 1;
+    }
+}
+
+/// Gets the [line counts][`DiffStats`] of the changes in a repository's
+/// [staging area] and [working tree].
+///
+/// See [`Repository::query_diff_stats`][`super::Repository::query_diff_stats`].
+/// Without `max_size`, libgit2's default of 512 MiB applies.
+///
+/// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+pub fn query_diff_stats(repo: &Repository, max_size: Option<u64>) -> Result<DiffStats, Error> {
+    let mut options = DiffOptions::new();
+    if let Some(max_size) = max_size {
+        options.max_size(i64::try_from(max_size).unwrap_or(i64::MAX));
+    }
+
+    let staging = repo.diff_tree_to_index(head_tree(repo)?.as_ref(), None, Some(&mut options))?;
+    let working_tree = repo.diff_index_to_workdir(None, Some(&mut options))?;
+
+    Ok(DiffStats {
+        working_tree: count_lines(&working_tree)?,
+        staging: count_lines(&staging)?,
+    })
+}
+
+/// Gets the tree of the commit HEAD points to, or [`None`] if HEAD is unborn.
+fn head_tree(repo: &Repository) -> Result<Option<Tree<'_>>, Error> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_tree()?)),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn count_lines(diff: &Diff) -> Result<LineChanges, Error> {
+    let stats = diff.stats()?;
+    Ok(LineChanges {
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// Gets the [superproject] of a repository, if it is a [submodule].
+///
+/// See [`Repository::query_superproject`][`super::Repository::query_superproject`].
+///
+/// [superproject]: https://git-scm.com/docs/gitglossary#def_superproject
+/// [submodule]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_superproject(repo: &Repository) -> Result<Option<Superproject>, Error> {
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir,
+        None => return Ok(None),
+    };
+    let superproject = match workdir.parent().map(Repository::discover) {
+        Some(Ok(superproject)) => superproject,
+        Some(Err(err)) if err.code() != ErrorCode::NotFound => return Err(err.into()),
+        _ => return Ok(None),
+    };
+    let path = match superproject
+        .workdir()
+        .and_then(|super_workdir| workdir.strip_prefix(super_workdir).ok())
+    {
+        Some(path) => path.to_owned(),
+        None => return Ok(None),
+    };
+
+    // Submodules are recorded as gitlinks, which have their own file mode.
+    let recorded = match superproject.index()?.get_path(&path, 0) {
+        Some(entry) if entry.mode == 0o160000 => entry.id,
+        _ => return Ok(None),
+    };
+    let head = match repo.head() {
+        Ok(head) => head.target(),
+        Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(Some(Superproject {
+        worktree: superproject.workdir().unwrap_or(workdir).to_owned(),
+        path,
+        out_of_date: head != Some(recorded),
+    }))
+}
+
+/// Gets the [summary][`SubmoduleSummary`] of the changes in a repository's [submodules].
+///
+/// Each submodule's `ignore` setting is respected.
+///
+/// [submodules]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_submodule_summary(repo: &Repository) -> Result<SubmoduleSummary, Error> {
+    let mut summary = SubmoduleSummary::default();
+    for submodule in repo.submodules()? {
+        let name = match submodule.name() {
+            Some(name) => name,
+            None => continue,
+        };
+        let status = repo.submodule_status(name, SubmoduleIgnore::Unspecified)?;
+        if status.is_wd_modified() {
+            summary.out_of_date += 1;
+        }
+        if (SubmoduleStatus::WD_INDEX_MODIFIED
+            | SubmoduleStatus::WD_WD_MODIFIED
+            | SubmoduleStatus::WD_UNTRACKED)
+            .intersects(status)
+        {
+            summary.modified += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// Gets the mode of the [sparse checkout] of a repository's working tree, if any.
+///
+/// See [`Repository::query_sparse_checkout`][`super::Repository::query_sparse_checkout`].
+///
+/// [sparse checkout]: https://git-scm.com/docs/git-sparse-checkout
+pub fn query_sparse_checkout(repo: &Repository) -> Result<Option<SparseCheckout>, Error> {
+    let config = repo.config()?;
+    // `git sparse-checkout` writes its settings to the per-worktree configuration
+    // when `extensions.worktreeConfig` is set, which libgit2 does not read.
+    let worktree_config_path = repo.path().join("config.worktree");
+    let worktree_config = match config.get_bool("extensions.worktreeConfig") {
+        Ok(true) if worktree_config_path.is_file() => Some(Config::open(&worktree_config_path)?),
+        Ok(_) => None,
+        Err(err) if err.code() == ErrorCode::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    let get_bool = |name: &str| -> Result<bool, Error> {
+        for config in worktree_config.iter().chain(Some(&config)) {
+            match config.get_bool(name) {
+                Ok(value) => return Ok(value),
+                Err(err) if err.code() == ErrorCode::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(false)
+    };
+    Ok(SparseCheckout::from_config(
+        get_bool("core.sparseCheckout")?,
+        get_bool("core.sparseCheckoutCone")?,
+    ))
+}
+
+/// Gets the [summary][`LfsSummary`] of the files managed by [Git LFS]
+/// in a repository's working tree.
+///
+/// See [`Repository::query_lfs_summary`][`super::Repository::query_lfs_summary`].
+///
+/// [Git LFS]: https://git-lfs.com/
+pub fn query_lfs_summary(repo: &Repository) -> Result<LfsSummary, Error> {
+    let worktree = match repo.workdir() {
+        Some(worktree) => worktree,
+        None => return Ok(LfsSummary::default()),
+    };
+    let index = repo.index()?;
+    let (mut lfs_paths, mut lockable_paths) = (Vec::new(), Vec::new());
+    for path in index
+        .iter()
+        // Regular files, leaving out the entries of a conflict's sides.
+        .filter(|entry| entry.mode & 0o170000 == 0o100000 && entry.flags & 0x3000 == 0)
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+    {
+        let attr = |name| {
+            repo.get_attr(Path::new(&path), name, AttrCheckFlags::default())
+                .map(AttrValue::from_string)
+        };
+        if attr("filter")? == AttrValue::String("lfs") {
+            lfs_paths.push(path.clone());
+        }
+        if attr("lockable")? == AttrValue::True {
+            lockable_paths.push(path);
+        }
+    }
+    Ok(LfsSummary::from_tracked_files(
+        worktree,
+        lfs_paths,
+        lockable_paths,
+    ))
+}
+
+/// Gets the [operation][`Operation`] in progress in a repository, if any.
+///
+/// See [`Operation::from_git_dir`].
+pub fn query_operation(repo: &Repository) -> Option<Operation> {
+    Operation::from_git_dir(repo.path())
+}
+
+/// Gets a repository's [stashes][`Stash`], the most recent first.
+///
+/// See [`Repository::query_stashes`][`super::Repository::query_stashes`].
+pub fn query_stashes(repo: &Repository) -> Result<Vec<Stash>, Error> {
+    // A missing `refs/stash` reflog reads as an empty one.
+    let reflog = repo.reflog("refs/stash")?;
+    let stashes = reflog
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            Stash::from_reflog(
+                index,
+                entry.id_new().into(),
+                String::from_utf8_lossy(entry.message_bytes().unwrap_or_default()).into_owned(),
+                entry.committer().when().seconds(),
+            )
+        })
+        .collect();
+    Ok(stashes)
+}
+
+/// Gets the number of [stashes][`Stash`] in a repository,
+/// only counting those created on `branch` if it is given.
+///
+/// See [`Repository::query_stash_count`][`super::Repository::query_stash_count`].
+pub fn query_stash_count(repo: &Repository, branch: Option<&str>) -> Result<usize, Error> {
+    Ok(count_stashes(&query_stashes(repo)?, branch))
+}
+
+impl super::Repository for Repository {
+    fn git_dir(&self) -> &Path {
+        self.path()
+    }
+
+    fn work_dir(&self) -> Option<&Path> {
+        self.workdir()
+    }
+
+    fn query_head(&self) -> Result<Head, Error> {
+        query_head(self)
+    }
+
+    fn query_push_target(&self, branch: &str) -> Result<Upstream, Error> {
+        query_push_target(self, branch)
+    }
+
+    fn query_remote_url(&self, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+        query_remote_url(self, remote)
+    }
+
+    fn query_last_commit(&self) -> Result<Option<LastCommit>, Error> {
+        query_last_commit(self)
+    }
+
+    fn query_stashes(&self) -> Result<Vec<Stash>, Error> {
+        query_stashes(self)
+    }
+
+    fn query_commit_description(&self, id: ObjectId) -> Result<CommitDescription, Error> {
+        query_commit_description(self, id)
+    }
+
+    fn query_status_summary(&self, limits: &StatusLimits) -> Result<StatusSummary, Error> {
+        query_bounded_status_summary(self, limits)
+    }
+
+    fn query_detailed_status_summary(
+        &self,
+        options: &DetailedStatusOptions,
+    ) -> Result<DetailedStatusSummary, Error> {
+        query_detailed_status_summary(self, options)
+    }
+
+    fn query_diff_stats(&self, max_size: Option<u64>) -> Result<DiffStats, Error> {
+        query_diff_stats(self, max_size)
+    }
+
+    fn query_superproject(&self) -> Result<Option<Superproject>, Error> {
+        query_superproject(self)
+    }
+
+    fn query_submodule_summary(&self) -> Result<SubmoduleSummary, Error> {
+        query_submodule_summary(self)
+    }
+
+    fn query_sparse_checkout(&self) -> Result<Option<SparseCheckout>, Error> {
+        query_sparse_checkout(self)
+    }
+
+    fn query_lfs_summary(&self) -> Result<LfsSummary, Error> {
+        query_lfs_summary(self)
+    }
+}
+
+impl From<::git2::Error> for Error {
+    fn from(err: ::git2::Error) -> Self {
+        let kind = match err.code() {
+            ErrorCode::NotFound => ErrorKind::NotFound,
+            _ => ErrorKind::Backend,
+        };
+        Error::new(kind, err.message())
+    }
+}
+
+impl From<Oid> for ObjectId {
+    fn from(oid: Oid) -> Self {
+        // libgit2 only supports SHA-1, whose 20 bytes are always a valid `ObjectId`.
+        ObjectId::from_bytes(oid.as_bytes()).expect("an `Oid` has 20 bytes")
+    }
+}
@@ -0,0 +1,575 @@
+mod query_operation {
+    use crate::env::git::{git2::query_operation, Operation, Progress};
+    use ::git2::Repository;
+    use std::fs;
+
+    #[test]
+    fn reads_the_operation_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert_eq!(query_operation(&repo), None);
+
+        let rebase_dir = repo.path().join("rebase-merge");
+        fs::create_dir(&rebase_dir).unwrap();
+        fs::write(rebase_dir.join("interactive"), "").unwrap();
+        fs::write(rebase_dir.join("msgnum"), "3\n").unwrap();
+        fs::write(rebase_dir.join("end"), "7\n").unwrap();
+        fs::write(rebase_dir.join("head-name"), "refs/heads/feature\n").unwrap();
+        assert_eq!(
+            query_operation(&repo),
+            Some(Operation::Rebase {
+                interactive: true,
+                progress: Some(Progress {
+                    current: 3,
+                    total: 7
+                }),
+                branch: Some(String::from("feature")),
+            })
+        );
+
+        fs::remove_dir_all(&rebase_dir).unwrap();
+        fs::write(repo.path().join("MERGE_HEAD"), "").unwrap();
+        assert_eq!(query_operation(&repo), Some(Operation::Merge));
+    }
+}
+
+mod query_commit_description {
+    use crate::env::git::{git2::query_commit_description, NearestBranch};
+    use ::git2::{Oid, Repository, Signature};
+
+    fn commit(repo: &Repository, message: &str) -> Oid {
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            parent.iter().collect::<Vec<_>>().as_slice(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn prefers_tags_then_branches() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first");
+        let second = commit(&repo, "second");
+        commit(&repo, "third");
+        let tagged = repo.find_object(first, None).unwrap();
+        repo.tag_lightweight("v1.0.0", &tagged, false).unwrap();
+
+        let description = query_commit_description(&repo, first.into()).unwrap();
+        assert_eq!(description.tag.as_deref(), Some("v1.0.0"));
+
+        let description = query_commit_description(&repo, second.into()).unwrap();
+        assert_eq!(description.tag, None);
+        assert_eq!(
+            description.describe,
+            Some(format!("v1.0.0-1-g{}", &second.to_string()[..7]))
+        );
+        assert_eq!(
+            description.nearest_branch,
+            Some(NearestBranch {
+                name: repo.head().unwrap().shorthand().unwrap().to_owned(),
+                distance: 1,
+            })
+        );
+    }
+}
+
+mod query_bounded_status_summary {
+    use crate::env::git::{git2::query_bounded_status_summary, StatusLimits, UntrackedFiles};
+    use ::git2::Repository;
+    use std::{
+        fs,
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn limits_the_files_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        for name in ["a", "b", "c", "d"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        // Staged and untracked files count toward the same limit.
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a")).unwrap();
+        index.add_path(Path::new("b")).unwrap();
+        index.write().unwrap();
+
+        let limits = StatusLimits {
+            max_files: Some(3),
+            untracked_files: UntrackedFiles::Normal,
+            ..StatusLimits::default()
+        };
+        let status = query_bounded_status_summary(&repo, &limits).unwrap();
+        assert_eq!(status.staging.added + status.working_tree.added, 3);
+        assert!(status.incomplete);
+
+        let limits = StatusLimits {
+            max_files: Some(4),
+            ..limits
+        };
+        let status = query_bounded_status_summary(&repo, &limits).unwrap();
+        assert_eq!((status.staging.added, status.working_tree.added), (2, 2));
+        assert!(!status.incomplete);
+
+        let limits = StatusLimits {
+            untracked_files: UntrackedFiles::No,
+            ..StatusLimits::default()
+        };
+        let status = query_bounded_status_summary(&repo, &limits).unwrap();
+        assert!(!status.working_tree.any_changes());
+        assert!(!status.incomplete);
+
+        repo.config()
+            .unwrap()
+            .set_str("status.showUntrackedFiles", "no")
+            .unwrap();
+        let status = query_bounded_status_summary(&repo, &StatusLimits::default()).unwrap();
+        assert!(!status.working_tree.any_changes());
+    }
+
+    #[test]
+    fn waits_no_longer_than_the_time_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        for i in 0..1000 {
+            fs::write(dir.path().join(i.to_string()), "").unwrap();
+        }
+
+        let limits = StatusLimits {
+            time_budget: Some(Duration::ZERO),
+            untracked_files: UntrackedFiles::All,
+            ..StatusLimits::default()
+        };
+        let start = Instant::now();
+        let status = query_bounded_status_summary(&repo, &limits).unwrap();
+        let elapsed = start.elapsed();
+        if status.incomplete {
+            assert!(!status.any_changes());
+            // Opening the repository and scanning the files is left to the thread.
+            assert!(elapsed < Duration::from_millis(100), "{:?}", elapsed);
+        } else {
+            assert_eq!(status.working_tree.added, 1000);
+        }
+    }
+}
+
+mod query_diff_stats {
+    use crate::env::git::{git2::query_diff_stats, DiffStats, LineChanges};
+    use ::git2::{Repository, Signature};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn counts_changed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut index = repo.index().unwrap();
+        fs::write(dir.path().join("a"), "1\n2\n3\n").unwrap();
+        index.add_path(Path::new("a")).unwrap();
+        index.write().unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])
+            .unwrap();
+
+        fs::write(dir.path().join("a"), "1\n2\n3\n4\n").unwrap();
+        fs::write(dir.path().join("b"), "1\n2\n").unwrap();
+        index.add_path(Path::new("a")).unwrap();
+        index.add_path(Path::new("b")).unwrap();
+        index.write().unwrap();
+        fs::write(dir.path().join("a"), "1\n3\n4\n5\n").unwrap();
+        fs::write(dir.path().join("c"), "untracked\n").unwrap();
+
+        assert_eq!(
+            query_diff_stats(&repo, None).unwrap(),
+            DiffStats {
+                working_tree: LineChanges {
+                    insertions: 1,
+                    deletions: 1,
+                },
+                staging: LineChanges {
+                    insertions: 3,
+                    deletions: 0,
+                },
+            }
+        );
+        assert_eq!(
+            query_diff_stats(&repo, Some(4)).unwrap().staging,
+            LineChanges {
+                insertions: 2,
+                deletions: 0,
+            }
+        );
+    }
+}
+
+mod query_detailed_status_summary {
+    use crate::env::git::{
+        git2::query_detailed_status_summary,
+        test::{git, git_command},
+        DetailedChangeSummary, DetailedStatusOptions, RenameDetection,
+    };
+    use ::git2::Repository;
+    use std::fs;
+
+    #[test]
+    fn detects_renames_above_the_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        let lines = (0..10).map(|i| format!("line {}\n", i)).collect::<String>();
+        fs::write(dir.path().join("a"), &lines).unwrap();
+        git(dir.path(), &["add", "a"]);
+        git(dir.path(), &["commit", "-m", "first"]);
+
+        // Renamed with 8 of the 10 lines kept.
+        git(dir.path(), &["mv", "a", "b"]);
+        let edited = lines
+            .replacen("line 0", "zero", 1)
+            .replacen("line 9", "nine", 1);
+        fs::write(dir.path().join("b"), edited).unwrap();
+        git(dir.path(), &["add", "b"]);
+        fs::write(dir.path().join("c"), "untracked\n").unwrap();
+
+        let repo = Repository::open(dir.path()).unwrap();
+        let summary =
+            query_detailed_status_summary(&repo, &DetailedStatusOptions::default()).unwrap();
+        assert_eq!(
+            summary.staging,
+            DetailedChangeSummary {
+                renamed: 1,
+                ..DetailedChangeSummary::default()
+            }
+        );
+        assert_eq!(summary.untracked, 1);
+
+        let options = DetailedStatusOptions {
+            renames: Some(RenameDetection {
+                threshold: 90,
+                copies: false,
+            }),
+            ..DetailedStatusOptions::default()
+        };
+        let summary = query_detailed_status_summary(&repo, &options).unwrap();
+        assert_eq!(
+            summary.staging,
+            DetailedChangeSummary {
+                added: 1,
+                deleted: 1,
+                ..DetailedChangeSummary::default()
+            }
+        );
+
+        // Copied from a modified file.
+        git(dir.path(), &["reset", "--hard"]);
+        fs::write(dir.path().join("b"), &lines).unwrap();
+        fs::write(dir.path().join("a"), lines.replacen("line 0", "zero", 1)).unwrap();
+        git(dir.path(), &["add", "a", "b"]);
+        let options = DetailedStatusOptions {
+            renames: Some(RenameDetection {
+                threshold: 50,
+                copies: true,
+            }),
+            ..DetailedStatusOptions::default()
+        };
+        let summary = query_detailed_status_summary(&repo, &options).unwrap();
+        assert_eq!(
+            summary.staging,
+            DetailedChangeSummary {
+                modified: 1,
+                copied: 1,
+                ..DetailedChangeSummary::default()
+            }
+        );
+    }
+
+    #[test]
+    fn counts_conflicts_once() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        fs::write(dir.path().join("a"), "base\n").unwrap();
+        git(dir.path(), &["add", "a"]);
+        git(dir.path(), &["commit", "-m", "base"]);
+        git(dir.path(), &["checkout", "-b", "other"]);
+        fs::write(dir.path().join("a"), "other\n").unwrap();
+        git(dir.path(), &["commit", "-am", "other"]);
+        git(dir.path(), &["checkout", "main"]);
+        fs::write(dir.path().join("a"), "main\n").unwrap();
+        git(dir.path(), &["commit", "-am", "main"]);
+        let output = git_command(dir.path())
+            .args(["merge", "other"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        let repo = Repository::open(dir.path()).unwrap();
+        let summary =
+            query_detailed_status_summary(&repo, &DetailedStatusOptions::default()).unwrap();
+        assert_eq!(summary.conflicted, 1);
+        assert!(!summary.staging.any_changes());
+        assert!(!summary.working_tree.any_changes());
+    }
+}
+
+mod query_superproject {
+    use crate::env::git::{
+        git2::{open_repo, query_submodule_summary, query_superproject},
+        test::git,
+        SubmoduleSummary,
+    };
+    use std::{fs, path::Path};
+
+    #[test]
+    fn finds_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let (main, lib) = (dir.path().join("main"), dir.path().join("lib"));
+        for path in [&main, &lib] {
+            git(dir.path(), &["init", path.to_str().unwrap()]);
+            git(path, &["commit", "--allow-empty", "-m", "first"]);
+        }
+        git(
+            &main,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "../lib",
+                "lib",
+            ],
+        );
+        git(&main, &["commit", "-m", "add lib"]);
+
+        let repo = open_repo(&main).unwrap();
+        assert_eq!(query_superproject(&repo).unwrap(), None);
+        assert!(!query_submodule_summary(&repo).unwrap().any_changes());
+
+        let submodule = open_repo(&main.join("lib")).unwrap();
+        let superproject = query_superproject(&submodule).unwrap().unwrap();
+        assert_eq!(superproject.path, Path::new("lib"));
+        assert!(!superproject.out_of_date);
+
+        git(
+            &main.join("lib"),
+            &["commit", "--allow-empty", "-m", "second"],
+        );
+        fs::write(main.join("lib/a"), "a").unwrap();
+        let submodule = open_repo(&main.join("lib")).unwrap();
+        assert!(query_superproject(&submodule).unwrap().unwrap().out_of_date);
+        assert_eq!(
+            query_submodule_summary(&repo).unwrap(),
+            SubmoduleSummary {
+                out_of_date: 1,
+                modified: 1,
+            }
+        );
+    }
+}
+
+mod query_push_target {
+    use crate::env::git::{
+        git2::{open_repo, query_head, query_push_target},
+        test::git,
+        AheadBehind, Head, Upstream,
+    };
+
+    #[test]
+    fn follows_the_push_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "first"]);
+        for remote in ["origin", "fork"] {
+            let url = format!("https://example.com/{}.git", remote);
+            git(dir.path(), &["remote", "add", remote, &url]);
+        }
+        git(
+            dir.path(),
+            &["update-ref", "refs/remotes/origin/main", "HEAD"],
+        );
+        git(dir.path(), &["branch", "--set-upstream-to=origin/main"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "second"]);
+
+        let repo = open_repo(dir.path()).unwrap();
+        let origin = Upstream::Present {
+            name: String::from("origin/main"),
+            ahead_behind: AheadBehind {
+                ahead: 1,
+                behind: 0,
+            },
+        };
+        assert_eq!(query_push_target(&repo, "main").unwrap(), origin);
+
+        git(dir.path(), &["config", "branch.main.pushRemote", "fork"]);
+        assert_eq!(
+            query_push_target(&repo, "main").unwrap(),
+            Upstream::Gone {
+                name: String::from("fork/main")
+            }
+        );
+        git(dir.path(), &["config", "push.default", "nothing"]);
+        assert_eq!(
+            query_push_target(&repo, "main").unwrap(),
+            Upstream::NotConfigured
+        );
+
+        git(
+            dir.path(),
+            &["update-ref", "-d", "refs/remotes/origin/main"],
+        );
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Gone {
+                    name: String::from("origin/main")
+                }),
+            }
+        );
+    }
+}
+
+mod query_last_commit {
+    use crate::env::git::git2::query_last_commit;
+    use ::git2::{Repository, Signature, Time};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn reads_the_head_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert_eq!(query_last_commit(&repo).unwrap(), None);
+
+        let signature =
+            Signature::new("test", "test@example.com", &Time::new(1_600_000_000, 60)).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let id = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "first line\nof the summary\n\nbody\n",
+                &tree,
+                &[],
+            )
+            .unwrap();
+
+        let last_commit = query_last_commit(&repo).unwrap().unwrap();
+        assert_eq!(last_commit.id, id.into());
+        assert_eq!(
+            last_commit.time,
+            UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+        );
+        assert_eq!(last_commit.author, "test");
+        assert_eq!(last_commit.author_email, "test@example.com");
+        assert_eq!(last_commit.summary, "first line of the summary");
+    }
+}
+
+mod query_stashes {
+    use crate::env::git::git2::{query_stash_count, query_stashes};
+    use ::git2::{Repository, Signature};
+    use std::fs;
+
+    #[test]
+    fn lists_the_most_recent_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        assert_eq!(query_stashes(&repo).unwrap(), []);
+
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        fs::write(dir.path().join("a"), "a").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path("a".as_ref()).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        fs::write(dir.path().join("a"), "b").unwrap();
+        repo.stash_save(&signature, "on main", None).unwrap();
+        repo.branch("other", &repo.find_commit(commit).unwrap(), false)
+            .unwrap();
+        repo.set_head("refs/heads/other").unwrap();
+        fs::write(dir.path().join("a"), "c").unwrap();
+        let id = repo.stash_save(&signature, "on other", None).unwrap();
+
+        let stashes = query_stashes(&repo).unwrap();
+        assert_eq!(stashes.len(), 2);
+        assert_eq!(stashes[0].index, 0);
+        assert_eq!(stashes[0].id, id.into());
+        assert_eq!(stashes[0].message, "On other: on other");
+        assert_eq!(stashes[0].branch.as_deref(), Some("other"));
+        assert_eq!(stashes[1].branch.as_deref(), Some("main"));
+        assert_eq!(query_stash_count(&repo, None).unwrap(), 2);
+        assert_eq!(query_stash_count(&repo, Some("main")).unwrap(), 1);
+        assert_eq!(query_stash_count(&repo, Some("topic")).unwrap(), 0);
+    }
+}
+
+mod query_sparse_checkout {
+    use crate::env::git::{
+        git2::{open_repo, query_lfs_summary, query_sparse_checkout},
+        test::git,
+        LfsSummary, SparseCheckout,
+    };
+    use std::fs;
+
+    #[test]
+    fn reads_the_worktree_config() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        fs::create_dir(dir.path().join("d")).unwrap();
+        fs::write(dir.path().join("d/a"), "a").unwrap();
+        let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:0\nsize 1\n";
+        fs::write(dir.path().join("b.bin"), pointer).unwrap();
+        fs::write(dir.path().join("c.txt"), pointer).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.bin filter=lfs\nd/a lockable\n",
+        )
+        .unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "first"]);
+        assert_eq!(
+            query_sparse_checkout(&open_repo(dir.path()).unwrap()).unwrap(),
+            None
+        );
+
+        git(dir.path(), &["config", "extensions.worktreeConfig", "true"]);
+        git(dir.path(), &["sparse-checkout", "set", "--cone", "d"]);
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(
+            query_sparse_checkout(&repo).unwrap(),
+            Some(SparseCheckout::Cone)
+        );
+        assert_eq!(
+            query_lfs_summary(&repo).unwrap(),
+            LfsSummary {
+                pointers: 1,
+                read_only: 0,
+            }
+        );
+
+        // Only the lockable files count as read-only.
+        for path in ["d/a", "b.bin"] {
+            let mut permissions = fs::metadata(dir.path().join(path)).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(dir.path().join(path), permissions).unwrap();
+        }
+        assert_eq!(query_lfs_summary(&repo).unwrap().read_only, 1);
+    }
+}
@@ -0,0 +1,803 @@
+//! Queries implemented with [gitoxide], through the [`gix`][::gix] crate.
+//!
+//! Unlike the [`git2`][`super::git2`] backend, it has no C dependencies,
+//! and unlike the [`cli`][`super::cli`] backend, it needs no `git` executable.
+//!
+//! [gitoxide]: https://github.com/GitoxideLabs/gitoxide
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    count_stashes, unix_time, AheadBehind, CommitDescription, DetailedStatusOptions,
+    DetailedStatusSummary, DiffStats, Error, ErrorKind, Head, LastCommit, LfsSummary, LineChanges,
+    NearestBranch, ObjectId, RemoteUrl, SparseCheckout, Stash, StatusLimits, StatusSummary,
+    SubmoduleSummary, Submodules, Superproject, UntrackedFiles, Upstream,
+};
+use ::gix::{
+    attrs::StateRef,
+    bstr::ByteSlice,
+    commit::describe::SelectRef,
+    diff::{
+        blob::{self, intern::InternedInput, sink::Counter, Algorithm},
+        index::ChangeRef,
+        rewrites::{Copies, CopySource},
+        Rewrites,
+    },
+    dir::{entry::Status as DirectoryStatus, walk::EmissionMode},
+    hash::oid,
+    head::Kind,
+    index::entry::Mode,
+    progress::Discard,
+    refs::{FullName, FullNameRef},
+    remote::Direction,
+    status::{
+        self,
+        index_worktree::{self, iter::Summary},
+        plumbing::index_as_worktree::{Change as WorktreeChange, EntryStatus},
+        tree_index::TrackRenames,
+    },
+    worktree::stack::state::attributes::Source as AttributesSource,
+    Repository, ThreadSafeRepository,
+};
+use std::{
+    convert::TryFrom,
+    fmt, fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+};
+
+/// Finds and opens a repository.
+///
+/// The search is done as git would from `dir`, including the `GIT_DIR` environment variable.
+pub fn open_repo(dir: &Path) -> Result<Repository, Error> {
+    ThreadSafeRepository::discover_with_environment_overrides(dir)
+        .map(Repository::from)
+        .map_err(|err| Error::new(ErrorKind::NotFound, err.to_string()))
+}
+
+/// Gets the information about a repository's [HEAD].
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_head(repo: &Repository) -> Result<Head, Error> {
+    match repo.head().map_err(backend_error)?.kind {
+        Kind::Symbolic(reference) => Ok(Head::Branch {
+            name: reference.name.shorten().to_str_lossy().into_owned(),
+            upstream: query_upstream(repo, reference.name.as_ref(), Direction::Fetch),
+        }),
+        Kind::Unborn(name) => Ok(Head::Unborn {
+            target: name.as_bstr().to_str_lossy().into_owned(),
+        }),
+        Kind::Detached { target, peeled } => Ok(Head::Commit(peeled.unwrap_or(target).into())),
+    }
+}
+
+/// Gets the state of a branch's [upstream][upstream branch] when `direction` is
+/// [`Fetch`][`Direction::Fetch`], or of the branch it is pushed to when it is
+/// [`Push`][`Direction::Push`].
+///
+/// [upstream branch]: https://git-scm.com/docs/gitglossary#def_upstream_branch
+pub fn query_upstream(
+    repo: &Repository,
+    branch: &FullNameRef,
+    direction: Direction,
+) -> Result<Upstream, Error> {
+    let upstream = match repo.branch_remote_tracking_ref_name(branch, direction) {
+        Some(upstream) => upstream.map_err(backend_error)?,
+        None => return Ok(Upstream::NotConfigured),
+    };
+    let name = upstream.shorten().to_str_lossy().into_owned();
+    let mut upstream = match repo.try_find_reference(upstream.as_ref()) {
+        Ok(Some(upstream)) => upstream,
+        Ok(None) => return Ok(Upstream::Gone { name }),
+        Err(err) => return Err(backend_error(err)),
+    };
+    let mut branch = repo.find_reference(branch).map_err(backend_error)?;
+    let branch = branch.peel_to_id().map_err(backend_error)?.detach();
+    let upstream = upstream.peel_to_id().map_err(backend_error)?.detach();
+    Ok(Upstream::Present {
+        name,
+        ahead_behind: AheadBehind {
+            ahead: count_commits(repo, branch, upstream)?,
+            behind: count_commits(repo, upstream, branch)?,
+        },
+    })
+}
+
+/// Gets the branch that a branch is pushed to by `git push`, as in `branch@{push}`.
+///
+/// See [`Repository::query_push_target`][`super::Repository::query_push_target`].
+pub fn query_push_target(repo: &Repository, branch: &str) -> Result<Upstream, Error> {
+    let branch = FullName::try_from(format!("refs/heads/{}", branch)).map_err(backend_error)?;
+    query_upstream(repo, branch.as_ref(), Direction::Push)
+}
+
+/// Gets the fetch URL of a [remote], or [`None`] if there is no such remote.
+///
+/// [remote]: https://git-scm.com/docs/gitglossary#def_remote
+pub fn query_remote_url(repo: &Repository, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+    let remote = match repo.try_find_remote(remote) {
+        Some(remote) => remote.map_err(backend_error)?,
+        None => return Ok(None),
+    };
+    Ok(remote
+        .url(Direction::Fetch)
+        .map(|url| RemoteUrl::parse(&url.to_bstring().to_str_lossy())))
+}
+
+/// Gets the information about the commit [HEAD] points to, or [`None`] if HEAD is unborn.
+///
+/// [HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_last_commit(repo: &Repository) -> Result<Option<LastCommit>, Error> {
+    let id = match repo.head().map_err(backend_error)?.id() {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let commit = id
+        .object()
+        .map_err(backend_error)?
+        .try_into_commit()
+        .map_err(backend_error)?;
+    let commit = commit.decode().map_err(backend_error)?;
+    let time = commit.committer().time().map_err(backend_error)?;
+    let author = commit.author();
+    Ok(Some(LastCommit {
+        id: id.detach().into(),
+        time: unix_time(time.seconds),
+        author: author.name.to_str_lossy().into_owned(),
+        author_email: author.email.to_str_lossy().into_owned(),
+        summary: commit.message_summary().to_str_lossy().into_owned(),
+    }))
+}
+
+/// Gets the names by which a commit can be described, usually a [detached HEAD]'s target.
+///
+/// See [`Repository::query_commit_description`][`super::Repository::query_commit_description`].
+///
+/// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn query_commit_description(
+    repo: &Repository,
+    id: ObjectId,
+) -> Result<CommitDescription, Error> {
+    let oid = ::gix::ObjectId::try_from(id.as_bytes()).map_err(backend_error)?;
+    let commit = repo.find_commit(oid).map_err(backend_error)?;
+
+    let (tag, describe) = match commit
+        .describe()
+        .names(SelectRef::AllTags)
+        .try_format()
+        .map_err(backend_error)?
+    {
+        Some(format) if format.is_exact_match() => (Some(format.to_string()), None),
+        Some(mut format) => {
+            format.hex_len = 7;
+            (None, Some(format.to_string()))
+        }
+        None => (None, None),
+    };
+
+    let mut nearest_branch: Option<NearestBranch> = None;
+    let references = repo.references().map_err(backend_error)?;
+    for branch in references.local_branches().map_err(backend_error)? {
+        let mut branch = branch.map_err(backend_error)?;
+        let tip = branch.peel_to_id().map_err(backend_error)?.detach();
+        let is_ancestor = tip == oid
+            || match repo.merge_base(tip, oid) {
+                Ok(base) => base == oid,
+                // A branch that shares no history with HEAD, like an orphan `gh-pages`.
+                Err(gix::repository::merge_base::Error::NotFound { .. }) => false,
+                Err(err) => return Err(backend_error(err)),
+            };
+        if !is_ancestor {
+            continue;
+        }
+        let distance = count_commits(repo, tip, oid)?;
+        let is_nearer = match &nearest_branch {
+            Some(nearest) => distance < nearest.distance,
+            None => true,
+        };
+        if is_nearer {
+            nearest_branch = Some(NearestBranch {
+                name: branch.name().shorten().to_str_lossy().into_owned(),
+                distance,
+            });
+        }
+    }
+
+    Ok(CommitDescription {
+        id,
+        tag,
+        describe,
+        nearest_branch,
+    })
+}
+
+/// Gets the [summary][`StatusSummary`] of a repository's [status] within some [`StatusLimits`].
+///
+/// See [`Repository::query_status_summary`][`super::Repository::query_status_summary`].
+/// When the time budget runs out, the status computation is interrupted.
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_status_summary(
+    repo: &Repository,
+    limits: &StatusLimits,
+) -> Result<StatusSummary, Error> {
+    let untracked_files = limits.untracked_files;
+    let max_files = limits.max_files;
+    let interrupt = Arc::new(AtomicBool::new(false));
+
+    let time_budget = match limits.time_budget {
+        Some(time_budget) => time_budget,
+        None => return count_status(repo, untracked_files, max_files, interrupt),
+    };
+
+    // `Repository` cannot be sent between threads, unlike the `ThreadSafeRepository` it comes from,
+    // which keeps how the repository was discovered, such as its work tree.
+    let sync_repo = repo.clone().into_sync();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn({
+        let interrupt = Arc::clone(&interrupt);
+        move || {
+            let repo = sync_repo.to_thread_local();
+            let result = count_status(&repo, untracked_files, max_files, interrupt);
+            // The receiver is gone when the time budget ran out.
+            let _ = sender.send(result);
+        }
+    });
+    match receiver.recv_timeout(time_budget) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            interrupt.store(true, Ordering::Relaxed);
+            Ok(StatusSummary {
+                incomplete: true,
+                ..StatusSummary::default()
+            })
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(Error::new(
+            ErrorKind::Backend,
+            "the status thread panicked.",
+        )),
+    }
+}
+
+/// Gets a repository's [stashes][`Stash`], the most recent first.
+///
+/// See [`Repository::query_stashes`][`super::Repository::query_stashes`].
+pub fn query_stashes(repo: &Repository) -> Result<Vec<Stash>, Error> {
+    let stash = match repo
+        .try_find_reference("refs/stash")
+        .map_err(backend_error)?
+    {
+        Some(stash) => stash,
+        None => return Ok(Vec::new()),
+    };
+    let mut log = stash.log_iter();
+    let lines = match log.all()? {
+        Some(lines) => lines
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(backend_error)?,
+        None => return Ok(Vec::new()),
+    };
+    // The reflog is stored oldest first, while `stash@{0}` is the most recent stash.
+    lines
+        .into_iter()
+        .rev()
+        .enumerate()
+        .map(|(index, line)| {
+            let time = line.signature.time().map_err(backend_error)?;
+            Ok(Stash::from_reflog(
+                index,
+                line.new_oid().into(),
+                line.message.to_str_lossy().into_owned(),
+                time.seconds,
+            ))
+        })
+        .collect()
+}
+
+/// Gets the number of [stashes][`Stash`] in a repository,
+/// only counting those created on `branch` if it is given.
+///
+/// See [`Repository::query_stash_count`][`super::Repository::query_stash_count`].
+pub fn query_stash_count(repo: &Repository, branch: Option<&str>) -> Result<usize, Error> {
+    Ok(count_stashes(&query_stashes(repo)?, branch))
+}
+
+/// Gets the mode of the [sparse checkout] of a repository's working tree, if any.
+///
+/// See [`Repository::query_sparse_checkout`][`super::Repository::query_sparse_checkout`].
+///
+/// [sparse checkout]: https://git-scm.com/docs/git-sparse-checkout
+pub fn query_sparse_checkout(repo: &Repository) -> Result<Option<SparseCheckout>, Error> {
+    let config = repo.config_snapshot();
+    let get_bool = |name: &str| config.boolean(name).unwrap_or(false);
+    Ok(SparseCheckout::from_config(
+        get_bool("core.sparseCheckout"),
+        get_bool("core.sparseCheckoutCone"),
+    ))
+}
+
+/// Gets the [summary][`LfsSummary`] of the files managed by [Git LFS]
+/// in a repository's working tree.
+///
+/// See [`Repository::query_lfs_summary`][`super::Repository::query_lfs_summary`].
+///
+/// [Git LFS]: https://git-lfs.com/
+pub fn query_lfs_summary(repo: &Repository) -> Result<LfsSummary, Error> {
+    let worktree = match repo.workdir() {
+        Some(worktree) => worktree,
+        None => return Ok(LfsSummary::default()),
+    };
+    let index = repo.index_or_empty().map_err(backend_error)?;
+    let mut attributes = repo
+        .attributes_only(&index, AttributesSource::WorktreeThenIdMapping)
+        .map_err(backend_error)?;
+    let mut outcome = attributes.selected_attribute_matches(["filter", "lockable"]);
+    let (mut lfs_paths, mut lockable_paths) = (Vec::new(), Vec::new());
+    for entry in index
+        .entries()
+        .iter()
+        // Regular files, leaving out the entries of a conflict's sides.
+        .filter(|entry| {
+            (entry.mode == Mode::FILE || entry.mode == Mode::FILE_EXECUTABLE)
+                && entry.stage_raw() == 0
+        })
+    {
+        let path = match entry.path(&index).to_str() {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        outcome.reset();
+        attributes
+            .at_entry(path, Some(entry.mode))
+            .map_err(backend_error)?
+            .matching_attributes(&mut outcome);
+        let mut states = outcome.iter_selected().map(|attr| attr.assignment.state);
+        if states.next() == Some(StateRef::Value("lfs".into())) {
+            lfs_paths.push(path);
+        }
+        if states.next() == Some(StateRef::Set) {
+            lockable_paths.push(path);
+        }
+    }
+    Ok(LfsSummary::from_tracked_files(
+        worktree,
+        lfs_paths,
+        lockable_paths,
+    ))
+}
+
+/// Gets a [detailed summary][`DetailedStatusSummary`] of a repository's [status].
+///
+/// See [`Repository::query_detailed_status_summary`][`super::Repository::query_detailed_status_summary`].
+/// Ignored files are only counted along with untracked files.
+///
+/// [status]: https://git-scm.com/docs/git-status
+pub fn query_detailed_status_summary(
+    repo: &Repository,
+    options: &DetailedStatusOptions,
+) -> Result<DetailedStatusSummary, Error> {
+    let mut platform = repo.status(Discard).map_err(backend_error)?;
+    platform = match options.untracked_files {
+        // gix reads `status.showUntrackedFiles` on its own.
+        UntrackedFiles::FromConfig => platform,
+        UntrackedFiles::No => platform.untracked_files(status::UntrackedFiles::None),
+        UntrackedFiles::Normal => platform.untracked_files(status::UntrackedFiles::Collapsed),
+        UntrackedFiles::All => platform.untracked_files(status::UntrackedFiles::Files),
+    };
+    if options.ignored_files {
+        platform = platform
+            .dirwalk_options(|dirwalk| dirwalk.emit_ignored(Some(EmissionMode::CollapseDirectory)));
+    }
+    if options.submodules == Submodules::Ignore {
+        platform = platform.index_worktree_submodules(None);
+    }
+    platform = match options.renames {
+        Some(renames) => {
+            let percentage = Some(f32::from(renames.threshold) / 100.0);
+            let rewrites = Rewrites {
+                copies: renames.copies.then_some(Copies {
+                    source: CopySource::FromSetOfModifiedFiles,
+                    percentage,
+                }),
+                percentage,
+                ..Rewrites::default()
+            };
+            platform
+                .tree_index_track_renames(TrackRenames::Given(rewrites))
+                .index_worktree_rewrites(rewrites)
+        }
+        None => platform
+            .tree_index_track_renames(TrackRenames::Disabled)
+            .index_worktree_rewrites(None),
+    };
+
+    // The executable bit is a mode change, while the other modes are types.
+    let kind = |mode: Mode| match mode {
+        Mode::FILE_EXECUTABLE => Mode::FILE,
+        mode => mode,
+    };
+
+    let mut summary = DetailedStatusSummary::default();
+    for item in platform.into_iter(None).map_err(backend_error)? {
+        let count = match item.map_err(backend_error)? {
+            status::Item::TreeIndex(change) => match change {
+                ChangeRef::Addition { .. } => &mut summary.staging.added,
+                ChangeRef::Deletion { .. } => &mut summary.staging.deleted,
+                ChangeRef::Modification {
+                    previous_entry_mode,
+                    entry_mode,
+                    ..
+                } if kind(previous_entry_mode) != kind(entry_mode) => {
+                    &mut summary.staging.typechanged
+                }
+                ChangeRef::Modification { .. } => &mut summary.staging.modified,
+                ChangeRef::Rewrite { copy: true, .. } => &mut summary.staging.copied,
+                ChangeRef::Rewrite { copy: false, .. } => &mut summary.staging.renamed,
+            },
+            status::Item::IndexWorktree(index_worktree::Item::DirectoryContents {
+                entry, ..
+            }) => match entry.status {
+                DirectoryStatus::Untracked => &mut summary.untracked,
+                DirectoryStatus::Ignored(_) => &mut summary.ignored,
+                _ => continue,
+            },
+            status::Item::IndexWorktree(item) => match item.summary() {
+                Some(Summary::Added) | Some(Summary::IntentToAdd) => {
+                    &mut summary.working_tree.added
+                }
+                Some(Summary::Removed) => &mut summary.working_tree.deleted,
+                Some(Summary::Modified) => &mut summary.working_tree.modified,
+                Some(Summary::TypeChange) => &mut summary.working_tree.typechanged,
+                Some(Summary::Renamed) => &mut summary.working_tree.renamed,
+                Some(Summary::Copied) => &mut summary.working_tree.copied,
+                Some(Summary::Conflict) => &mut summary.conflicted,
+                None => continue,
+            },
+        };
+        *count += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Gets the [line counts][`DiffStats`] of the changes in a repository's
+/// [staging area] and [working tree].
+///
+/// See [`Repository::query_diff_stats`][`super::Repository::query_diff_stats`].
+/// Like git, it takes files with a NUL byte in their first 8000 bytes as binary.
+/// Unlike git, it compares working tree files without applying any [filter],
+/// and leaves out symbolic links in the working tree.
+///
+/// [staging area]: https://git-scm.com/docs/gitglossary#def_index
+/// [working tree]: https://git-scm.com/docs/gitglossary#def_working_tree
+/// [filter]: https://git-scm.com/docs/gitattributes#_filter
+pub fn query_diff_stats(repo: &Repository, max_size: Option<u64>) -> Result<DiffStats, Error> {
+    let max_size = max_size.unwrap_or(u64::MAX);
+    let workdir = repo.workdir();
+    let mut stats = DiffStats::default();
+
+    let platform = repo
+        .status(Discard)
+        .map_err(backend_error)?
+        .untracked_files(status::UntrackedFiles::None)
+        .tree_index_track_renames(TrackRenames::Disabled)
+        .index_worktree_rewrites(None)
+        .index_worktree_submodules(None);
+    for item in platform.into_iter(None).map_err(backend_error)? {
+        match item.map_err(backend_error)? {
+            status::Item::TreeIndex(change) => {
+                let (before, after) = match &change {
+                    ChangeRef::Addition { entry_mode, id, .. } => {
+                        (None, Some((*entry_mode, id.as_ref())))
+                    }
+                    ChangeRef::Deletion { entry_mode, id, .. } => {
+                        (Some((*entry_mode, id.as_ref())), None)
+                    }
+                    ChangeRef::Modification {
+                        previous_entry_mode,
+                        previous_id,
+                        entry_mode,
+                        id,
+                        ..
+                    } => (
+                        Some((*previous_entry_mode, previous_id.as_ref())),
+                        Some((*entry_mode, id.as_ref())),
+                    ),
+                    ChangeRef::Rewrite { .. } => continue,
+                };
+                let read = |blob: Option<(Mode, &oid)>| match blob {
+                    Some((mode, _)) if mode.is_submodule() => Ok(None),
+                    Some((_, id)) => read_blob(repo, id, max_size),
+                    None => Ok(Some(Vec::new())),
+                };
+                if let (Some(before), Some(after)) = (read(before)?, read(after)?) {
+                    add_line_changes(&mut stats.staging, &before, &after);
+                }
+            }
+            status::Item::IndexWorktree(index_worktree::Item::Modification {
+                entry,
+                rela_path,
+                status: EntryStatus::Change(change),
+                ..
+            }) => {
+                if entry.mode.is_submodule() || entry.mode == Mode::SYMLINK {
+                    continue;
+                }
+                let after = match change {
+                    WorktreeChange::Removed => Some(Vec::new()),
+                    WorktreeChange::Modification { .. } => match workdir {
+                        Some(workdir) => {
+                            read_file(&workdir.join(rela_path.to_path_lossy()), max_size)?
+                        }
+                        None => None,
+                    },
+                    _ => None,
+                };
+                if let (Some(before), Some(after)) = (read_blob(repo, &entry.id, max_size)?, after)
+                {
+                    add_line_changes(&mut stats.working_tree, &before, &after);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Gets the [superproject] of a repository, if it is a [submodule].
+///
+/// See [`Repository::query_superproject`][`super::Repository::query_superproject`].
+///
+/// [superproject]: https://git-scm.com/docs/gitglossary#def_superproject
+/// [submodule]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_superproject(repo: &Repository) -> Result<Option<Superproject>, Error> {
+    let workdir = match repo.workdir() {
+        Some(workdir) => fs::canonicalize(workdir)?,
+        None => return Ok(None),
+    };
+    let superproject = match workdir.parent().map(::gix::discover) {
+        Some(Ok(superproject)) => superproject,
+        _ => return Ok(None),
+    };
+    let worktree = match superproject.workdir() {
+        Some(worktree) => fs::canonicalize(worktree)?,
+        None => return Ok(None),
+    };
+    let path = match workdir.strip_prefix(&worktree) {
+        Ok(path) => path.to_owned(),
+        Err(_) => return Ok(None),
+    };
+
+    let index = superproject.index_or_empty().map_err(backend_error)?;
+    let recorded = match index.entry_by_path(&::gix::path::into_bstr(&path)) {
+        Some(entry) if entry.mode == Mode::COMMIT => entry.id,
+        _ => return Ok(None),
+    };
+    let head = repo.head_id().ok().map(::gix::Id::detach);
+
+    Ok(Some(Superproject {
+        worktree,
+        path,
+        out_of_date: head != Some(recorded),
+    }))
+}
+
+/// Gets the [summary][`SubmoduleSummary`] of the changes in a repository's [submodules].
+///
+/// Each submodule's `ignore` setting is respected.
+///
+/// [submodules]: https://git-scm.com/docs/gitglossary#def_submodule
+pub fn query_submodule_summary(repo: &Repository) -> Result<SubmoduleSummary, Error> {
+    let mut summary = SubmoduleSummary::default();
+    let submodules = match repo.submodules().map_err(backend_error)? {
+        Some(submodules) => submodules,
+        None => return Ok(summary),
+    };
+    for submodule in submodules {
+        let ignore = submodule.ignore().map_err(backend_error)?;
+        let status = submodule
+            .status(ignore.unwrap_or_default(), false)
+            .map_err(backend_error)?;
+        let is_out_of_date = match (status.index_id, status.checked_out_head_id) {
+            (Some(recorded), Some(head)) => recorded != head,
+            _ => false,
+        };
+        if is_out_of_date {
+            summary.out_of_date += 1;
+        }
+        if status.changes.is_some_and(|changes| !changes.is_empty()) {
+            summary.modified += 1;
+        }
+    }
+    Ok(summary)
+}
+
+impl super::Repository for Repository {
+    fn git_dir(&self) -> &Path {
+        Repository::git_dir(self)
+    }
+
+    fn work_dir(&self) -> Option<&Path> {
+        self.workdir()
+    }
+
+    fn query_head(&self) -> Result<Head, Error> {
+        query_head(self)
+    }
+
+    fn query_push_target(&self, branch: &str) -> Result<Upstream, Error> {
+        query_push_target(self, branch)
+    }
+
+    fn query_remote_url(&self, remote: &str) -> Result<Option<RemoteUrl>, Error> {
+        query_remote_url(self, remote)
+    }
+
+    fn query_last_commit(&self) -> Result<Option<LastCommit>, Error> {
+        query_last_commit(self)
+    }
+
+    fn query_stashes(&self) -> Result<Vec<Stash>, Error> {
+        query_stashes(self)
+    }
+
+    fn query_commit_description(&self, id: ObjectId) -> Result<CommitDescription, Error> {
+        query_commit_description(self, id)
+    }
+
+    fn query_status_summary(&self, limits: &StatusLimits) -> Result<StatusSummary, Error> {
+        query_status_summary(self, limits)
+    }
+
+    fn query_detailed_status_summary(
+        &self,
+        options: &DetailedStatusOptions,
+    ) -> Result<DetailedStatusSummary, Error> {
+        query_detailed_status_summary(self, options)
+    }
+
+    fn query_diff_stats(&self, max_size: Option<u64>) -> Result<DiffStats, Error> {
+        query_diff_stats(self, max_size)
+    }
+
+    fn query_superproject(&self) -> Result<Option<Superproject>, Error> {
+        query_superproject(self)
+    }
+
+    fn query_submodule_summary(&self) -> Result<SubmoduleSummary, Error> {
+        query_submodule_summary(self)
+    }
+
+    fn query_sparse_checkout(&self) -> Result<Option<SparseCheckout>, Error> {
+        query_sparse_checkout(self)
+    }
+
+    fn query_lfs_summary(&self) -> Result<LfsSummary, Error> {
+        query_lfs_summary(self)
+    }
+}
+
+impl From<::gix::ObjectId> for ObjectId {
+    fn from(oid: ::gix::ObjectId) -> Self {
+        ObjectId::from_bytes(oid.as_bytes()).expect("an `ObjectId` has 20 or 32 bytes")
+    }
+}
+
+fn count_status(
+    repo: &Repository,
+    untracked_files: UntrackedFiles,
+    max_files: Option<usize>,
+    interrupt: Arc<AtomicBool>,
+) -> Result<StatusSummary, Error> {
+    let mut platform = repo
+        .status(Discard)
+        .map_err(backend_error)?
+        .tree_index_track_renames(TrackRenames::Disabled)
+        .index_worktree_rewrites(None)
+        .should_interrupt_owned(interrupt);
+    platform = match untracked_files {
+        // gix reads `status.showUntrackedFiles` on its own.
+        UntrackedFiles::FromConfig => platform,
+        UntrackedFiles::No => platform.untracked_files(status::UntrackedFiles::None),
+        UntrackedFiles::Normal => platform.untracked_files(status::UntrackedFiles::Collapsed),
+        UntrackedFiles::All => platform.untracked_files(status::UntrackedFiles::Files),
+    };
+
+    let mut summary = StatusSummary::default();
+    let mut files = 0;
+
+    for item in platform.into_iter(None).map_err(backend_error)? {
+        let item = item.map_err(backend_error)?;
+        let count = match &item {
+            status::Item::TreeIndex(change) => match change {
+                ChangeRef::Addition { .. } => &mut summary.staging.added,
+                ChangeRef::Deletion { .. } => &mut summary.staging.deleted,
+                ChangeRef::Modification { .. } | ChangeRef::Rewrite { .. } => {
+                    &mut summary.staging.modified
+                }
+            },
+            status::Item::IndexWorktree(item) => match item.summary() {
+                Some(Summary::Added) | Some(Summary::IntentToAdd) => {
+                    &mut summary.working_tree.added
+                }
+                Some(Summary::Removed) => &mut summary.working_tree.deleted,
+                Some(Summary::Modified)
+                | Some(Summary::TypeChange)
+                | Some(Summary::Renamed)
+                | Some(Summary::Copied) => &mut summary.working_tree.modified,
+                Some(Summary::Conflict) => &mut summary.conflicted,
+                // The file only needs its cached stat updated in the index.
+                None => continue,
+            },
+        };
+
+        if max_files.is_some_and(|max_files| files >= max_files) {
+            summary.incomplete = true;
+            break;
+        }
+        files += 1;
+        *count += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Reads a blob, unless it is larger than `max_size`.
+fn read_blob(repo: &Repository, id: &oid, max_size: u64) -> Result<Option<Vec<u8>>, Error> {
+    let header = repo.find_header(id).map_err(backend_error)?;
+    if header.size() > max_size {
+        return Ok(None);
+    }
+    let blob = repo.find_blob(id).map_err(backend_error)?;
+    Ok(Some(blob.detach().data))
+}
+
+/// Reads a file, unless it is larger than `max_size`.
+fn read_file(path: &Path, max_size: u64) -> Result<Option<Vec<u8>>, Error> {
+    if fs::metadata(path)?.len() > max_size {
+        return Ok(None);
+    }
+    Ok(Some(fs::read(path)?))
+}
+
+/// Adds the lines changed from `before` to `after` to `changes`, unless either is binary.
+fn add_line_changes(changes: &mut LineChanges, before: &[u8], after: &[u8]) {
+    let is_binary = |data: &[u8]| data[..data.len().min(8000)].contains(&0);
+    if is_binary(before) || is_binary(after) {
+        return;
+    }
+    let input = InternedInput::new(before, after);
+    let counter = blob::diff(Algorithm::Myers, &input, Counter::default());
+    changes.insertions += counter.insertions as usize;
+    changes.deletions += counter.removals as usize;
+}
+
+/// Counts the commits reachable from `from` but not from `hidden`.
+fn count_commits(
+    repo: &Repository,
+    from: ::gix::ObjectId,
+    hidden: ::gix::ObjectId,
+) -> Result<usize, Error> {
+    let walk = repo
+        .rev_walk([from])
+        .with_hidden([hidden])
+        .all()
+        .map_err(backend_error)?;
+    let mut count = 0;
+    for commit in walk {
+        commit.map_err(backend_error)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn backend_error(err: impl fmt::Display) -> Error {
+    Error::new(ErrorKind::Backend, err.to_string())
+}
@@ -0,0 +1,411 @@
+mod repository {
+    use crate::env::git::{
+        gix::{
+            open_repo, query_commit_description, query_detailed_status_summary, query_diff_stats,
+            query_head, query_last_commit, query_lfs_summary, query_push_target, query_remote_url,
+            query_sparse_checkout, query_stash_count, query_stashes, query_status_summary,
+            query_submodule_summary, query_superproject,
+        },
+        test::git,
+        AheadBehind, DetailedChangeSummary, DetailedStatusOptions, DetailedStatusSummary,
+        DiffStats, Head, HostKind, LfsSummary, LineChanges, NearestBranch, SparseCheckout,
+        StatusLimits, SubmoduleSummary, UntrackedFiles, Upstream,
+    };
+    use std::{
+        fs,
+        path::Path,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn queries_the_head_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        assert_eq!(
+            query_head(&open_repo(dir.path()).unwrap()).unwrap(),
+            Head::Unborn {
+                target: String::from("refs/heads/main")
+            }
+        );
+
+        fs::write(dir.path().join("a"), "a").unwrap();
+        git(dir.path(), &["add", "a"]);
+        git(dir.path(), &["commit", "-m", "first"]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", "https://example.com/repo.git"],
+        );
+        git(
+            dir.path(),
+            &["update-ref", "refs/remotes/origin/main", "HEAD"],
+        );
+        git(dir.path(), &["config", "branch.main.remote", "origin"]);
+        git(
+            dir.path(),
+            &["config", "branch.main.merge", "refs/heads/main"],
+        );
+        fs::write(dir.path().join("a"), "b").unwrap();
+        git(dir.path(), &["commit", "-am", "second"]);
+        fs::write(dir.path().join("a"), "c").unwrap();
+        git(dir.path(), &["stash"]);
+
+        fs::write(dir.path().join("a"), "d").unwrap();
+        fs::write(dir.path().join("b"), "b").unwrap();
+        fs::write(dir.path().join("c"), "c").unwrap();
+        git(dir.path(), &["add", "c"]);
+
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Present {
+                    name: String::from("origin/main"),
+                    ahead_behind: AheadBehind {
+                        ahead: 1,
+                        behind: 0
+                    },
+                }),
+            }
+        );
+        let status = query_status_summary(
+            &repo,
+            &StatusLimits {
+                untracked_files: UntrackedFiles::Normal,
+                ..StatusLimits::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(status.staging.added, 1);
+        assert_eq!(status.working_tree.modified, 1);
+        assert_eq!(status.working_tree.added, 1);
+        assert!(!status.incomplete);
+        assert_eq!(query_stash_count(&repo, None).unwrap(), 1);
+        assert_eq!(query_stash_count(&repo, Some("main")).unwrap(), 1);
+        assert_eq!(query_stash_count(&repo, Some("other")).unwrap(), 0);
+        let stashes = query_stashes(&repo).unwrap();
+        assert_eq!(stashes[0].index, 0);
+        assert!(stashes[0].message.starts_with("WIP on main: "));
+        assert_eq!(stashes[0].branch.as_deref(), Some("main"));
+
+        assert_eq!(
+            query_remote_url(&repo, "origin")
+                .unwrap()
+                .unwrap()
+                .host_kind,
+            HostKind::SelfHosted
+        );
+        assert_eq!(query_remote_url(&repo, "fork").unwrap(), None);
+        git(
+            dir.path(),
+            &["remote", "add", "fork", "git@github.com:me/repo.git"],
+        );
+        git(dir.path(), &["config", "remote.pushDefault", "fork"]);
+        git(dir.path(), &["config", "push.default", "current"]);
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(
+            query_push_target(&repo, "main").unwrap(),
+            Upstream::Gone {
+                name: String::from("fork/main")
+            }
+        );
+        git(
+            dir.path(),
+            &["update-ref", "refs/remotes/fork/main", "HEAD~1"],
+        );
+        assert_eq!(
+            query_push_target(&repo, "main").unwrap().ahead_behind(),
+            Some(&AheadBehind {
+                ahead: 1,
+                behind: 0
+            })
+        );
+        git(
+            dir.path(),
+            &["update-ref", "-d", "refs/remotes/origin/main"],
+        );
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head::Branch {
+                name: String::from("main"),
+                upstream: Ok(Upstream::Gone {
+                    name: String::from("origin/main")
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn counts_changed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        fs::write(dir.path().join("a"), "1\n2\n3\n").unwrap();
+        fs::write(dir.path().join("b"), "1\n").unwrap();
+        git(dir.path(), &["add", "a", "b"]);
+        git(dir.path(), &["commit", "-m", "first"]);
+
+        fs::write(dir.path().join("a"), "1\n2\n3\n4\n").unwrap();
+        fs::write(dir.path().join("c"), "1\n2\n").unwrap();
+        git(dir.path(), &["add", "a", "c"]);
+        fs::write(dir.path().join("a"), "1\n3\n4\n5\n").unwrap();
+        fs::remove_file(dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("d"), "untracked\n").unwrap();
+
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(
+            query_diff_stats(&repo, None).unwrap(),
+            DiffStats {
+                working_tree: LineChanges {
+                    insertions: 1,
+                    deletions: 2,
+                },
+                staging: LineChanges {
+                    insertions: 3,
+                    deletions: 0,
+                },
+            }
+        );
+        assert_eq!(
+            query_diff_stats(&repo, Some(4)).unwrap(),
+            DiffStats {
+                working_tree: LineChanges {
+                    insertions: 0,
+                    deletions: 1,
+                },
+                staging: LineChanges {
+                    insertions: 2,
+                    deletions: 0,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn separates_the_detailed_categories() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.path().join("a"), "the same contents\n").unwrap();
+        fs::write(dir.path().join("b"), "b\n").unwrap();
+        git(dir.path(), &["add", ".gitignore", "a", "b"]);
+        git(dir.path(), &["commit", "-m", "first"]);
+
+        git(dir.path(), &["mv", "a", "c"]);
+        fs::remove_file(dir.path().join("b")).unwrap();
+        std::os::unix::fs::symlink("c", dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("d"), "d\n").unwrap();
+        fs::write(dir.path().join("e.log"), "e\n").unwrap();
+
+        let repo = open_repo(dir.path()).unwrap();
+        let options = DetailedStatusOptions {
+            untracked_files: UntrackedFiles::Normal,
+            ignored_files: true,
+            ..DetailedStatusOptions::default()
+        };
+        assert_eq!(
+            query_detailed_status_summary(&repo, &options).unwrap(),
+            DetailedStatusSummary {
+                staging: DetailedChangeSummary {
+                    renamed: 1,
+                    ..DetailedChangeSummary::default()
+                },
+                working_tree: DetailedChangeSummary {
+                    typechanged: 1,
+                    ..DetailedChangeSummary::default()
+                },
+                untracked: 1,
+                ignored: 1,
+                conflicted: 0,
+            }
+        );
+
+        let options = DetailedStatusOptions {
+            renames: None,
+            ..options
+        };
+        let status = query_detailed_status_summary(&repo, &options).unwrap();
+        assert_eq!(status.staging.added, 1);
+        assert_eq!(status.staging.deleted, 1);
+        assert_eq!(status.staging.renamed, 0);
+    }
+
+    #[test]
+    fn prefers_tags_then_branches() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        assert_eq!(
+            query_last_commit(&open_repo(dir.path()).unwrap()).unwrap(),
+            None
+        );
+        for message in ["first", "second", "third"] {
+            git(dir.path(), &["commit", "--allow-empty", "-m", message]);
+        }
+        git(dir.path(), &["tag", "v1.0.0", "HEAD~2"]);
+        // A branch without a merge base is not a candidate for the nearest branch.
+        git(dir.path(), &["checkout", "--orphan", "gh-pages"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "pages"]);
+        git(dir.path(), &["checkout", "main"]);
+        let first = git(dir.path(), &["rev-parse", "HEAD~2"]);
+        let second = git(dir.path(), &["rev-parse", "HEAD~1"]);
+        let repo = open_repo(dir.path()).unwrap();
+
+        let last_commit = query_last_commit(&repo).unwrap().unwrap();
+        assert_eq!(last_commit.author, "test");
+        assert_eq!(last_commit.summary, "third");
+        let timestamp = git(dir.path(), &["log", "-1", "--format=%ct"]);
+        assert_eq!(
+            last_commit.time,
+            UNIX_EPOCH + Duration::from_secs(timestamp.parse().unwrap())
+        );
+
+        let id = |hex: &str| crate::env::git::ObjectId::from_hex(hex).unwrap();
+        let description = query_commit_description(&repo, id(&first)).unwrap();
+        assert_eq!(description.tag.as_deref(), Some("v1.0.0"));
+
+        let description = query_commit_description(&repo, id(&second)).unwrap();
+        assert_eq!(description.tag, None);
+        assert_eq!(
+            description.describe,
+            Some(format!("v1.0.0-1-g{}", &second[..7]))
+        );
+        assert_eq!(
+            description.nearest_branch,
+            Some(NearestBranch {
+                name: String::from("main"),
+                distance: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn finds_submodules() {
+        let dir = tempfile::tempdir().unwrap();
+        let (main, lib) = (dir.path().join("main"), dir.path().join("lib"));
+        for path in [&main, &lib] {
+            git(dir.path(), &["init", path.to_str().unwrap()]);
+            git(path, &["commit", "--allow-empty", "-m", "first"]);
+        }
+        git(
+            &main,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "../lib",
+                "lib",
+            ],
+        );
+        git(&main, &["commit", "-m", "add lib"]);
+
+        let repo = open_repo(&main).unwrap();
+        assert_eq!(query_superproject(&repo).unwrap(), None);
+        assert!(!query_submodule_summary(&repo).unwrap().any_changes());
+
+        let submodule = open_repo(&main.join("lib")).unwrap();
+        let superproject = query_superproject(&submodule).unwrap().unwrap();
+        assert_eq!(superproject.path, Path::new("lib"));
+        assert!(!superproject.out_of_date);
+
+        git(
+            &main.join("lib"),
+            &["commit", "--allow-empty", "-m", "second"],
+        );
+        fs::write(main.join("lib/a"), "a").unwrap();
+        let submodule = open_repo(&main.join("lib")).unwrap();
+        assert!(query_superproject(&submodule).unwrap().unwrap().out_of_date);
+        assert_eq!(
+            query_submodule_summary(&repo).unwrap(),
+            SubmoduleSummary {
+                out_of_date: 1,
+                modified: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn finds_sparse_checkouts_and_lfs_pointers() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init"]);
+        fs::create_dir(dir.path().join("d")).unwrap();
+        fs::write(dir.path().join("d/a"), "a").unwrap();
+        let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:0\nsize 1\n";
+        fs::write(dir.path().join("b.bin"), pointer).unwrap();
+        fs::write(dir.path().join("c.txt"), pointer).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "*.bin filter=lfs\nd/a lockable\n",
+        )
+        .unwrap();
+        git(dir.path(), &["add", "."]);
+        git(dir.path(), &["commit", "-m", "first"]);
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(query_sparse_checkout(&repo).unwrap(), None);
+        assert_eq!(
+            query_lfs_summary(&repo).unwrap(),
+            LfsSummary {
+                pointers: 1,
+                read_only: 0,
+            }
+        );
+        // Only the lockable files count as read-only.
+        for path in ["d/a", "b.bin"] {
+            let mut permissions = fs::metadata(dir.path().join(path)).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(dir.path().join(path), permissions).unwrap();
+        }
+        assert_eq!(query_lfs_summary(&repo).unwrap().read_only, 1);
+
+        git(dir.path(), &["config", "extensions.worktreeConfig", "true"]);
+        git(
+            dir.path(),
+            &["sparse-checkout", "set", "--no-cone", "/b.bin"],
+        );
+        let repo = open_repo(dir.path()).unwrap();
+        assert_eq!(
+            query_sparse_checkout(&repo).unwrap(),
+            Some(SparseCheckout::NonCone)
+        );
+        assert_eq!(query_lfs_summary(&repo).unwrap().pointers, 1);
+    }
+}
+
+mod query_status_summary {
+    use crate::env::git::{
+        gix::{open_repo, query_status_summary},
+        test::git,
+        ChangeSummary, StatusLimits, StatusSummary,
+    };
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn keeps_the_work_tree_with_a_time_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join("repo.git");
+        let work_tree = dir.path().join("work_tree");
+        fs::create_dir_all(&work_tree).unwrap();
+        git(dir.path(), &["init", "--quiet", "--bare", "repo.git"]);
+        git(&git_dir, &["config", "core.bare", "false"]);
+        git(&git_dir, &["config", "core.worktree", "../work_tree"]);
+        fs::write(work_tree.join("a"), "a").unwrap();
+
+        // The work tree has no `.git`, so it is only known from the repository's configuration.
+        let repo = open_repo(&git_dir).unwrap();
+        let expected = StatusSummary {
+            working_tree: ChangeSummary {
+                added: 1,
+                ..ChangeSummary::default()
+            },
+            ..StatusSummary::default()
+        };
+        assert_eq!(
+            query_status_summary(&repo, &StatusLimits::default()).unwrap(),
+            expected
+        );
+        let limits = StatusLimits {
+            time_budget: Some(Duration::from_secs(60)),
+            ..StatusLimits::default()
+        };
+        assert_eq!(query_status_summary(&repo, &limits).unwrap(), expected);
+    }
+}
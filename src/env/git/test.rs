@@ -0,0 +1,136 @@
+#[cfg(any(
+    feature = "env-git-git2",
+    feature = "env-git-gix",
+    feature = "env-git-cli"
+))]
+use std::{path::Path, process::Command};
+
+/// A `git` command run in `dir`, with an identity to create commits with.
+#[cfg(any(
+    feature = "env-git-git2",
+    feature = "env-git-gix",
+    feature = "env-git-cli"
+))]
+pub(crate) fn git_command(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"]);
+    command
+}
+
+/// Runs `git` in `dir` and returns its trimmed output, panicking if it fails.
+#[cfg(any(
+    feature = "env-git-git2",
+    feature = "env-git-gix",
+    feature = "env-git-cli"
+))]
+pub(crate) fn git(dir: &Path, args: &[&str]) -> String {
+    let output = git_command(dir).args(args).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+mod remote_url {
+    use crate::env::git::{HostKind, RemoteUrl};
+
+    fn parse(url: &str) -> (Option<String>, String, HostKind) {
+        let url = RemoteUrl::parse(url);
+        (url.host, url.path, url.host_kind)
+    }
+
+    #[test]
+    fn splits_hosts_and_paths() {
+        let remote = |host: &str, path: &str, kind| (Some(host.to_owned()), path.to_owned(), kind);
+        assert_eq!(
+            parse("https://github.com/owner/repo.git"),
+            remote("github.com", "owner/repo.git", HostKind::GitHub)
+        );
+        assert_eq!(
+            parse("git@github.com:owner/repo.git"),
+            remote("github.com", "owner/repo.git", HostKind::GitHub)
+        );
+        assert_eq!(
+            parse("ssh://git@GitLab.com:22/group/repo"),
+            remote("gitlab.com", "group/repo", HostKind::GitLab)
+        );
+        assert_eq!(
+            parse("https://gitlab.example.com/group/repo"),
+            remote("gitlab.example.com", "group/repo", HostKind::GitLab)
+        );
+        assert_eq!(
+            parse("git@bitbucket.org:team/repo.git"),
+            remote("bitbucket.org", "team/repo.git", HostKind::Bitbucket)
+        );
+        assert_eq!(
+            parse("ssh://[::1]:2222/srv/repo.git"),
+            remote("::1", "srv/repo.git", HostKind::SelfHosted)
+        );
+    }
+
+    #[test]
+    fn recognizes_local_paths() {
+        let local = |path: &str| (None, path.to_owned(), HostKind::Local);
+        assert_eq!(parse("/srv/repo.git"), local("/srv/repo.git"));
+        assert_eq!(parse("file:///srv/repo.git"), local("/srv/repo.git"));
+        assert_eq!(parse("../repo"), local("../repo"));
+        assert_eq!(parse("./a:b"), local("./a:b"));
+        assert_eq!(parse("C:\\repo"), local("C:\\repo"));
+    }
+}
+
+mod stash {
+    use crate::env::git::Stash;
+
+    #[test]
+    fn reads_the_branch_from_the_message() {
+        assert_eq!(
+            Stash::branch_from_message("WIP on main: 1234567 first"),
+            Some(String::from("main"))
+        );
+        assert_eq!(
+            Stash::branch_from_message("On feature/a: my message"),
+            Some(String::from("feature/a"))
+        );
+        assert_eq!(
+            Stash::branch_from_message("WIP on (no branch): 1234567 first"),
+            None
+        );
+        assert_eq!(Stash::branch_from_message("autostash"), None);
+    }
+}
+
+#[cfg(feature = "env-git-git2")]
+#[allow(deprecated)]
+mod deprecated {
+    use crate::env::git::{
+        open_repo, query_head, query_stash_count, query_status_summary, AheadBehind, Head,
+        StatusSummary,
+    };
+
+    #[test]
+    fn forward_to_the_git2_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        ::git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a"), "a").unwrap();
+        let mut repo = open_repo(dir.path()).unwrap();
+
+        let head = query_head(&repo).unwrap();
+        assert!(matches!(head, Head::Unborn { .. }));
+        assert_eq!(Head::from_repo(&repo).unwrap(), head);
+
+        let status = query_status_summary(&repo).unwrap();
+        assert_eq!(status.working_tree.added, 1);
+        assert_eq!(StatusSummary::from_repo(&repo).unwrap(), status);
+        assert_eq!(query_stash_count(&mut repo).unwrap(), 0);
+
+        let signature = ::git2::Signature::now("test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "first", &tree, &[])
+            .unwrap();
+        let branch = repo.branches(None).unwrap().next().unwrap().unwrap().0;
+        assert_eq!(AheadBehind::from_branch(&repo, branch).unwrap(), None);
+    }
+}
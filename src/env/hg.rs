@@ -0,0 +1,258 @@
+//! Utilities for querying and representing information about a [Mercurial] repository.
+//!
+//! The information is read directly from the repository's files, without running `hg`.
+//! The working directory's state comes from the [dirstate], of which only the first version
+//! of the format can be read for the [status][`query_status_summary`],
+//! while the [head][`query_head`] can be read from both versions.
+//!
+//! [Mercurial]: https://www.mercurial-scm.org/
+//! [dirstate]: https://wiki.mercurial-scm.org/DirState
+
+#[cfg(test)]
+mod test;
+
+mod dirstate;
+mod ignore;
+
+use self::{
+    dirstate::{Dirstate, EntryState},
+    ignore::IgnorePatterns,
+};
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A Mercurial repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Repository {
+    root: PathBuf,
+}
+impl Repository {
+    /// The root of the repository's working directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The `.hg` directory, where the repository's data is stored.
+    pub fn hg_dir(&self) -> PathBuf {
+        self.root.join(".hg")
+    }
+
+    fn read_dirstate(&self) -> Result<Option<Vec<u8>>, Error> {
+        match fs::read(self.hg_dir().join("dirstate")) {
+            Ok(content) => Ok(Some(content)),
+            // A repository gets its dirstate on its first update or `hg add`.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Finds a repository in `dir` or the closest of its ancestors that has one,
+/// as `hg` does.
+pub fn open_repo(dir: &Path) -> Result<Repository, Error> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".hg").is_dir())
+        .map(|root| Repository {
+            root: root.to_owned(),
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no repository found in {}", dir.display()),
+            )
+        })
+}
+
+/// Gets the information about the parent of a repository's working directory.
+pub fn query_head(repo: &Repository) -> Result<Head, Error> {
+    let hg_dir = repo.hg_dir();
+    let read = |name: &str| match fs::read_to_string(hg_dir.join(name)) {
+        Ok(content) => Ok(Some(content.trim().to_owned()).filter(|content| !content.is_empty())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    };
+    let revision = match repo.read_dirstate()? {
+        Some(dirstate) => dirstate::read_parent(&dirstate)?,
+        None => None,
+    };
+    Ok(Head {
+        branch: read("branch")?.unwrap_or_else(|| String::from("default")),
+        bookmark: read("bookmarks.current")?,
+        revision,
+    })
+}
+
+/// Gets the [summary][`StatusSummary`] of a repository's [status].
+///
+/// The files are compared with the dirstate by size, modification time and permissions only,
+/// so a file whose modification time changed is counted as modified
+/// even if its content did not change.
+/// Unknown files are only looked for if `unknown_files` is `true`,
+/// which walks the whole working directory, leaving out the files ignored by `.hgignore`,
+/// and fails with [`ErrorKind::InvalidIgnore`] if it has a pattern that cannot be matched.
+///
+/// [status]: https://www.mercurial-scm.org/doc/hg.1.html#status
+pub fn query_status_summary(
+    repo: &Repository,
+    unknown_files: bool,
+) -> Result<StatusSummary, Error> {
+    let dirstate = match repo.read_dirstate()? {
+        Some(dirstate) => Dirstate::parse(&dirstate)?,
+        None => Dirstate::default(),
+    };
+
+    let mut summary = StatusSummary::default();
+    for (path, entry) in &dirstate.entries {
+        match entry.state {
+            EntryState::Added => summary.added += 1,
+            EntryState::Removed => summary.removed += 1,
+            EntryState::Merged => summary.modified += 1,
+            EntryState::Normal => {
+                let path = repo.root.join(String::from_utf8_lossy(path).as_ref());
+                match fs::symlink_metadata(path) {
+                    Ok(metadata) if entry.is_modified(&metadata) => summary.modified += 1,
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => summary.missing += 1,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+    }
+
+    if unknown_files {
+        let ignore = IgnorePatterns::read(&repo.root)?;
+        let tracked = dirstate
+            .entries
+            .keys()
+            .map(Vec::as_slice)
+            .collect::<HashSet<_>>();
+        summary.unknown = count_unknown_files(&repo.root, "", &tracked, &ignore)?;
+    }
+    Ok(summary)
+}
+
+/// Counts the files under `dir`, whose path relative to the root is `prefix`,
+/// that are neither tracked nor ignored.
+fn count_unknown_files(
+    dir: &Path,
+    prefix: &str,
+    tracked: &HashSet<&[u8]>,
+    ignore: &IgnorePatterns,
+) -> Result<usize, Error> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let path = format!("{}{}", prefix, name.to_string_lossy());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            // Nested repositories are not part of the repository.
+            if name == ".hg" || entry.path().join(".hg").is_dir() || ignore.is_ignored(&path) {
+                continue;
+            }
+            count += count_unknown_files(&entry.path(), &format!("{}/", path), tracked, ignore)?;
+        } else if !tracked.contains(path.as_bytes()) && !ignore.is_ignored(&path) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// The hash identifying a revision.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId([u8; 20]);
+impl NodeId {
+    /// Creates a [`NodeId`] from the bytes of a hash.
+    pub fn from_bytes(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    /// The bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Debug for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeId({})", self)
+    }
+}
+
+/// An error that occurred while querying a repository.
+pub type Error = super::error::Error<ErrorKind>;
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::new(ErrorKind::Io, err.to_string())
+    }
+}
+
+/// The category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The repository does not exist.
+    NotFound,
+    /// An I/O operation failed.
+    Io,
+    /// The dirstate could not be understood.
+    InvalidDirstate,
+    /// The dirstate is in a format that cannot be read.
+    UnsupportedDirstate,
+    /// An `.hgignore` file has a pattern that cannot be matched,
+    /// such as a regular expression with a lookaround.
+    InvalidIgnore,
+}
+
+/// Information about the parent of a repository's working directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Head {
+    /// The name of the [branch] the next commit will be on.
+    ///
+    /// [branch]: https://www.mercurial-scm.org/doc/hg.1.html#branch
+    pub branch: String,
+    /// The name of the active [bookmark], if any.
+    ///
+    /// [bookmark]: https://www.mercurial-scm.org/doc/hg.1.html#bookmarks
+    pub bookmark: Option<String>,
+    /// The hash of the working directory's parent revision,
+    /// or [`None`] if nothing is checked out, as in an empty repository.
+    pub revision: Option<NodeId>,
+}
+
+/// A summary of a repository's [status].
+///
+/// [status]: https://www.mercurial-scm.org/doc/hg.1.html#status
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusSummary {
+    /// The count of modified files (`M`).
+    pub modified: usize,
+    /// The count of files added with `hg add` (`A`).
+    pub added: usize,
+    /// The count of files removed with `hg remove` or `hg forget` (`R`).
+    pub removed: usize,
+    /// The count of tracked files missing from the working directory (`!`).
+    pub missing: usize,
+    /// The count of files that are neither tracked nor ignored (`?`).
+    pub unknown: usize,
+}
+
+impl StatusSummary {
+    /// Tell if there are any changes.
+    pub fn any_changes(&self) -> bool {
+        self.modified != 0
+            || self.added != 0
+            || self.removed != 0
+            || self.missing != 0
+            || self.unknown != 0
+    }
+}
@@ -0,0 +1,169 @@
+//! A reader for the [dirstate], where Mercurial keeps the working directory's parents
+//! and the state of the tracked files.
+//!
+//! [dirstate]: https://wiki.mercurial-scm.org/DirState
+
+use super::{Error, ErrorKind, NodeId};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    fmt,
+    fs::Metadata,
+    time::UNIX_EPOCH,
+};
+
+/// The marker the second version of the format starts with.
+const V2_MARKER: &[u8] = b"dirstate-v2\n";
+
+/// The tracked files, as read from the first version of the format.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct Dirstate {
+    /// The tracked files, by their paths relative to the root of the repository.
+    pub entries: HashMap<Vec<u8>, Entry>,
+}
+
+/// The state of a tracked file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct Entry {
+    pub state: EntryState,
+    /// The permissions and type of the file, as in `st_mode`.
+    pub mode: i32,
+    /// The size of the file, or a negative number when it needs to be compared by content.
+    pub size: i32,
+    /// The modification time of the file, or `-1` if it is not known.
+    pub mtime: i32,
+}
+
+/// How a tracked file is to be committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum EntryState {
+    /// The file is committed as found in the working directory.
+    Normal,
+    /// The file was added with `hg add`.
+    Added,
+    /// The file was removed with `hg remove` or `hg forget`.
+    Removed,
+    /// The file was changed by a merge.
+    Merged,
+}
+
+/// The sizes and modification times are stored on 31 bits.
+const RANGE_MASK: u64 = 0x7fff_ffff;
+
+impl Dirstate {
+    /// Parses a dirstate in the first version of the format.
+    pub fn parse(mut bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.starts_with(V2_MARKER) {
+            return Err(Error::new(
+                ErrorKind::UnsupportedDirstate,
+                "the dirstate-v2 format cannot be read.",
+            ));
+        }
+        // The parents are read by `read_parent`.
+        take(&mut bytes, 40)?;
+
+        let mut entries = HashMap::new();
+        while !bytes.is_empty() {
+            let state = match take(&mut bytes, 1)?[0] {
+                b'n' => EntryState::Normal,
+                b'a' => EntryState::Added,
+                b'r' => EntryState::Removed,
+                b'm' => EntryState::Merged,
+                state => {
+                    return Err(invalid_dirstate(format!(
+                        "unknown file state `{}`",
+                        char::from(state).escape_default()
+                    )))
+                }
+            };
+            let mode = read_i32(&mut bytes)?;
+            let size = read_i32(&mut bytes)?;
+            let mtime = read_i32(&mut bytes)?;
+            let length = read_i32(&mut bytes)?;
+            let length =
+                usize::try_from(length).map_err(|_| invalid_dirstate("negative path length"))?;
+            // A copied file's path is followed by a NUL and the path it was copied from.
+            let path = take(&mut bytes, length)?;
+            let path = path.split(|&byte| byte == b'\0').next().unwrap_or(path);
+            entries.insert(
+                path.to_owned(),
+                Entry {
+                    state,
+                    mode,
+                    size,
+                    mtime,
+                },
+            );
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Reads the first parent of the working directory
+/// from a dirstate in either version of the format.
+pub(super) fn read_parent(mut bytes: &[u8]) -> Result<Option<NodeId>, Error> {
+    if bytes.starts_with(V2_MARKER) {
+        // The parents follow the marker, padded to 32 bytes.
+        bytes = &bytes[V2_MARKER.len()..];
+    }
+    Ok(read_node(take(&mut bytes, 20)?))
+}
+
+impl Entry {
+    /// Tell if the file, whose metadata is given, differs from the entry of a normal file.
+    pub fn is_modified(&self, metadata: &Metadata) -> bool {
+        if self.size < 0 {
+            return true;
+        }
+        let size_changed = self.size as u64 != metadata.len() & RANGE_MASK;
+        let mtime_changed = self.mtime != -1
+            && modified_seconds(metadata).is_some_and(|mtime| self.mtime as u64 != mtime);
+        size_changed || mtime_changed || self.mode_changed(metadata)
+    }
+
+    #[cfg(unix)]
+    fn mode_changed(&self, metadata: &Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        // Only the file type and the executable bit are tracked.
+        let mode = self.mode as u32 ^ metadata.mode();
+        mode & (0o170000 | 0o100) != 0
+    }
+
+    #[cfg(not(unix))]
+    fn mode_changed(&self, _metadata: &Metadata) -> bool {
+        false
+    }
+}
+
+fn modified_seconds(metadata: &Metadata) -> Option<u64> {
+    let mtime = metadata.modified().ok()?;
+    let seconds = mtime.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(seconds & RANGE_MASK)
+}
+
+fn read_node(bytes: &[u8]) -> Option<NodeId> {
+    let bytes: [u8; 20] = bytes.try_into().ok()?;
+    // The null revision, whose hash is all zeros, is the parent of the root revisions.
+    (bytes != [0; 20]).then_some(NodeId::from_bytes(bytes))
+}
+
+fn read_i32(bytes: &mut &[u8]) -> Result<i32, Error> {
+    let value = take(bytes, 4)?;
+    Ok(i32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if bytes.len() < len {
+        return Err(invalid_dirstate("unexpected end of file"));
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
+
+fn invalid_dirstate(message: impl fmt::Display) -> Error {
+    Error::new(
+        ErrorKind::InvalidDirstate,
+        format!("invalid dirstate: {}.", message),
+    )
+}
@@ -0,0 +1,258 @@
+//! A reader for [`.hgignore`][hgignore] files.
+//!
+//! Mercurial matches paths with Python regular expressions, to which glob patterns are translated.
+//! They are matched here with the [`regex`] crate, as Mercurial's Rust implementation does,
+//! so in linear time. Its syntax is the same for the common features,
+//! but it has no lookarounds nor backreferences:
+//! an `.hgignore` file using them cannot be read.
+//!
+//! [hgignore]: https://www.mercurial-scm.org/doc/hgignore.5.html
+
+use super::{Error, ErrorKind};
+use regex::RegexSet;
+use std::{fs, io, path::Path};
+
+/// How deep `include:` and `subinclude:` lines can nest, so that cycles end.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The patterns of an `.hgignore` file.
+#[derive(Debug, Default, Clone)]
+pub(super) struct IgnorePatterns {
+    /// The patterns of the file and of the files it includes, a set for each file.
+    patterns: Vec<RegexSet>,
+    /// The patterns of the files included with `subinclude:`,
+    /// matching the paths under the directory of the file, by the path of that directory.
+    subincludes: Vec<(String, IgnorePatterns)>,
+}
+
+/// The syntax a pattern is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    /// A regular expression, matching anywhere in the path unless anchored with `^`.
+    Regexp,
+    /// A glob, matching at any directory.
+    Glob,
+    /// A glob, matching from the root.
+    RootGlob,
+    /// A path, from the root, of a file or a directory.
+    Path,
+}
+
+impl IgnorePatterns {
+    /// Reads the patterns of the `.hgignore` file at the root of a repository, if it exists.
+    pub fn read(root: &Path) -> Result<Self, Error> {
+        let mut patterns = Self::default();
+        patterns.read_file(root, &root.join(".hgignore"), 0)?;
+        Ok(patterns)
+    }
+
+    /// Tell if the file or directory at `path`, relative to the root, is ignored.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.patterns.iter().any(|set| set.is_match(path))
+            || self.subincludes.iter().any(|(dir, patterns)| {
+                let path = match dir.as_str() {
+                    "" => Some(path),
+                    dir => path
+                        .strip_prefix(dir)
+                        .and_then(|path| path.strip_prefix('/')),
+                };
+                path.is_some_and(|path| patterns.is_ignored(path))
+            })
+    }
+
+    /// Adds the patterns of the file at `file`, which match the paths relative to `root`.
+    ///
+    /// A missing file is skipped, as Mercurial does after warning about it.
+    fn read_file(&mut self, root: &Path, file: &Path, depth: usize) -> Result<(), Error> {
+        match fs::read(file) {
+            Ok(content) => {
+                let dir = file.parent().unwrap_or(root);
+                self.add(&String::from_utf8_lossy(&content), root, dir, depth)
+                    .map_err(|err| {
+                        Error::new(err.kind(), format!("{}: {}", file.display(), err.message()))
+                    })
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Adds the patterns of the `content` of a file in `dir`, which match the paths relative to `root`.
+    fn add(&mut self, content: &str, root: &Path, dir: &Path, depth: usize) -> Result<(), Error> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(Error::new(
+                ErrorKind::InvalidIgnore,
+                "too many nested `include:` or `subinclude:` lines",
+            ));
+        }
+        let mut syntax = Syntax::Regexp;
+        let mut regexes = Vec::new();
+        for line in content.lines() {
+            let line = strip_comment(line);
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("syntax:") {
+                syntax = parse_syntax(name.trim()).unwrap_or(syntax);
+                continue;
+            }
+            let (line_syntax, pattern) = match line.split_once(':') {
+                Some(("include", file)) => {
+                    self.read_file(root, &dir.join(file), depth + 1)?;
+                    continue;
+                }
+                Some(("subinclude", file)) => {
+                    let file = dir.join(file);
+                    let sub_root = file.parent().unwrap_or(dir).to_owned();
+                    let mut patterns = Self::default();
+                    patterns.read_file(&sub_root, &file, depth + 1)?;
+                    self.subincludes
+                        .push((relative_dir(root, &sub_root)?, patterns));
+                    continue;
+                }
+                Some((name, pattern)) => match parse_syntax(name) {
+                    Some(line_syntax) => (line_syntax, pattern),
+                    None => (syntax, line),
+                },
+                None => (syntax, line),
+            };
+            regexes.push(to_regex(line_syntax, pattern));
+        }
+        if !regexes.is_empty() {
+            let set = RegexSet::new(&regexes)
+                .map_err(|err| Error::new(ErrorKind::InvalidIgnore, err.to_string()))?;
+            self.patterns.push(set);
+        }
+        Ok(())
+    }
+}
+
+/// Gets the path of `dir` relative to `root`, with `/` separators.
+fn relative_dir(root: &Path, dir: &Path) -> Result<String, Error> {
+    let relative = dir.strip_prefix(root).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidIgnore,
+            format!("`subinclude:` of a file outside of {}", root.display()),
+        )
+    })?;
+    Ok(relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn parse_syntax(name: &str) -> Option<Syntax> {
+    match name {
+        "re" | "regexp" => Some(Syntax::Regexp),
+        "glob" => Some(Syntax::Glob),
+        "rootglob" => Some(Syntax::RootGlob),
+        "path" => Some(Syntax::Path),
+        _ => None,
+    }
+}
+
+/// Strips a comment, which starts with a `#` that is not escaped, and unescapes the other `#`.
+fn strip_comment(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => break,
+            '\\' => match chars.next() {
+                Some('#') => stripped.push('#'),
+                Some(c) => {
+                    stripped.push('\\');
+                    stripped.push(c);
+                }
+                None => stripped.push('\\'),
+            },
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Translates a pattern to a regular expression matching anywhere in a path, as Mercurial does.
+fn to_regex(syntax: Syntax, pattern: &str) -> String {
+    // Paths also match the files under them, hence the `(?:/|$)` suffixes.
+    match syntax {
+        Syntax::Regexp => pattern.to_owned(),
+        Syntax::Glob => format!("^(?:|.*/){}(?:/|$)", glob_to_regex(pattern)),
+        Syntax::RootGlob => format!("^{}(?:/|$)", glob_to_regex(pattern)),
+        Syntax::Path => format!("^{}(?:/|$)", regex::escape(pattern.trim_end_matches('/'))),
+    }
+}
+
+/// Translates a glob to a regular expression the way Mercurial does.
+fn glob_to_regex(glob: &str) -> String {
+    let glob = glob.chars().collect::<Vec<_>>();
+    let mut regex = String::new();
+    let mut groups = 0;
+    let mut i = 0;
+    while i < glob.len() {
+        let c = glob[i];
+        i += 1;
+        match c {
+            '*' if glob.get(i) == Some(&'*') => {
+                i += 1;
+                if glob.get(i) == Some(&'/') {
+                    i += 1;
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let mut j = i;
+                if matches!(glob.get(j), Some('!' | ']')) {
+                    j += 1;
+                }
+                while j < glob.len() && glob[j] != ']' {
+                    j += 1;
+                }
+                if j >= glob.len() {
+                    regex.push_str("\\[");
+                } else {
+                    let class = glob[i..j].iter().collect::<String>();
+                    i = j + 1;
+                    regex.push('[');
+                    let class = match class.strip_prefix('!') {
+                        Some(class) => {
+                            regex.push('^');
+                            class
+                        }
+                        None => class.as_str(),
+                    };
+                    for (k, c) in class.chars().enumerate() {
+                        // Unlike in Python, `[`, `&` and `~` can start nested classes
+                        // or set operations, and a leading `^` negates the class.
+                        if matches!(c, '\\' | '[' | '&' | '~') || (k == 0 && c == '^') {
+                            regex.push('\\');
+                        }
+                        regex.push(c);
+                    }
+                    regex.push(']');
+                }
+            }
+            '{' => {
+                groups += 1;
+                regex.push_str("(?:");
+            }
+            '}' if groups > 0 => {
+                groups -= 1;
+                regex.push(')');
+            }
+            ',' if groups > 0 => regex.push('|'),
+            '\\' if i < glob.len() => {
+                regex.push_str(&regex::escape(&glob[i].to_string()));
+                i += 1;
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
@@ -0,0 +1,226 @@
+mod ignore {
+    use crate::env::hg::{ignore::IgnorePatterns, Error, ErrorKind};
+    use std::{fs, time::Instant};
+
+    /// Reads the patterns of an `.hgignore` file with the given content.
+    fn read(content: &str) -> Result<IgnorePatterns, Error> {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".hgignore"), content).unwrap();
+        IgnorePatterns::read(dir.path())
+    }
+
+    #[test]
+    fn matches_like_mercurial() {
+        let patterns = read(
+            "# comment\n\
+             \\.pyc$\n\
+             ^build/\n\
+             a\\#b # comment\n\
+             syntax: glob\n\
+             *.o\n\
+             docs/**/_*.html\n\
+             [!a-c]x\n\
+             rootglob:dist\n\
+             path:out/log.txt\n",
+        )
+        .unwrap();
+        assert!(patterns.is_ignored("a.pyc"));
+        assert!(patterns.is_ignored("src/a.pyc"));
+        assert!(!patterns.is_ignored("a.pyc.txt"));
+        assert!(patterns.is_ignored("build/a"));
+        assert!(!patterns.is_ignored("src/build/a"));
+        assert!(patterns.is_ignored("a#b"));
+        assert!(patterns.is_ignored("a.o"));
+        assert!(patterns.is_ignored("src/lib/a.o"));
+        assert!(patterns.is_ignored("docs/_index.html"));
+        assert!(patterns.is_ignored("docs/api/v1/_a.html"));
+        assert!(!patterns.is_ignored("docs/api/a.html"));
+        assert!(patterns.is_ignored("dx"));
+        assert!(!patterns.is_ignored("bx"));
+        assert!(patterns.is_ignored("dist"));
+        assert!(patterns.is_ignored("dist/a"));
+        assert!(!patterns.is_ignored("src/dist"));
+        assert!(patterns.is_ignored("out/log.txt"));
+        assert!(!patterns.is_ignored("out/log.txt2"));
+    }
+
+    #[test]
+    fn supports_the_common_regular_expressions() {
+        let is_ignored = |pattern: &str, path: &str| read(pattern).unwrap().is_ignored(path);
+        assert!(is_ignored(r"^(src|lib)/.*\.rs\.bk$", "lib/a.rs.bk"));
+        assert!(!is_ignored(r"^(src|lib)/.*\.rs\.bk$", "bin/a.rs.bk"));
+        assert!(is_ignored(r"^tmp\d{2,3}$", "tmp123"));
+        assert!(!is_ignored(r"^tmp\d{2,3}$", "tmp1234"));
+        assert!(is_ignored(r"^[^/]+~$", "a~"));
+        assert!(!is_ignored(r"^[^/]+~$", "a/b~"));
+        assert!(is_ignored(r"^\w+\.log$", "a_1.log"));
+        assert!(is_ignored(r"(?i)^readme$", "README"));
+    }
+
+    #[test]
+    fn rejects_unsupported_patterns() {
+        assert_eq!(
+            read("\\.o$\nre:(?<=x)y\n")
+                .map(|_| ())
+                .map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidIgnore)
+        );
+    }
+
+    #[test]
+    fn matches_in_linear_time() {
+        let patterns = read("(.*/)*build$\n(a|aa)*c\n").unwrap();
+        let start = Instant::now();
+        assert!(!patterns.is_ignored(&"a/".repeat(1000)));
+        assert!(!patterns.is_ignored(&"a".repeat(1000)));
+        assert!(start.elapsed().as_secs() < 1);
+    }
+
+    #[test]
+    fn reads_included_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/lib")).unwrap();
+        fs::write(
+            root.join(".hgignore"),
+            "include:common\nsubinclude:src/.hgignore\nsubinclude:sub\ninclude:missing\n",
+        )
+        .unwrap();
+        fs::write(root.join("common"), "syntax: glob\n*.tmp\n").unwrap();
+        fs::write(root.join("sub"), "^c$\n").unwrap();
+        fs::write(
+            root.join("src/.hgignore"),
+            "^gen/\nsubinclude:lib/.hgignore\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/lib/.hgignore"), "^out$\n").unwrap();
+
+        let patterns = IgnorePatterns::read(root).unwrap();
+        assert!(patterns.is_ignored("a.tmp"));
+        assert!(patterns.is_ignored("src/a.tmp"));
+        assert!(patterns.is_ignored("src/gen/a"));
+        assert!(!patterns.is_ignored("gen/a"));
+        assert!(!patterns.is_ignored("srcgen/a"));
+        assert!(patterns.is_ignored("src/lib/out"));
+        assert!(!patterns.is_ignored("src/out"));
+        assert!(patterns.is_ignored("c"));
+
+        fs::write(root.join("common"), "include:.hgignore\n").unwrap();
+        assert_eq!(
+            IgnorePatterns::read(root)
+                .map(|_| ())
+                .map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidIgnore)
+        );
+    }
+}
+
+mod repository {
+    use crate::env::hg::{
+        open_repo, query_head, query_status_summary, ErrorKind, Head, NodeId, StatusSummary,
+    };
+    use std::{fs, path::Path, time::UNIX_EPOCH};
+
+    /// Writes a dirstate whose entries are the files at `paths`, in the given states,
+    /// as they are in the working directory.
+    fn write_dirstate(root: &Path, parent: [u8; 20], entries: &[(u8, &str)]) {
+        let mut dirstate = parent.to_vec();
+        dirstate.extend_from_slice(&[0; 20]);
+        for &(state, path) in entries {
+            let (mode, size, mtime) = match fs::symlink_metadata(root.join(path)) {
+                Ok(metadata) => (
+                    0o100644,
+                    metadata.len() as i32,
+                    metadata
+                        .modified()
+                        .unwrap()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs() as i32,
+                ),
+                Err(_) => (0o100644, 0, 0),
+            };
+            dirstate.push(state);
+            for value in [mode, size, mtime, path.len() as i32] {
+                dirstate.extend_from_slice(&value.to_be_bytes());
+            }
+            dirstate.extend_from_slice(path.as_bytes());
+        }
+        fs::write(root.join(".hg/dirstate"), dirstate).unwrap();
+    }
+
+    #[test]
+    fn reads_the_head_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(
+            open_repo(root).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+        fs::create_dir_all(root.join(".hg")).unwrap();
+        fs::create_dir_all(root.join("src/nested/.hg")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+
+        let repo = open_repo(&root.join("src/other")).unwrap();
+        assert_eq!(repo.root(), root);
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head {
+                branch: String::from("default"),
+                bookmark: None,
+                revision: None,
+            }
+        );
+        assert_eq!(
+            query_status_summary(&repo, true).unwrap(),
+            StatusSummary::default()
+        );
+
+        for path in ["a", "b", "c", "src/d", "src/nested/e", "target/f", "g.tmp"] {
+            fs::write(root.join(path), "0").unwrap();
+        }
+        fs::write(root.join(".hgignore"), "syntax: glob\ntarget\n*.tmp\n").unwrap();
+        write_dirstate(
+            root,
+            [1; 20],
+            &[
+                (b'n', "a"),
+                (b'n', "missing"),
+                (b'a', "b"),
+                (b'r', "removed"),
+                (b'n', "c"),
+            ],
+        );
+        fs::write(root.join("c"), "changed").unwrap();
+        fs::write(root.join(".hg/branch"), "stable\n").unwrap();
+        fs::write(root.join(".hg/bookmarks.current"), "feature").unwrap();
+
+        let repo = open_repo(root).unwrap();
+        assert_eq!(
+            query_head(&repo).unwrap(),
+            Head {
+                branch: String::from("stable"),
+                bookmark: Some(String::from("feature")),
+                revision: Some(NodeId::from_bytes([1; 20])),
+            }
+        );
+        assert_eq!(
+            query_status_summary(&repo, true).unwrap(),
+            StatusSummary {
+                modified: 1,
+                added: 1,
+                removed: 1,
+                missing: 1,
+                // `.hgignore` and `src/d`.
+                unknown: 2,
+            }
+        );
+        assert_eq!(query_status_summary(&repo, false).unwrap().unknown, 0);
+
+        fs::write(root.join(".hg/dirstate"), b"dirstate-v2\n").unwrap();
+        assert_eq!(
+            query_status_summary(&repo, false).map_err(|err| err.kind()),
+            Err(ErrorKind::UnsupportedDirstate)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Utilities for querying and representing information about a [Jujutsu] workspace.
+//!
+//! The information is queried by running the [`jj`][jj] executable
+//! with `--ignore-working-copy`, so that querying a workspace never snapshots
+//! the working copy nor records an operation, as running `jj` otherwise would.
+//! As a consequence, the changes made since the last `jj` command are not accounted for.
+//!
+//! [Jujutsu]: https://jj-vcs.github.io/jj/
+//! [jj]: https://jj-vcs.github.io/jj/latest/cli-reference/
+
+#[cfg(test)]
+mod test;
+
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str,
+};
+
+/// The fields of the working-copy commit, separated by NULs.
+const WORKING_COPY_TEMPLATE: &str = concat!(
+    r#"change_id.shortest().prefix() ++ "\0" ++ change_id ++ "\0" ++ commit_id ++ "\0""#,
+    r#" ++ local_bookmarks.map(|b| b.name()).join(" ") ++ "\0""#,
+    r#" ++ if(empty, "1", "0") ++ "\0" ++ if(conflict, "1", "0")"#,
+);
+
+/// A Jujutsu workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Workspace {
+    root: PathBuf,
+}
+impl Workspace {
+    /// The root of the workspace's working copy, which holds the `.jj` directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn jj<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = Command::new("jj");
+        command
+            .arg("--repository")
+            .arg(&self.root)
+            .args(["--ignore-working-copy", "--no-pager", "--color=never"])
+            .args(args);
+        command
+    }
+}
+
+/// Finds a workspace in `dir` or the closest of its ancestors that has one,
+/// as `jj` does.
+///
+/// No `jj` command is run, so this is cheap enough to tell
+/// whether a git repository is used through Jujutsu.
+pub fn open_workspace(dir: &Path) -> Result<Workspace, Error> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".jj").is_dir())
+        .map(|root| Workspace {
+            root: root.to_owned(),
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no workspace found in {}", dir.display()),
+            )
+        })
+}
+
+/// Gets the information about a workspace's [working-copy commit][`WorkingCopy`] with `jj log`.
+pub fn query_working_copy(workspace: &Workspace) -> Result<WorkingCopy, Error> {
+    let mut command = workspace.jj([
+        "log",
+        "--no-graph",
+        "--revisions=@",
+        "--template",
+        WORKING_COPY_TEMPLATE,
+    ]);
+    let output = run(&mut command)?;
+    parse_working_copy(&output)
+}
+
+/// Gets the [summary][`ChangeSummary`] of the changes in a workspace's working-copy commit
+/// with `jj diff --summary`.
+pub fn query_change_summary(workspace: &Workspace) -> Result<ChangeSummary, Error> {
+    let mut command = workspace.jj(["diff", "--summary", "--revisions=@"]);
+    let output = run(&mut command)?;
+    parse_change_summary(&output)
+}
+
+fn run(command: &mut Command) -> Result<Vec<u8>, Error> {
+    let output = command.stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        let args = command
+            .get_args()
+            .map(OsStr::to_string_lossy)
+            .collect::<Vec<_>>()
+            .join(" ");
+        return Err(Error::new(
+            ErrorKind::Command,
+            format!(
+                "`jj {}` failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+fn parse_change_summary(output: &[u8]) -> Result<ChangeSummary, Error> {
+    let mut summary = ChangeSummary::default();
+    for line in output
+        .split(|&byte| byte == b'\n')
+        .filter(|line| !line.is_empty())
+    {
+        match line.first() {
+            // A copied file is a new file.
+            Some(b'A' | b'C') => summary.added += 1,
+            // A renamed file is counted once, as modified.
+            Some(b'M' | b'R') => summary.modified += 1,
+            Some(b'D') => summary.deleted += 1,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidOutput,
+                    format!(
+                        "`jj diff` wrote an unexpected line: {:?}",
+                        String::from_utf8_lossy(line)
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn parse_working_copy(output: &[u8]) -> Result<WorkingCopy, Error> {
+    let invalid_output = || {
+        Error::new(
+            ErrorKind::InvalidOutput,
+            format!(
+                "`jj log` wrote an unexpected output: {:?}",
+                String::from_utf8_lossy(output)
+            ),
+        )
+    };
+    let output = str::from_utf8(output).map_err(|_| invalid_output())?;
+    let mut fields = output.trim_end_matches('\n').split('\0');
+    let mut field = || fields.next().ok_or_else(invalid_output);
+    let flag = |value: &str| match value {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        _ => Err(invalid_output()),
+    };
+    Ok(WorkingCopy {
+        change_id_prefix: field()?.to_owned(),
+        change_id: field()?.to_owned(),
+        commit_id: field()?.to_owned(),
+        bookmarks: field()?.split_whitespace().map(str::to_owned).collect(),
+        empty: flag(field()?)?,
+        conflict: flag(field()?)?,
+    })
+}
+
+/// An error that occurred while querying a workspace.
+pub type Error = super::error::Error<ErrorKind>;
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::new(ErrorKind::Io, err.to_string())
+    }
+}
+
+/// The category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The workspace does not exist.
+    NotFound,
+    /// An I/O operation failed, such as running `jj`.
+    Io,
+    /// A jj command exited unsuccessfully.
+    Command,
+    /// A jj command wrote something that could not be understood.
+    InvalidOutput,
+}
+
+/// Information about a workspace's [working-copy commit],
+/// which Jujutsu amends as the files in the working copy change.
+///
+/// [working-copy commit]: https://jj-vcs.github.io/jj/latest/working-copy/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WorkingCopy {
+    /// The shortest prefix of the [change ID][`Self::change_id`]
+    /// that tells it apart from the other changes in the repository.
+    pub change_id_prefix: String,
+    /// The [change ID], which, unlike the commit ID, stays the same when the commit is rewritten.
+    ///
+    /// [change ID]: https://jj-vcs.github.io/jj/latest/glossary/#change-id
+    pub change_id: String,
+    /// The hash of the commit.
+    pub commit_id: String,
+    /// The names of the local [bookmarks] pointing to the commit.
+    ///
+    /// [bookmarks]: https://jj-vcs.github.io/jj/latest/bookmarks/
+    pub bookmarks: Vec<String>,
+    /// Whether the commit changes no files.
+    pub empty: bool,
+    /// Whether the commit has unresolved [conflicts].
+    ///
+    /// [conflicts]: https://jj-vcs.github.io/jj/latest/conflicts/
+    pub conflict: bool,
+}
+
+/// A summary of the changes in a workspace's working-copy commit, relative to its parents.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChangeSummary {
+    /// The number of added or copied files.
+    pub added: usize,
+    /// The number of modified or renamed files.
+    pub modified: usize,
+    /// The number of deleted files.
+    pub deleted: usize,
+}
+impl ChangeSummary {
+    /// Tell if the summary indicates the presence of changes.
+    pub fn any_changes(&self) -> bool {
+        self.added != 0 || self.modified != 0 || self.deleted != 0
+    }
+}
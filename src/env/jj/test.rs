@@ -0,0 +1,70 @@
+mod parse_change_summary {
+    use crate::env::jj::{parse_change_summary, ChangeSummary, ErrorKind};
+
+    #[test]
+    fn counts_the_changed_files() {
+        assert_eq!(
+            parse_change_summary(b"M src/lib.rs\nA new\nC {new => copy}\nR {a => b}\nD old\n")
+                .unwrap(),
+            ChangeSummary {
+                added: 2,
+                modified: 2,
+                deleted: 1,
+            }
+        );
+        assert!(!parse_change_summary(b"").unwrap().any_changes());
+        assert_eq!(
+            parse_change_summary(b"Working copy changes:\n").map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidOutput)
+        );
+    }
+}
+
+mod parse_working_copy {
+    use crate::env::jj::{parse_working_copy, ErrorKind, WorkingCopy};
+
+    #[test]
+    fn reads_the_template_fields() {
+        assert_eq!(
+            parse_working_copy(
+                b"kx\0kxqpmwzrsvtuoyplnnrlkxozwqlmmkvy\x000123abcd\0main feature\x001\x000\n"
+            )
+            .unwrap(),
+            WorkingCopy {
+                change_id_prefix: String::from("kx"),
+                change_id: String::from("kxqpmwzrsvtuoyplnnrlkxozwqlmmkvy"),
+                commit_id: String::from("0123abcd"),
+                bookmarks: vec![String::from("main"), String::from("feature")],
+                empty: true,
+                conflict: false,
+            }
+        );
+        let working_copy = parse_working_copy(b"k\0kx\x000123\0\x000\x001").unwrap();
+        assert!(working_copy.bookmarks.is_empty());
+        assert!(working_copy.conflict);
+        assert_eq!(
+            parse_working_copy(b"k\0kx\x000123\0\x000").map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidOutput)
+        );
+    }
+}
+
+mod open_workspace {
+    use crate::env::jj::{open_workspace, ErrorKind};
+    use std::fs;
+
+    #[test]
+    fn finds_the_closest_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            open_workspace(dir.path()).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+        fs::create_dir_all(dir.path().join(".jj")).unwrap();
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        assert_eq!(
+            open_workspace(&dir.path().join("a/b")).unwrap().root(),
+            dir.path()
+        );
+    }
+}
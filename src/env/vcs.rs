@@ -0,0 +1,518 @@
+//! Utilities for detecting which version control system manages a directory
+//! and querying it through a common interface.
+//!
+//! A directory and its ancestors are searched once, closest first, for the files each system
+//! keeps at the root of its working copies, and the first one found is [detected][`detect`].
+//! The search stops where git's would: at the [ceiling directories][ceilings]
+//! and, unless told otherwise, at the boundary of the starting directory's file system.
+//!
+//! A [detected][`Detected`] working copy is [opened][`Detected::open`] as a [`Vcs`]
+//! when its system has queries in this crate under the enabled features:
+//!
+//! - git, with the first enabled of the [`git2`][crate::env::git::git2],
+//!   [`gix`][crate::env::git::gix] and [`cli`][crate::env::git::cli] backends;
+//! - Mercurial, with [`env::hg`][crate::env::hg] (feature `env-hg`);
+//! - Jujutsu, with [`env::jj`][crate::env::jj] (feature `env-jj`).
+//!
+//! Subversion, Fossil and Pijul keep their state in databases that cannot be read here,
+//! so they are only detected.
+//!
+//! [ceilings]: https://git-scm.com/docs/git#Documentation/git.txt-GITCEILINGDIRECTORIES
+
+#[cfg(test)]
+mod test;
+
+use std::{
+    env, fmt, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// A working copy of a version control system whose information can be queried.
+pub trait Vcs {
+    /// The system managing the working copy.
+    fn kind(&self) -> Kind;
+
+    /// The root of the working copy.
+    fn root(&self) -> &Path;
+
+    /// Gets the information about what the working copy is based on.
+    fn query_head(&self) -> Result<Head, Error>;
+
+    /// Gets the [summary][`StatusSummary`] of the working copy's changes.
+    ///
+    /// Untracked files are only counted if `untracked_files` is `true`,
+    /// which for git defers to `status.showUntrackedFiles`.
+    fn query_status_summary(&self, untracked_files: bool) -> Result<StatusSummary, Error>;
+}
+
+/// A version control system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// [git](https://git-scm.com/)
+    Git,
+    /// [Mercurial](https://www.mercurial-scm.org/)
+    Mercurial,
+    /// [Subversion](https://subversion.apache.org/)
+    Subversion,
+    /// [Fossil](https://fossil-scm.org/)
+    Fossil,
+    /// [Jujutsu](https://jj-vcs.github.io/jj/)
+    Jujutsu,
+    /// [Pijul](https://pijul.org/)
+    Pijul,
+}
+impl Kind {
+    /// The systems in the order they are looked for in a directory.
+    ///
+    /// Jujutsu comes before git, as it can share its directory with a git repository.
+    const DETECTION_ORDER: [Kind; 6] = [
+        Kind::Jujutsu,
+        Kind::Git,
+        Kind::Mercurial,
+        Kind::Pijul,
+        Kind::Fossil,
+        Kind::Subversion,
+    ];
+
+    /// The name of the system's executable, as in `hg`.
+    pub fn command(self) -> &'static str {
+        match self {
+            Kind::Git => "git",
+            Kind::Mercurial => "hg",
+            Kind::Subversion => "svn",
+            Kind::Fossil => "fossil",
+            Kind::Jujutsu => "jj",
+            Kind::Pijul => "pijul",
+        }
+    }
+
+    /// Tell if `dir` is the root of a working copy of the system.
+    fn is_root(self, dir: &Path) -> bool {
+        match self {
+            // A linked worktree or a submodule has a `.git` file.
+            Kind::Git => dir.join(".git").exists(),
+            Kind::Mercurial => dir.join(".hg").is_dir(),
+            // Every directory of a working copy made before Subversion 1.7 has a `.svn`,
+            // so this finds the closest rather than the topmost one.
+            Kind::Subversion => dir.join(".svn").is_dir(),
+            Kind::Fossil => dir.join(".fslckout").is_file() || dir.join("_FOSSIL_").is_file(),
+            Kind::Jujutsu => dir.join(".jj").is_dir(),
+            Kind::Pijul => dir.join(".pijul").is_dir(),
+        }
+    }
+}
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.command())
+    }
+}
+
+/// Where [`detect`] stops looking for a working copy.
+///
+/// The [default][`Default`] options have no ceiling directories
+/// and do not cross file system boundaries.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct DetectOptions {
+    /// The directories not to look into, nor above, unless the search starts in one of them.
+    ///
+    /// Relative paths are ignored.
+    pub ceiling_dirs: Vec<PathBuf>,
+    /// Whether to keep looking in the ancestors on another file system than the starting directory.
+    pub across_filesystems: bool,
+}
+impl DetectOptions {
+    /// Reads the options from [`GIT_CEILING_DIRECTORIES`] and [`GIT_DISCOVERY_ACROSS_FILESYSTEM`],
+    /// as git does.
+    ///
+    /// [`GIT_CEILING_DIRECTORIES`]: https://git-scm.com/docs/git#Documentation/git.txt-GITCEILINGDIRECTORIES
+    /// [`GIT_DISCOVERY_ACROSS_FILESYSTEM`]: https://git-scm.com/docs/git#Documentation/git.txt-GITDISCOVERYACROSSFILESYSTEM
+    pub fn from_env() -> Self {
+        let ceiling_dirs = env::var_os("GIT_CEILING_DIRECTORIES")
+            .map(|dirs| env::split_paths(&dirs).collect())
+            .unwrap_or_default();
+        let across_filesystems = env::var("GIT_DISCOVERY_ACROSS_FILESYSTEM").is_ok_and(|value| {
+            matches!(
+                value.to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        Self {
+            ceiling_dirs,
+            across_filesystems,
+        }
+    }
+
+    /// Tell if `dir` is one of the [ceiling directories][`Self::ceiling_dirs`],
+    /// either as given or once its symbolic links are resolved.
+    fn is_ceiling(&self, dir: &Path) -> bool {
+        self.ceiling_dirs
+            .iter()
+            .filter(|ceiling| ceiling.is_absolute())
+            .any(|ceiling| {
+                ceiling == dir || fs::canonicalize(ceiling).is_ok_and(|ceiling| ceiling == dir)
+            })
+    }
+}
+
+/// A working copy found by [`detect`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Detected {
+    /// The system managing the working copy.
+    pub kind: Kind,
+    /// The root of the working copy.
+    pub root: PathBuf,
+}
+impl Detected {
+    /// Opens the working copy to query it.
+    ///
+    /// Fails with [`ErrorKind::Unsupported`] if its system has no queries
+    /// under the enabled features.
+    pub fn open(&self) -> Result<Box<dyn Vcs>, Error> {
+        match self.kind {
+            #[cfg(any(
+                feature = "env-git-git2",
+                feature = "env-git-gix",
+                feature = "env-git-cli"
+            ))]
+            Kind::Git => Ok(Box::new(GitRepository::open(&self.root)?)),
+            #[cfg(feature = "env-hg")]
+            Kind::Mercurial => Ok(Box::new(crate::env::hg::open_repo(&self.root)?)),
+            #[cfg(feature = "env-jj")]
+            Kind::Jujutsu => Ok(Box::new(crate::env::jj::open_workspace(&self.root)?)),
+            kind => Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("{} working copies cannot be queried", kind),
+            )),
+        }
+    }
+}
+
+/// Finds the working copy in `dir` or the closest of its ancestors that has one.
+///
+/// `dir` is always searched, even when it is a ceiling directory.
+/// A relative `dir`, or one with `..` components, is [canonicalized][`fs::canonicalize`] first,
+/// so that its ancestors are those of the directory it names.
+pub fn detect(dir: &Path, options: &DetectOptions) -> Result<Detected, Error> {
+    let canonical;
+    let dir = if dir.is_relative() || dir.components().any(|c| c == Component::ParentDir) {
+        canonical = fs::canonicalize(dir)?;
+        &canonical
+    } else {
+        dir
+    };
+    let start_device = device(dir)?;
+    for (i, ancestor) in dir.ancestors().enumerate() {
+        if i != 0 {
+            if options.is_ceiling(ancestor) {
+                break;
+            }
+            if !options.across_filesystems && device(ancestor).ok().flatten() != start_device {
+                break;
+            }
+        }
+        if let Some(kind) = Kind::DETECTION_ORDER
+            .iter()
+            .copied()
+            .find(|kind| kind.is_root(ancestor))
+        {
+            return Ok(Detected {
+                kind,
+                root: ancestor.to_owned(),
+            });
+        }
+    }
+    Err(Error::new(
+        ErrorKind::NotFound,
+        format!("no working copy found in {}", dir.display()),
+    ))
+}
+
+/// [Detects][`detect`] the working copy in `dir` or its ancestors and [opens][`Detected::open`] it.
+pub fn open_vcs(dir: &Path, options: &DetectOptions) -> Result<Box<dyn Vcs>, Error> {
+    detect(dir, options)?.open()
+}
+
+/// The identifier of the file system `dir` is on, if it can be known.
+#[cfg(unix)]
+fn device(dir: &Path) -> io::Result<Option<u64>> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(Some(fs::metadata(dir)?.dev()))
+}
+
+/// The identifier of the file system `dir` is on, if it can be known.
+#[cfg(not(unix))]
+fn device(dir: &Path) -> io::Result<Option<u64>> {
+    fs::metadata(dir)?;
+    Ok(None)
+}
+
+/// An error that occurred while detecting or querying a working copy.
+pub type Error = super::error::Error<ErrorKind>;
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::new(ErrorKind::Io, err.to_string())
+    }
+}
+
+/// The category of an [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// No working copy was found.
+    NotFound,
+    /// An I/O operation failed.
+    Io,
+    /// The working copy's system cannot be queried.
+    Unsupported,
+    /// A query of the working copy's system failed.
+    Query,
+}
+
+/// What a working copy is based on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Head {
+    /// The working copy is on a named line of development:
+    /// a git branch, a Mercurial bookmark or branch, or a Jujutsu bookmark.
+    Branch(String),
+    /// The working copy is on an unnamed revision, such as a detached git HEAD,
+    /// identified by its hash or, for Jujutsu, its change ID prefix.
+    Revision(String),
+    /// Nothing was committed yet, and the first commit will be on the given branch, if known.
+    Unborn(Option<String>),
+}
+
+/// A summary of the changes in a working copy.
+///
+/// The changes are those of git's working tree and staging area together,
+/// those of Mercurial's working directory, or those of Jujutsu's working-copy commit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusSummary {
+    /// The number of added files.
+    pub added: usize,
+    /// The number of modified files.
+    pub modified: usize,
+    /// The number of deleted files, including the files missing from the working copy.
+    pub deleted: usize,
+    /// The number of files that are neither tracked nor ignored.
+    pub untracked: usize,
+    /// The number of files with unresolved conflicts.
+    ///
+    /// Jujutsu only tells whether the working-copy commit has conflicts, which counts as 1.
+    pub conflicted: usize,
+    /// Whether a limit was hit before all files were counted, in which case the counts are lower bounds.
+    ///
+    /// Only git repositories given status limits have incomplete summaries.
+    ///
+    /// An incomplete summary [without changes][`Self::any_changes`] means the status is unknown.
+    pub incomplete: bool,
+}
+impl StatusSummary {
+    /// Tell if the summary indicates the presence of changes.
+    pub fn any_changes(&self) -> bool {
+        self.added != 0
+            || self.modified != 0
+            || self.deleted != 0
+            || self.untracked != 0
+            || self.conflicted != 0
+    }
+}
+
+#[cfg(feature = "env-git-types")]
+pub use self::git::GitRepository;
+
+#[cfg(feature = "env-git-types")]
+mod git {
+    use super::{Error, ErrorKind, Head, Kind, StatusSummary, Vcs};
+    use crate::env::git::{self, StatusLimits, UntrackedFiles};
+    use std::path::{Path, PathBuf};
+
+    /// A git repository, queried through any of the [git backends][crate::env::git].
+    pub struct GitRepository {
+        root: PathBuf,
+        repo: Box<dyn git::Repository>,
+        status_limits: StatusLimits,
+    }
+    impl GitRepository {
+        /// Wraps a repository opened from `root`, the root of its working tree.
+        pub fn new(root: PathBuf, repo: Box<dyn git::Repository>) -> Self {
+            Self {
+                root,
+                repo,
+                status_limits: StatusLimits::default(),
+            }
+        }
+
+        /// Sets the time budget and the maximum number of files of the status summaries.
+        ///
+        /// The [untracked files][`StatusLimits::untracked_files`] of `limits` are ignored,
+        /// as [`Vcs::query_status_summary`] tells whether to count them.
+        pub fn with_status_limits(mut self, limits: StatusLimits) -> Self {
+            self.status_limits = limits;
+            self
+        }
+
+        /// Opens the repository at `root` with the first enabled backend.
+        #[cfg(any(
+            feature = "env-git-git2",
+            feature = "env-git-gix",
+            feature = "env-git-cli"
+        ))]
+        pub(super) fn open(root: &Path) -> Result<Self, Error> {
+            #[cfg(feature = "env-git-git2")]
+            let repo = Box::new(git::git2::open_repo(root)?);
+            #[cfg(all(not(feature = "env-git-git2"), feature = "env-git-gix"))]
+            let repo = Box::new(git::gix::open_repo(root)?);
+            #[cfg(all(
+                not(feature = "env-git-git2"),
+                not(feature = "env-git-gix"),
+                feature = "env-git-cli"
+            ))]
+            let repo = Box::new(git::cli::open_repo(root)?);
+            Ok(Self::new(root.to_owned(), repo))
+        }
+    }
+    impl Vcs for GitRepository {
+        fn kind(&self) -> Kind {
+            Kind::Git
+        }
+
+        fn root(&self) -> &Path {
+            &self.root
+        }
+
+        fn query_head(&self) -> Result<Head, Error> {
+            Ok(match self.repo.query_head()? {
+                git::Head::Branch { name, .. } => Head::Branch(name),
+                git::Head::Commit(id) => Head::Revision(id.to_string()),
+                git::Head::Unborn { target } => {
+                    Head::Unborn(target.strip_prefix("refs/heads/").map(String::from))
+                }
+            })
+        }
+
+        fn query_status_summary(&self, untracked_files: bool) -> Result<StatusSummary, Error> {
+            let limits = StatusLimits {
+                untracked_files: if untracked_files {
+                    UntrackedFiles::FromConfig
+                } else {
+                    UntrackedFiles::No
+                },
+                ..self.status_limits
+            };
+            let summary = self.repo.query_status_summary(&limits)?;
+            Ok(StatusSummary {
+                added: summary.staging.added,
+                modified: summary.staging.modified + summary.working_tree.modified,
+                deleted: summary.staging.deleted + summary.working_tree.deleted,
+                // The working tree's added files are the untracked ones.
+                untracked: summary.working_tree.added,
+                conflicted: summary.conflicted,
+                incomplete: summary.incomplete,
+            })
+        }
+    }
+    impl From<git::Error> for Error {
+        fn from(err: git::Error) -> Self {
+            let kind = match err.kind() {
+                git::ErrorKind::NotFound => ErrorKind::NotFound,
+                _ => ErrorKind::Query,
+            };
+            Self::new(kind, err.message())
+        }
+    }
+}
+
+#[cfg(feature = "env-hg")]
+mod hg {
+    use super::{Error, ErrorKind, Head, Kind, StatusSummary, Vcs};
+    use crate::env::hg::{self, Repository};
+    use std::path::Path;
+
+    impl Vcs for Repository {
+        fn kind(&self) -> Kind {
+            Kind::Mercurial
+        }
+
+        fn root(&self) -> &Path {
+            Repository::root(self)
+        }
+
+        fn query_head(&self) -> Result<Head, Error> {
+            let head = hg::query_head(self)?;
+            let name = head.bookmark.unwrap_or(head.branch);
+            Ok(match head.revision {
+                Some(_) => Head::Branch(name),
+                None => Head::Unborn(Some(name)),
+            })
+        }
+
+        fn query_status_summary(&self, untracked_files: bool) -> Result<StatusSummary, Error> {
+            let summary = hg::query_status_summary(self, untracked_files)?;
+            Ok(StatusSummary {
+                added: summary.added,
+                modified: summary.modified,
+                deleted: summary.removed + summary.missing,
+                untracked: summary.unknown,
+                conflicted: 0,
+                incomplete: false,
+            })
+        }
+    }
+    impl From<hg::Error> for Error {
+        fn from(err: hg::Error) -> Self {
+            let kind = match err.kind() {
+                hg::ErrorKind::NotFound => ErrorKind::NotFound,
+                hg::ErrorKind::UnsupportedDirstate => ErrorKind::Unsupported,
+                _ => ErrorKind::Query,
+            };
+            Self::new(kind, err.message())
+        }
+    }
+}
+
+#[cfg(feature = "env-jj")]
+mod jj {
+    use super::{Error, ErrorKind, Head, Kind, StatusSummary, Vcs};
+    use crate::env::jj::{self, Workspace};
+    use std::path::Path;
+
+    impl Vcs for Workspace {
+        fn kind(&self) -> Kind {
+            Kind::Jujutsu
+        }
+
+        fn root(&self) -> &Path {
+            Workspace::root(self)
+        }
+
+        fn query_head(&self) -> Result<Head, Error> {
+            let working_copy = jj::query_working_copy(self)?;
+            Ok(match working_copy.bookmarks.into_iter().next() {
+                Some(bookmark) => Head::Branch(bookmark),
+                None => Head::Revision(working_copy.change_id_prefix),
+            })
+        }
+
+        /// Untracked files are never counted, as Jujutsu tracks every file that is not ignored.
+        fn query_status_summary(&self, _untracked_files: bool) -> Result<StatusSummary, Error> {
+            let summary = jj::query_change_summary(self)?;
+            let working_copy = jj::query_working_copy(self)?;
+            Ok(StatusSummary {
+                added: summary.added,
+                modified: summary.modified,
+                deleted: summary.deleted,
+                untracked: 0,
+                conflicted: usize::from(working_copy.conflict),
+                incomplete: false,
+            })
+        }
+    }
+    impl From<jj::Error> for Error {
+        fn from(err: jj::Error) -> Self {
+            let kind = match err.kind() {
+                jj::ErrorKind::NotFound => ErrorKind::NotFound,
+                _ => ErrorKind::Query,
+            };
+            Self::new(kind, err.message())
+        }
+    }
+}
@@ -0,0 +1,246 @@
+mod detect {
+    use crate::env::vcs::{detect, DetectOptions, Detected, ErrorKind, Kind};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    /// Options that keep the search from leaving `dir`.
+    fn options_within(dir: &Path) -> DetectOptions {
+        DetectOptions {
+            ceiling_dirs: vec![dir.parent().unwrap().to_owned()],
+            across_filesystems: false,
+        }
+    }
+
+    #[test]
+    fn finds_the_closest_working_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let options = options_within(root);
+        assert_eq!(
+            detect(root, &options).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+
+        fs::create_dir_all(root.join(".hg")).unwrap();
+        fs::create_dir_all(root.join("other")).unwrap();
+        fs::create_dir_all(root.join("colocated/.git")).unwrap();
+        fs::create_dir_all(root.join("colocated/.jj")).unwrap();
+        fs::create_dir_all(root.join("colocated/src")).unwrap();
+        fs::create_dir_all(root.join("worktree/src")).unwrap();
+        fs::write(root.join("worktree/.git"), "gitdir: elsewhere\n").unwrap();
+        fs::create_dir_all(root.join("fossil")).unwrap();
+        fs::write(root.join("fossil/.fslckout"), "").unwrap();
+
+        let detected = |dir: &str| detect(&root.join(dir), &options).unwrap();
+        assert_eq!(
+            detected("other"),
+            Detected {
+                kind: Kind::Mercurial,
+                root: root.to_owned(),
+            }
+        );
+        assert_eq!(detected("colocated/src").kind, Kind::Jujutsu);
+        assert_eq!(detected("colocated/src").root, root.join("colocated"));
+        assert_eq!(detected("worktree/src").kind, Kind::Git);
+        assert_eq!(detected("fossil").kind, Kind::Fossil);
+    }
+
+    #[test]
+    fn stops_at_ceiling_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".svn")).unwrap();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+
+        let options = DetectOptions {
+            ceiling_dirs: vec![root.join("a"), "relative".into()],
+            across_filesystems: false,
+        };
+        assert_eq!(
+            detect(&root.join("a/b"), &options).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+        // The ceiling directory is only searched when the search starts there.
+        fs::create_dir_all(root.join("a/.pijul")).unwrap();
+        assert_eq!(
+            detect(&root.join("a/b"), &options).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+        assert_eq!(detect(&root.join("a"), &options).unwrap().kind, Kind::Pijul);
+        assert_eq!(
+            detect(&root.join("a/b"), &options_within(root))
+                .unwrap()
+                .kind,
+            Kind::Pijul
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_relative_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join(".hg")).unwrap();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+
+        // The path of `a/b` from the working directory, through the root.
+        let cwd = std::env::current_dir().unwrap();
+        let relative = cwd
+            .components()
+            .skip(1)
+            .map(|_| Path::new(".."))
+            .collect::<PathBuf>()
+            .join(root.strip_prefix("/").unwrap())
+            .join("a/b");
+        assert!(relative.is_relative());
+        assert_eq!(
+            detect(&relative, &options_within(&root)).unwrap(),
+            Detected {
+                kind: Kind::Mercurial,
+                root: root.clone(),
+            }
+        );
+        assert_eq!(
+            detect(&root.join("a/../a/b"), &options_within(&root))
+                .unwrap()
+                .root,
+            root
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stops_at_file_system_boundaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".hg")).unwrap();
+        // `/proc` is on its own file system, which the search leaves at `root`.
+        std::os::unix::fs::symlink("/proc", root.join("proc")).unwrap();
+        let start = root.join("proc/self");
+
+        let options = options_within(root);
+        assert_eq!(
+            detect(&start, &options).map_err(|err| err.kind()),
+            Err(ErrorKind::NotFound)
+        );
+        let options = DetectOptions {
+            across_filesystems: true,
+            ..options
+        };
+        assert_eq!(detect(&start, &options).unwrap().root, root);
+    }
+}
+
+#[cfg(feature = "env-hg")]
+mod hg {
+    use crate::env::vcs::{open_vcs, DetectOptions, ErrorKind, Head, Kind, StatusSummary};
+    use std::fs;
+
+    #[test]
+    fn queries_through_the_trait() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".hg")).unwrap();
+        fs::write(root.join("a"), "").unwrap();
+        let options = DetectOptions {
+            ceiling_dirs: vec![root.parent().unwrap().to_owned()],
+            across_filesystems: false,
+        };
+
+        let vcs = open_vcs(root, &options).unwrap();
+        assert_eq!(vcs.kind(), Kind::Mercurial);
+        assert_eq!(vcs.root(), root);
+        assert_eq!(
+            vcs.query_head().unwrap(),
+            Head::Unborn(Some(String::from("default")))
+        );
+        assert_eq!(
+            vcs.query_status_summary(true).unwrap(),
+            StatusSummary {
+                untracked: 1,
+                ..StatusSummary::default()
+            }
+        );
+        assert!(!vcs.query_status_summary(false).unwrap().any_changes());
+
+        fs::create_dir_all(root.join(".svn")).unwrap();
+        fs::remove_dir(root.join(".hg")).unwrap();
+        assert_eq!(
+            open_vcs(root, &options)
+                .map(|_| ())
+                .map_err(|err| err.kind()),
+            Err(ErrorKind::Unsupported)
+        );
+    }
+}
+
+#[cfg(any(
+    feature = "env-git-git2",
+    feature = "env-git-gix",
+    feature = "env-git-cli"
+))]
+mod git {
+    use crate::env::{
+        git::test::git,
+        vcs::{open_vcs, DetectOptions, Head, Kind, StatusSummary},
+    };
+    use std::fs;
+
+    #[test]
+    fn queries_through_the_trait() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "--quiet", "--initial-branch=trunk"]);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a"), "").unwrap();
+
+        let vcs = open_vcs(&root.join("src"), &DetectOptions::default()).unwrap();
+        assert_eq!(vcs.kind(), Kind::Git);
+        assert_eq!(vcs.root(), root);
+        assert_eq!(
+            vcs.query_head().unwrap(),
+            Head::Unborn(Some(String::from("trunk")))
+        );
+        assert_eq!(
+            vcs.query_status_summary(true).unwrap(),
+            StatusSummary {
+                untracked: 1,
+                ..StatusSummary::default()
+            }
+        );
+        assert!(!vcs.query_status_summary(false).unwrap().any_changes());
+    }
+
+    #[cfg(feature = "env-git-cli")]
+    #[test]
+    fn applies_the_status_limits() {
+        use crate::env::{
+            git::{cli, StatusLimits},
+            vcs::{GitRepository, Vcs},
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "--quiet"]);
+        for name in ["a", "b", "c"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+
+        let repo = GitRepository::new(root.to_owned(), Box::new(cli::open_repo(root).unwrap()))
+            .with_status_limits(StatusLimits {
+                max_files: Some(2),
+                ..StatusLimits::default()
+            });
+        assert_eq!(
+            repo.query_status_summary(true).unwrap(),
+            StatusSummary {
+                untracked: 2,
+                incomplete: true,
+                ..StatusSummary::default()
+            }
+        );
+        assert!(!repo.query_status_summary(false).unwrap().incomplete);
+    }
+}
@@ -9,5 +9,11 @@ pub mod duration;
 #[cfg(feature = "fmt-git")]
 pub mod git;
 
+#[cfg(feature = "fmt-hg")]
+pub mod hg;
+
+#[cfg(feature = "fmt-jj")]
+pub mod jj;
+
 #[cfg(feature = "fmt-path")]
 pub mod path;
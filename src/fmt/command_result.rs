@@ -83,6 +83,11 @@ pub fn write_exit_code_symbol_with_defaults(
 
 /// When to show the exit code in [`write_exit_code_symbol`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum When {
     /// Never show the exit code.
     Never,
@@ -457,6 +457,32 @@ pub fn write_skip_high_zeros(
     Ok(())
 }
 
+/// Write only the most significant nonzero component of a [`HumanDuration`], as in `3h`.
+///
+/// A zero duration is written as `0s`.
+pub fn write_most_significant(
+    writer: &mut (impl io::Write + ?Sized),
+    duration: HumanDuration,
+) -> io::Result<()> {
+    if duration.days != 0 {
+        write!(writer, "{}d", duration.days)
+    } else if duration.hours != 0 {
+        write!(writer, "{}h", duration.hours)
+    } else if duration.minutes != 0 {
+        write!(writer, "{}m", duration.minutes)
+    } else if duration.seconds != 0 {
+        write!(writer, "{}s", duration.seconds)
+    } else if duration.milliseconds != 0 {
+        write!(writer, "{}ms", duration.milliseconds)
+    } else if duration.microseconds != 0 {
+        write!(writer, "{}µs", duration.microseconds)
+    } else if duration.nanoseconds != 0 {
+        write!(writer, "{}ns", duration.nanoseconds)
+    } else {
+        write!(writer, "0s")
+    }
+}
+
 /// Write a [`HumanDuration`] up to the least significant nonzero component.
 pub fn write_skip_low_zeros(
     writer: &mut (impl io::Write + ?Sized),
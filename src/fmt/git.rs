@@ -3,18 +3,23 @@
 //! [git]: https://git-scm.com/
 
 use crate::{
-    env::git::{AheadBehind, ChangeSummary, Head, StatusSummary},
+    env::git::{
+        AheadBehind, ChangeSummary, CommitDescription, DiffStats, Head, HostKind, LastCommit,
+        LfsSummary, LineChanges, LinkedWorktree, NearestBranch, Operation, Progress, RemoteUrl,
+        Shallow, SparseCheckout, StatusSummary, SubmoduleSummary, Superproject, Upstream,
+    },
+    fmt::duration::{write_most_significant, HumanDuration},
     styling::StyledWrite,
 };
-use std::io;
+use std::{io, time::SystemTime};
 
 /// Writes a short representation of a [`Head`].
 ///
 /// Writes the name (when [`Branch`][`Head::Branch`] or [`Unborn`][`Head::Unborn`])
 /// or short hash (when [`Commit`][`Head::Commit`]) of the [`Head`]'s target
 /// preceded by a symbol indicating the [`Head`]'s state.
-/// When applicable and present, the [ahead and behind upstream count][`Head::Branch::upstream`]
-/// then follows, in the format of [`write_ahead_behind`].
+/// When applicable and configured, the [upstream][`Head::Branch::upstream`]
+/// then follows, in the format of [`write_upstream`].
 pub fn write_head(writer: &mut (impl StyledWrite + ?Sized), head: &Head) -> io::Result<()> {
     match head {
         Head::Unborn { target } => write!(
@@ -24,9 +29,12 @@ pub fn write_head(writer: &mut (impl StyledWrite + ?Sized), head: &Head) -> io::
         ),
         Head::Branch { name, upstream } => {
             write!(writer, "{}", name)?;
-            if let Ok(Some(upstream)) = upstream {
-                write!(writer, " ")?;
-                write_ahead_behind(writer, upstream)?;
+            match upstream {
+                Ok(Upstream::NotConfigured) | Err(_) => {}
+                Ok(upstream) => {
+                    write!(writer, " ")?;
+                    write_upstream(writer, upstream)?;
+                }
             }
             Ok(())
         }
@@ -37,6 +45,152 @@ pub fn write_head(writer: &mut (impl StyledWrite + ?Sized), head: &Head) -> io::
     }
 }
 
+/// Writes the most meaningful name of a [`CommitDescription`],
+/// meant to replace the short hash written by [`write_head`] for a [detached HEAD].
+///
+/// The name is preceded by `◉`, like in [`write_head`], and is the first available of:
+/// the [tag][`CommitDescription::tag`],
+/// the [nearest branch][`CommitDescription::nearest_branch`] if its tip is the commit,
+/// the [`describe`][`CommitDescription::describe`] output,
+/// the nearest branch followed by `~` and the [distance][`NearestBranch::distance`],
+/// and the short hash.
+///
+/// [detached HEAD]: https://git-scm.com/docs/gitglossary#def_HEAD
+pub fn write_commit_description(
+    writer: &mut (impl StyledWrite + ?Sized),
+    description: &CommitDescription,
+) -> io::Result<()> {
+    match description {
+        CommitDescription { tag: Some(tag), .. } => write!(writer, "◉{}", tag),
+        CommitDescription {
+            nearest_branch: Some(NearestBranch { name, distance: 0 }),
+            ..
+        } => write!(writer, "◉{}", name),
+        CommitDescription {
+            describe: Some(describe),
+            ..
+        } => write!(writer, "◉{}", describe),
+        CommitDescription {
+            nearest_branch: Some(NearestBranch { name, distance }),
+            ..
+        } => write!(writer, "◉{}~{}", name, distance),
+        CommitDescription { id, .. } => write_head(writer, &Head::Commit(*id)),
+    }
+}
+
+/// Writes how long ago a [`LastCommit`] was committed relative to `now`,
+/// as in `last commit 3h ago`.
+///
+/// The age is written in the format of [`write_most_significant`].
+/// Commits from the future, as when clocks are out of sync, are taken to be from `now`.
+pub fn write_last_commit_age(
+    writer: &mut (impl StyledWrite + ?Sized),
+    commit: &LastCommit,
+    now: SystemTime,
+) -> io::Result<()> {
+    let age = now.duration_since(commit.time).unwrap_or_default();
+    write!(writer, "last commit ")?;
+    write_most_significant(writer, HumanDuration::from_seconds(age.as_secs().into()))?;
+    write!(writer, " ago")
+}
+
+/// Writes a short representation of an [`Operation`], meant to be written next to the [`Head`].
+///
+/// The operation is written in uppercase, as `MERGING`, `CHERRY-PICKING`, `REVERTING`,
+/// `BISECTING`, `AM` or `REBASE` (`REBASE-i` when interactive),
+/// followed by its [progress][`Progress`] as `current/total`, when known,
+/// and by the name of the branch being rebased within parentheses, when known,
+/// as in `REBASE-i 3/7 (feature)`.
+pub fn write_operation(
+    writer: &mut (impl StyledWrite + ?Sized),
+    operation: &Operation,
+) -> io::Result<()> {
+    fn write_progress(
+        writer: &mut (impl StyledWrite + ?Sized),
+        progress: &Option<Progress>,
+    ) -> io::Result<()> {
+        match progress {
+            Some(Progress { current, total }) => write!(writer, " {}/{}", current, total),
+            None => Ok(()),
+        }
+    }
+
+    match operation {
+        Operation::Merge => write!(writer, "MERGING"),
+        Operation::CherryPick => write!(writer, "CHERRY-PICKING"),
+        Operation::Revert => write!(writer, "REVERTING"),
+        Operation::Bisect => write!(writer, "BISECTING"),
+        Operation::ApplyMailbox { progress } => {
+            write!(writer, "AM")?;
+            write_progress(writer, progress)
+        }
+        Operation::Rebase {
+            interactive,
+            progress,
+            branch,
+        } => {
+            write!(writer, "REBASE{}", if *interactive { "-i" } else { "" })?;
+            write_progress(writer, progress)?;
+            match branch {
+                Some(branch) => write!(writer, " ({})", branch),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Writes a short representation of an [`Upstream`].
+///
+/// When [present][`Upstream::Present`], the ahead and behind count is written
+/// in the format of [`write_ahead_behind`].
+/// When [gone][`Upstream::Gone`], `gone` is written,
+/// and when [not configured][`Upstream::NotConfigured`], nothing is.
+pub fn write_upstream(
+    writer: &mut (impl StyledWrite + ?Sized),
+    upstream: &Upstream,
+) -> io::Result<()> {
+    match upstream {
+        Upstream::NotConfigured => Ok(()),
+        Upstream::Gone { .. } => write!(writer, "gone"),
+        Upstream::Present { ahead_behind, .. } => write_ahead_behind(writer, ahead_behind),
+    }
+}
+
+/// Writes a short representation of the [`Upstream`] a branch is pushed to,
+/// meant to be written after the [`Head`] when it differs from the branch's upstream.
+///
+/// The push target is written preceded by `→`, in the format of [`write_upstream`],
+/// as in `→↑2`, and nothing is written when it is [not configured][`Upstream::NotConfigured`].
+pub fn write_push_target(
+    writer: &mut (impl StyledWrite + ?Sized),
+    push_target: &Upstream,
+) -> io::Result<()> {
+    if *push_target == Upstream::NotConfigured {
+        return Ok(());
+    }
+    write!(writer, "→")?;
+    write_upstream(writer, push_target)
+}
+
+/// Writes an icon for the [kind of host][`HostKind`] of a [`RemoteUrl`].
+///
+/// The icons are those of [Nerd Fonts](https://www.nerdfonts.com/):
+/// the logos of GitHub, GitLab and Bitbucket,
+/// the git logo for other hosts and a folder for local repositories.
+pub fn write_remote_host(
+    writer: &mut (impl StyledWrite + ?Sized),
+    url: &RemoteUrl,
+) -> io::Result<()> {
+    let icon = match url.host_kind {
+        HostKind::GitHub => '\u{f09b}',
+        HostKind::GitLab => '\u{f296}',
+        HostKind::Bitbucket => '\u{f171}',
+        HostKind::SelfHosted => '\u{f1d3}',
+        HostKind::Local => '\u{f07b}',
+    };
+    write!(writer, "{}", icon)
+}
+
 /// Writes a short representation of an [`AheadBehind`].
 ///
 /// [`ahead`] and [`behind`] are written preceded by `↑` and `↓`, respectively,
@@ -76,13 +230,18 @@ pub fn write_ahead_behind(
 /// and separated by a vertical bar `|`.
 /// When it is not zero, the [count of files with merge conflicts][`StatusSummary::conflicted`]
 /// follows preceded by an exclamation mark `!`.
+///
+/// When the summary is [incomplete][`StatusSummary::incomplete`],
+/// each count is followed by a `+`, as in `~99+`,
+/// and a question mark `?` is written if there are no counts at all.
 pub fn write_status_summary(
     writer: &mut (impl StyledWrite + ?Sized),
     status: &StatusSummary,
 ) -> io::Result<()> {
+    let suffix = if status.incomplete { "+" } else { "" };
     let mut is_preceded = false;
     if status.staging.any_changes() {
-        write_change_summary(writer, &status.staging)?;
+        write_change_summary_with_suffix(writer, &status.staging, suffix)?;
         is_preceded = true;
     }
     if status.working_tree.any_changes() {
@@ -90,14 +249,16 @@ pub fn write_status_summary(
             write!(writer, " ")?;
         }
         write!(writer, "| ")?;
-        write_change_summary(writer, &status.working_tree)?;
+        write_change_summary_with_suffix(writer, &status.working_tree, suffix)?;
         is_preceded = true;
     }
     if status.conflicted != 0 {
         if is_preceded {
             write!(writer, " ")?;
         }
-        write!(writer, "!{}", status.conflicted)?;
+        write!(writer, "!{}{}", status.conflicted, suffix)?;
+    } else if !is_preceded && status.incomplete {
+        write!(writer, "?")?;
     }
     Ok(())
 }
@@ -111,24 +272,183 @@ pub fn write_status_summary(
 pub fn write_change_summary(
     writer: &mut (impl StyledWrite + ?Sized),
     changes: &ChangeSummary,
+) -> io::Result<()> {
+    write_change_summary_with_suffix(writer, changes, "")
+}
+
+fn write_change_summary_with_suffix(
+    writer: &mut (impl StyledWrite + ?Sized),
+    changes: &ChangeSummary,
+    suffix: &str,
 ) -> io::Result<()> {
     let mut is_preceded = false;
     if changes.added != 0 {
-        write!(writer, "+{}", changes.added)?;
+        write!(writer, "+{}{}", changes.added, suffix)?;
         is_preceded = true;
     }
     if changes.modified != 0 {
         if is_preceded {
             write!(writer, " ")?;
         }
-        write!(writer, "~{}", changes.modified)?;
+        write!(writer, "~{}{}", changes.modified, suffix)?;
         is_preceded = true;
     }
     if changes.deleted != 0 {
         if is_preceded {
             write!(writer, " ")?;
         }
-        write!(writer, "-{}", changes.deleted)?;
+        write!(writer, "-{}{}", changes.deleted, suffix)?;
+    }
+    Ok(())
+}
+
+/// Writes a short representation of a [`DiffStats`].
+///
+/// The [staging area][`DiffStats::staging`] and [working tree][`DiffStats::working_tree`]
+/// changes are, in that order, written following the format of [`write_line_changes`]
+/// and separated by a vertical bar `|`, as in [`write_status_summary`].
+pub fn write_diff_stats(
+    writer: &mut (impl StyledWrite + ?Sized),
+    stats: &DiffStats,
+) -> io::Result<()> {
+    let mut is_preceded = false;
+    if stats.staging.any_changes() {
+        write_line_changes(writer, &stats.staging)?;
+        is_preceded = true;
+    }
+    if stats.working_tree.any_changes() {
+        if is_preceded {
+            write!(writer, " ")?;
+        }
+        write!(writer, "| ")?;
+        write_line_changes(writer, &stats.working_tree)?;
+    }
+    Ok(())
+}
+
+/// Writes a short representation of a [`LineChanges`], as in `+120 -34`.
+///
+/// The [insertions][`LineChanges::insertions`] and [deletions][`LineChanges::deletions`]
+/// are, in that order, written preceded by `+` and `-`, respectively.
+/// Any of these counts that are zero are omitted.
+pub fn write_line_changes(
+    writer: &mut (impl StyledWrite + ?Sized),
+    changes: &LineChanges,
+) -> io::Result<()> {
+    if changes.insertions != 0 {
+        write!(writer, "+{}", changes.insertions)?;
+        if changes.deletions != 0 {
+            write!(writer, " ")?;
+        }
+    }
+    if changes.deletions != 0 {
+        write!(writer, "-{}", changes.deletions)?;
+    }
+    Ok(())
+}
+
+/// Writes a short representation of a [`LinkedWorktree`], as in `⎇ wt:feature`.
+pub fn write_linked_worktree(
+    writer: &mut (impl StyledWrite + ?Sized),
+    worktree: &LinkedWorktree,
+) -> io::Result<()> {
+    write!(writer, "⎇ wt:{}", worktree.name)
+}
+
+/// Writes a short representation of a [`Superproject`], as in `sub:libfoo*`.
+///
+/// The submodule is named after the last component of its [path][`Superproject::path`],
+/// which is followed by `*` when the submodule is [out of date][`Superproject::out_of_date`].
+pub fn write_superproject(
+    writer: &mut (impl StyledWrite + ?Sized),
+    superproject: &Superproject,
+) -> io::Result<()> {
+    let name = superproject
+        .path
+        .file_name()
+        .unwrap_or(superproject.path.as_os_str());
+    write!(
+        writer,
+        "sub:{}{}",
+        name.to_string_lossy(),
+        if superproject.out_of_date { "*" } else { "" },
+    )
+}
+
+/// Writes a short representation of a [`SubmoduleSummary`], as in `sub:*1 ~2`.
+///
+/// The [out of date][`SubmoduleSummary::out_of_date`] and [modified][`SubmoduleSummary::modified`]
+/// counts are, in that order, written preceded by `*` and `~`, respectively,
+/// and omitted when zero.
+/// Nothing is written when there are no changes.
+pub fn write_submodule_summary(
+    writer: &mut (impl StyledWrite + ?Sized),
+    summary: &SubmoduleSummary,
+) -> io::Result<()> {
+    if !summary.any_changes() {
+        return Ok(());
+    }
+    write!(writer, "sub:")?;
+    if summary.out_of_date != 0 {
+        write!(writer, "*{}", summary.out_of_date)?;
+        if summary.modified != 0 {
+            write!(writer, " ")?;
+        }
+    }
+    if summary.modified != 0 {
+        write!(writer, "~{}", summary.modified)?;
+    }
+    Ok(())
+}
+
+/// Writes a short representation of a [`Shallow`] history, as in `shallow`.
+///
+/// When the history has more than one [boundary][`Shallow::boundary`] commit,
+/// as after fetching several branches with `--depth`, their count follows, as in `shallow:3`.
+pub fn write_shallow(
+    writer: &mut (impl StyledWrite + ?Sized),
+    shallow: &Shallow,
+) -> io::Result<()> {
+    match shallow.boundary.len() {
+        0 | 1 => write!(writer, "shallow"),
+        count => write!(writer, "shallow:{}", count),
+    }
+}
+
+/// Writes a short representation of a [`SparseCheckout`] mode,
+/// which is `sparse:cone` in cone mode and `sparse` otherwise.
+pub fn write_sparse_checkout(
+    writer: &mut (impl StyledWrite + ?Sized),
+    sparse_checkout: SparseCheckout,
+) -> io::Result<()> {
+    match sparse_checkout {
+        SparseCheckout::Cone => write!(writer, "sparse:cone"),
+        SparseCheckout::NonCone => write!(writer, "sparse"),
+    }
+}
+
+/// Writes a short representation of an [`LfsSummary`], as in `lfs:↓2 ⊘1`.
+///
+/// The [pointer file][`LfsSummary::pointers`] and [read-only][`LfsSummary::read_only`] counts
+/// are, in that order, written preceded by `↓` and `⊘`, respectively,
+/// and omitted when zero.
+/// Nothing is written when there are no such files.
+pub fn write_lfs_summary(
+    writer: &mut (impl StyledWrite + ?Sized),
+    summary: &LfsSummary,
+) -> io::Result<()> {
+    if !summary.any_files() {
+        return Ok(());
+    }
+    write!(writer, "lfs:")?;
+    if summary.pointers != 0 {
+        write!(writer, "↓{}", summary.pointers)?;
+        if summary.read_only != 0 {
+            write!(writer, " ")?;
+        }
+    }
+    if summary.read_only != 0 {
+        write!(writer, "⊘{}", summary.read_only)?;
     }
     Ok(())
 }
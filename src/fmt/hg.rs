@@ -0,0 +1,52 @@
+//! Formatting for information about a [Mercurial] repository.
+//!
+//! [Mercurial]: https://www.mercurial-scm.org/
+
+use crate::{
+    env::hg::{Head, StatusSummary},
+    styling::StyledWrite,
+};
+use std::io;
+
+/// Writes a short representation of a [`Head`].
+///
+/// Writes the name of the active [bookmark][`Head::bookmark`], if any,
+/// or else of the [branch][`Head::branch`],
+/// preceded by `○` when no [revision][`Head::revision`] is checked out,
+/// like [`fmt::git::write_head`][`crate::fmt::git::write_head`] does for an unborn branch.
+pub fn write_head(writer: &mut (impl StyledWrite + ?Sized), head: &Head) -> io::Result<()> {
+    let name = head.bookmark.as_ref().unwrap_or(&head.branch);
+    match head.revision {
+        Some(_) => write!(writer, "{}", name),
+        None => write!(writer, "○{}", name),
+    }
+}
+
+/// Writes a short representation of a [`StatusSummary`].
+///
+/// The [added][`StatusSummary::added`], [modified][`StatusSummary::modified`],
+/// [removed][`StatusSummary::removed`], [missing][`StatusSummary::missing`]
+/// and [unknown][`StatusSummary::unknown`] counts are, in that order, written
+/// preceded by `+`, `~`, `-`, `!` and `?`, respectively, and separated by spaces.
+/// Any of these counts that are zero are omitted.
+pub fn write_status_summary(
+    writer: &mut (impl StyledWrite + ?Sized),
+    status: &StatusSummary,
+) -> io::Result<()> {
+    let counts = [
+        ('+', status.added),
+        ('~', status.modified),
+        ('-', status.removed),
+        ('!', status.missing),
+        ('?', status.unknown),
+    ];
+    let mut is_preceded = false;
+    for (symbol, count) in counts.iter().filter(|(_, count)| *count != 0) {
+        if is_preceded {
+            write!(writer, " ")?;
+        }
+        write!(writer, "{}{}", symbol, count)?;
+        is_preceded = true;
+    }
+    Ok(())
+}
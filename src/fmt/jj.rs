@@ -0,0 +1,49 @@
+//! Formatting for information about a [Jujutsu] workspace.
+//!
+//! [Jujutsu]: https://jj-vcs.github.io/jj/
+
+use crate::{
+    env::{git::Head, jj::WorkingCopy},
+    fmt::git::write_head,
+    styling::StyledWrite,
+};
+use std::io;
+
+/// Writes a short representation of a [`WorkingCopy`], as in `@kx main ∅`.
+///
+/// The [change ID prefix][`WorkingCopy::change_id_prefix`] is written preceded by `@`,
+/// then each of the [bookmarks][`WorkingCopy::bookmarks`],
+/// then `∅` when the commit is [empty][`WorkingCopy::empty`]
+/// and `!` when it is [conflicted][`WorkingCopy::conflict`], all separated by spaces.
+pub fn write_working_copy(
+    writer: &mut (impl StyledWrite + ?Sized),
+    working_copy: &WorkingCopy,
+) -> io::Result<()> {
+    write!(writer, "@{}", working_copy.change_id_prefix)?;
+    for bookmark in &working_copy.bookmarks {
+        write!(writer, " {}", bookmark)?;
+    }
+    if working_copy.empty {
+        write!(writer, " ∅")?;
+    }
+    if working_copy.conflict {
+        write!(writer, " !")?;
+    }
+    Ok(())
+}
+
+/// Writes the [`WorkingCopy`] of a Jujutsu workspace if there is one,
+/// or else the git repository's [`Head`], in the format of [`write_head`].
+///
+/// Jujutsu leaves the HEAD of the git repository it works on detached
+/// at the parent of the working-copy commit, so the working copy tells more.
+pub fn write_working_copy_or_head(
+    writer: &mut (impl StyledWrite + ?Sized),
+    working_copy: Option<&WorkingCopy>,
+    head: &Head,
+) -> io::Result<()> {
+    match working_copy {
+        Some(working_copy) => write_working_copy(writer, working_copy),
+        None => write_head(writer, head),
+    }
+}
@@ -0,0 +1,285 @@
+//! Layout of prompt parts on a line of known width.
+//!
+//! Parts are [rendered][`Rendered::render`] separately, so that their widths are known
+//! before they are written, and then written to any [`StyledWrite`].
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    styling::{Style, StyleChange, StyledWrite},
+    writers::width_counting::{display_width, AmbiguousWidth},
+};
+use std::io;
+
+/// A part of a prompt rendered ahead of being written.
+///
+/// It holds the text and the style operations issued while rendering,
+/// which are replayed when the part is [written][`Rendered::write_to`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Rendered {
+    operations: Vec<Operation>,
+}
+impl Rendered {
+    /// Renders a part by running `render` on a recording [`StyledWrite`].
+    ///
+    /// The recording writer starts with the [default style][`Style::default`].
+    ///
+    /// # Errors
+    ///
+    /// When `render` fails, the error is bubbled up.
+    pub fn render(render: impl FnOnce(&mut dyn StyledWrite) -> io::Result<()>) -> io::Result<Self> {
+        let mut recorder = Recorder::default();
+        render(&mut recorder)?;
+        recorder.finish_text();
+        Ok(Self {
+            operations: recorder.operations,
+        })
+    }
+
+    /// Tells whether no text was written while rendering.
+    pub fn is_empty(&self) -> bool {
+        !self
+            .operations
+            .iter()
+            .any(|operation| matches!(operation, Operation::Text(text) if !text.is_empty()))
+    }
+
+    /// The number of terminal columns taken by the rendered text, as in [`display_width`].
+    pub fn width(&self, ambiguous: AmbiguousWidth) -> usize {
+        self.operations
+            .iter()
+            .map(|operation| match operation {
+                Operation::Text(text) => display_width(text, ambiguous),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Writes the rendered part to `writer`.
+    pub fn write_to(&self, writer: &mut (impl StyledWrite + ?Sized)) -> io::Result<()> {
+        for operation in &self.operations {
+            operation.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    /// A copy of the rendered part with its text cut
+    /// so that it takes at most `max_width` columns.
+    ///
+    /// When the text is cut, `ellipsis` is written in place of the removed text
+    /// (and is itself cut if wider than `max_width`).
+    /// All style operations are kept, even those following the cut.
+    pub fn truncated(&self, max_width: usize, ellipsis: &str, ambiguous: AmbiguousWidth) -> Self {
+        if self.width(ambiguous) <= max_width {
+            return self.clone();
+        }
+
+        let ellipsis_width = display_width(ellipsis, ambiguous).min(max_width);
+        let mut remaining = max_width - ellipsis_width;
+        let mut is_cut = false;
+        let mut operations = Vec::with_capacity(self.operations.len());
+        for operation in &self.operations {
+            match operation {
+                Operation::Text(text) => {
+                    if is_cut {
+                        continue;
+                    }
+                    let (kept, cut) = split_at_width(text, remaining, ambiguous);
+                    remaining -= display_width(kept, ambiguous);
+                    let mut text = String::from(kept);
+                    if cut {
+                        text.push_str(split_at_width(ellipsis, ellipsis_width, ambiguous).0);
+                        is_cut = true;
+                    }
+                    operations.push(Operation::Text(text));
+                }
+                operation => operations.push(operation.clone()),
+            }
+        }
+        Self { operations }
+    }
+}
+
+/// Splits `text` at the last character boundary that keeps the first part
+/// within `max_width` columns.
+///
+/// The returned flag tells whether anything was left out of the first part.
+fn split_at_width(text: &str, max_width: usize, ambiguous: AmbiguousWidth) -> (&str, bool) {
+    let mut width = 0;
+    for (index, character) in text.char_indices() {
+        let end = index + character.len_utf8();
+        width += display_width(&text[index..end], ambiguous);
+        if width > max_width {
+            return (&text[..index], true);
+        }
+    }
+    (text, false)
+}
+
+/// An operation issued on the recording writer of [`Rendered::render`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Operation {
+    Text(String),
+    ChangeStyle(StyleChange),
+    ResetStyle,
+    SwapColors,
+}
+impl Operation {
+    fn write_to(&self, writer: &mut (impl StyledWrite + ?Sized)) -> io::Result<()> {
+        match self {
+            Operation::Text(text) => writer.write_all(text.as_bytes()),
+            Operation::ChangeStyle(change) => writer.change_style(change.clone()),
+            Operation::ResetStyle => writer.reset_style(),
+            Operation::SwapColors => writer.swap_colors(),
+        }
+    }
+}
+
+/// The recording writer of [`Rendered::render`].
+#[derive(Default)]
+struct Recorder {
+    operations: Vec<Operation>,
+    style: Style,
+    text: Vec<u8>,
+}
+impl Recorder {
+    fn finish_text(&mut self) {
+        if !self.text.is_empty() {
+            let text = String::from_utf8_lossy(&self.text).into_owned();
+            self.operations.push(Operation::Text(text));
+            self.text.clear();
+        }
+    }
+
+    fn push(&mut self, operation: Operation) {
+        self.finish_text();
+        self.operations.push(operation);
+    }
+}
+impl io::Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.text.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+impl StyledWrite for Recorder {
+    fn style(&self) -> &Style {
+        &self.style
+    }
+
+    fn change_style(&mut self, change: StyleChange) -> io::Result<()> {
+        self.style = change.apply_to(&self.style);
+        self.push(Operation::ChangeStyle(change));
+        Ok(())
+    }
+
+    fn reset_style(&mut self) -> io::Result<()> {
+        self.style = Default::default();
+        self.push(Operation::ResetStyle);
+        Ok(())
+    }
+
+    fn swap_colors(&mut self) -> io::Result<()> {
+        self.style.swap_colors();
+        self.push(Operation::SwapColors);
+        Ok(())
+    }
+}
+
+/// What to do with the right part of a [`LeftRightLayout`] when it does not fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow<'e> {
+    /// Omit the right part.
+    Drop,
+    /// Cut the right part's text to fit, writing `ellipsis` in place of the removed text.
+    ///
+    /// The right part is still omitted if no column is left for it.
+    Truncate { ellipsis: &'e str },
+}
+
+/// A single line layout with a left part and a right part flush to the right edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LeftRightLayout<'e> {
+    /// The width of the line, in columns (usually the terminal's width).
+    pub columns: usize,
+    /// The minimum number of columns between the left and the right parts.
+    pub min_gap: usize,
+    /// What to do with the right part when it does not fit.
+    pub overflow: Overflow<'e>,
+    /// How to measure characters whose East Asian Width is ambiguous.
+    pub ambiguous: AmbiguousWidth,
+}
+impl<'e> LeftRightLayout<'e> {
+    /// Creates a [`LeftRightLayout`] for a line of `columns` columns.
+    ///
+    /// The parts are kept at least one column apart,
+    /// the right part is [dropped][`Overflow::Drop`] when it does not fit,
+    /// and ambiguous characters are measured as [narrow][`AmbiguousWidth::Narrow`].
+    pub const fn new(columns: usize) -> Self {
+        Self {
+            columns,
+            min_gap: 1,
+            overflow: Overflow::Drop,
+            ambiguous: AmbiguousWidth::Narrow,
+        }
+    }
+
+    /// Writes `left`, followed by spaces and then `right`, so that `right` ends at the last column.
+    ///
+    /// When both parts do not fit, `right` is handled according to [`Self::overflow`].
+    /// The padding is written in the style `writer` had before `left` was written.
+    /// The same style is restored after each part.
+    pub fn write(
+        &self,
+        writer: &mut (impl StyledWrite + ?Sized),
+        left: &Rendered,
+        right: &Rendered,
+    ) -> io::Result<()> {
+        let base_style = writer.style().clone();
+
+        left.write_to(writer)?;
+        restore_style(writer, &base_style)?;
+
+        let left_width = left.width(self.ambiguous);
+        let right_width = right.width(self.ambiguous);
+        let available = self
+            .columns
+            .saturating_sub(left_width)
+            .saturating_sub(self.min_gap);
+
+        if right.is_empty() || available == 0 {
+            return Ok(());
+        }
+
+        let truncated;
+        let (right, right_width) = if right_width <= available {
+            (right, right_width)
+        } else {
+            match self.overflow {
+                Overflow::Drop => return Ok(()),
+                Overflow::Truncate { ellipsis } => {
+                    truncated = right.truncated(available, ellipsis, self.ambiguous);
+                    (&truncated, truncated.width(self.ambiguous))
+                }
+            }
+        };
+
+        let padding = self.columns - left_width - right_width;
+        write!(writer, "{:1$}", "", padding)?;
+
+        right.write_to(writer)?;
+        restore_style(writer, &base_style)
+    }
+}
+
+fn restore_style(writer: &mut (impl StyledWrite + ?Sized), style: &Style) -> io::Result<()> {
+    if writer.style() != style {
+        writer.change_style(StyleChange::setting_to(style))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+#[cfg(all(test, feature = "not_styled_writer"))]
+mod left_right_layout {
+
+    use crate::{
+        layout::{LeftRightLayout, Overflow, Rendered},
+        styling::{Color, Color4Bit, Style},
+        writers::not_styled::NotStyledWriter,
+    };
+
+    fn layout(layout: LeftRightLayout, left: &str, right: &str) -> String {
+        let left = Rendered::render(|writer| write!(writer, "{}", left)).unwrap();
+        let right = Rendered::render(|writer| {
+            styled_write!(writer, foreground: Color::Color4Bit(Color4Bit::DARK_BLUE); "{}", right)
+        })
+        .unwrap();
+        let mut writer = NotStyledWriter::new(Vec::new());
+        layout.write(&mut writer, &left, &right).unwrap();
+        assert_eq!(writer.style, Style::default());
+        String::from_utf8(writer.writer).unwrap()
+    }
+
+    #[test]
+    fn right_part_is_flush_to_the_right_edge() {
+        assert_eq!(layout(LeftRightLayout::new(10), "ab", "cd"), "ab      cd");
+        assert_eq!(layout(LeftRightLayout::new(10), "漢", "字"), "漢      字");
+        assert_eq!(layout(LeftRightLayout::new(5), "ab", "cd"), "ab cd");
+    }
+
+    #[test]
+    fn colliding_right_part_is_dropped() {
+        assert_eq!(layout(LeftRightLayout::new(4), "ab", "cd"), "ab");
+        assert_eq!(layout(LeftRightLayout::new(1), "ab", "cd"), "ab");
+    }
+
+    #[test]
+    fn colliding_right_part_is_truncated() {
+        let truncating = LeftRightLayout {
+            overflow: Overflow::Truncate { ellipsis: "…" },
+            ..LeftRightLayout::new(8)
+        };
+        assert_eq!(layout(truncating, "abc", "defgh"), "abc def…");
+        assert_eq!(layout(truncating, "abc", "漢字漢"), "abc  漢…");
+        assert_eq!(layout(truncating, "abcdefg", "hi"), "abcdefg");
+    }
+}
@@ -5,7 +5,9 @@ pub mod styling;
 #[cfg(any(
     feature = "writers",
     feature = "not_styled_writer",
-    feature = "ansi_styled_writer"
+    feature = "ansi_styled_writer",
+    feature = "zsh_styled_writer",
+    feature = "width_counting_writer",
 ))]
 pub mod writers;
 
@@ -13,10 +15,13 @@ pub mod writers;
     feature = "env",
     feature = "env-access_rights",
     feature = "env-command_result",
-    feature = "env-git",
+    feature = "env-git-types",
+    feature = "env-hg",
+    feature = "env-jj",
     feature = "env-path",
     feature = "env-python",
     feature = "env-session",
+    feature = "env-vcs",
 ))]
 pub mod env;
 
@@ -25,6 +30,17 @@ pub mod env;
     feature = "fmt-command_result",
     feature = "fmt-duration",
     feature = "fmt-git",
+    feature = "fmt-hg",
+    feature = "fmt-jj",
     feature = "fmt-path",
 ))]
 pub mod fmt;
+
+#[cfg(feature = "layout")]
+pub mod layout;
+
+#[cfg(feature = "segments")]
+pub mod segments;
+
+#[cfg(feature = "config")]
+pub mod config;
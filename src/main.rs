@@ -0,0 +1,342 @@
+//! Renders a shell prompt described by a [`PromptConfig`] file.
+//!
+//! Run `prompt_utils help` for the usage.
+
+#[cfg(test)]
+mod test;
+
+use prompt_utils::{
+    config::{Context, PromptConfig},
+    env::command_result::ExitCode,
+    layout::{LeftRightLayout, Rendered},
+    styling::{ColorDepth, StyledWrite},
+    writers::{
+        ansi::{ANSIStyledWriter, Shell},
+        zsh::ZshStyledWriter,
+    },
+};
+use std::{env, fmt, io, path::PathBuf, process, str::FromStr, time::Duration};
+
+const USAGE: &str = "\
+Usage:
+    prompt_utils [render] [OPTIONS]
+    prompt_utils init <SHELL>
+    prompt_utils help
+
+Commands:
+    render    Print the prompt (default)
+    init      Print the snippet that sets up SHELL to use this program for its prompt
+    help      Print this message
+
+Options for render:
+    --exit-code <CODE>      The exit code of the last command
+    --duration-ms <MS>      How long the last command took, in milliseconds
+    --shell <SHELL>         The shell that will display the prompt (default: plain)
+    --width <COLUMNS>       The terminal width; when given, the right part of the prompt
+                            is written on the same line, flush to the right edge
+    --config <PATH>         The prompt description file (default: $PROMPT_UTILS_CONFIG,
+                            or prompt_utils/config.toml in the user's config directory)
+    --right                 Print only the right part of the prompt
+
+Shells: bash, zsh, fish, powershell, plain";
+
+/// The prompt used when there is no prompt description file.
+const DEFAULT_CONFIG: &str = r#"
+separator = " "
+suffix = { text = " ❯ ", style = "bold" }
+
+[[segment]]
+kind = "path"
+mode = "short"
+style = "fg:bright-blue"
+
+[[segment]]
+kind = "git"
+style = "fg:bright-magenta"
+
+[[segment]]
+kind = "exit_code"
+
+[right]
+separator = " "
+
+[[right.segment]]
+kind = "duration"
+precision = "seconds"
+threshold_ms = 2000
+style = "fg:bright-yellow"
+"#;
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("init") => match &args[1..] {
+            [shell] => shell.parse().map_err(Error::Usage).and_then(print_init),
+            _ => Err(Error::Usage(String::from(
+                "`init` expects exactly one shell",
+            ))),
+        },
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some("render") => RenderArgs::parse(&args[1..]).and_then(render),
+        _ => RenderArgs::parse(&args).and_then(render),
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(Error::Usage(message)) => {
+            eprintln!("prompt_utils: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+        Err(err) => {
+            eprintln!("prompt_utils: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Usage(String),
+    Config(prompt_utils::config::ConfigError),
+    Io(io::Error),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Config(err) => write!(f, "{}", err),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Plain,
+}
+impl FromStr for ShellKind {
+    type Err = String;
+
+    fn from_str(shell: &str) -> Result<Self, Self::Err> {
+        match shell {
+            "bash" => Ok(ShellKind::Bash),
+            "zsh" => Ok(ShellKind::Zsh),
+            "fish" => Ok(ShellKind::Fish),
+            "powershell" | "pwsh" => Ok(ShellKind::PowerShell),
+            "plain" => Ok(ShellKind::Plain),
+            _ => Err(format!("unknown shell `{}`", shell)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RenderArgs {
+    exit_code: Option<ExitCode>,
+    duration: Option<Duration>,
+    shell: Option<ShellKind>,
+    width: Option<usize>,
+    config: Option<PathBuf>,
+    right: bool,
+}
+impl RenderArgs {
+    fn parse(args: &[String]) -> Result<Self, Error> {
+        fn value<'a, T: FromStr>(
+            args: &mut impl Iterator<Item = &'a String>,
+            option: &str,
+        ) -> Result<T, Error> {
+            let value = args
+                .next()
+                .ok_or_else(|| Error::Usage(format!("`{}` expects a value", option)))?;
+            value
+                .parse()
+                .map_err(|_| Error::Usage(format!("invalid value for `{}`: `{}`", option, value)))
+        }
+
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--exit-code" => parsed.exit_code = Some(ExitCode(value(&mut args, arg)?)),
+                "--duration-ms" => {
+                    parsed.duration = Some(Duration::from_millis(value(&mut args, arg)?))
+                }
+                "--shell" => parsed.shell = Some(value(&mut args, arg)?),
+                "--width" => parsed.width = Some(value(&mut args, arg)?),
+                "--config" => parsed.config = Some(value(&mut args, arg)?),
+                "--right" => parsed.right = true,
+                _ => return Err(Error::Usage(format!("unexpected argument `{}`", arg))),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn load_config(path: Option<PathBuf>) -> Result<PromptConfig, Error> {
+    let path = path
+        .or_else(|| env::var_os("PROMPT_UTILS_CONFIG").map(PathBuf::from))
+        .or_else(|| {
+            let path = dirs::config_dir()?.join("prompt_utils").join("config.toml");
+            path.is_file().then_some(path)
+        });
+    match path {
+        Some(path) => PromptConfig::from_file(&path).map_err(Error::Config),
+        None => PromptConfig::from_toml(DEFAULT_CONFIG).map_err(Error::Config),
+    }
+}
+
+fn render(args: RenderArgs) -> Result<(), Error> {
+    let config = load_config(args.config.clone())?;
+    let context = Context {
+        exit_code: args.exit_code,
+        duration: args.duration,
+        current_dir: env::current_dir().ok(),
+    };
+
+    let stdout = io::stdout();
+    let stdout = io::BufWriter::new(stdout.lock());
+    let result = match args.shell.unwrap_or(ShellKind::Plain) {
+        ShellKind::Zsh => write_prompt(ZshStyledWriter::new(stdout), &config, &context, &args),
+        shell => {
+            let shell = match shell {
+                ShellKind::Bash => Shell::Bash,
+                _ => Shell::Plain,
+            };
+            let mut writer = ANSIStyledWriter::with_shell(stdout, shell);
+            writer.set_color_depth(ColorDepth::detect_from_env());
+            write_prompt(writer, &config, &context, &args)
+        }
+    };
+    result.map_err(Error::Io)
+}
+
+fn write_prompt(
+    mut writer: impl StyledWrite,
+    config: &PromptConfig,
+    context: &Context,
+    args: &RenderArgs,
+) -> io::Result<()> {
+    if args.right {
+        if let Some(right) = config.build_right(context) {
+            right.render(&mut writer)?;
+        }
+    } else {
+        let left = config.build_left(context);
+        match (config.build_right(context), args.width) {
+            (Some(right), Some(width)) => {
+                let left = Rendered::render(|writer| left.render(writer))?;
+                let right = Rendered::render(|writer| right.render(writer))?;
+                LeftRightLayout::new(width).write(&mut writer, &left, &right)?;
+            }
+            _ => left.render(&mut writer)?,
+        }
+    }
+    writer.flush()
+}
+
+fn print_init(shell: ShellKind) -> Result<(), Error> {
+    let exe = env::current_exe().map_err(Error::Io)?;
+    let exe = exe.to_string_lossy();
+    let snippet = match shell {
+        ShellKind::Bash => BASH_INIT.replace("{exe}", &quote_posix(&exe)),
+        ShellKind::Zsh => ZSH_INIT.replace("{exe}", &quote_posix(&exe)),
+        ShellKind::Fish => FISH_INIT.replace("{exe}", &quote_fish(&exe)),
+        ShellKind::PowerShell => POWERSHELL_INIT.replace("{exe}", &quote_powershell(&exe)),
+        ShellKind::Plain => {
+            return Err(Error::Usage(String::from(
+                "there is no init snippet for the plain shell",
+            )))
+        }
+    };
+    print!("{}", snippet);
+    Ok(())
+}
+
+fn quote_posix(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn quote_fish(text: &str) -> String {
+    format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn quote_powershell(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Measures command durations with `EPOCHREALTIME` (bash 5 or later),
+/// recording the start time in a `DEBUG` trap only for the first command after a prompt.
+/// The trap is armed by the last command of `PROMPT_COMMAND`,
+/// so that the commands of an existing `PROMPT_COMMAND` are not taken for the user's.
+const BASH_INIT: &str = r#"__prompt_utils_preexec() {
+    if [ -n "$__prompt_utils_at_prompt" ]; then
+        unset __prompt_utils_at_prompt
+        __prompt_utils_start=${EPOCHREALTIME/[.,]/}
+    fi
+}
+__prompt_utils_precmd() {
+    local exit_code=$?
+    local -a args=(--shell bash --exit-code "$exit_code" --width "${COLUMNS:-80}")
+    if [ -n "$__prompt_utils_start" ] && [ -n "$EPOCHREALTIME" ]; then
+        local now=${EPOCHREALTIME/[.,]/}
+        args+=(--duration-ms "$(( (now - __prompt_utils_start) / 1000 ))")
+    fi
+    unset __prompt_utils_start
+    PS1="$({exe} render "${args[@]}")"
+}
+trap '__prompt_utils_preexec' DEBUG
+PROMPT_COMMAND="__prompt_utils_precmd${PROMPT_COMMAND:+;$PROMPT_COMMAND};__prompt_utils_at_prompt=1"
+"#;
+
+/// Leaves the right part of the prompt to `RPROMPT`.
+const ZSH_INIT: &str = r#"zmodload zsh/datetime zsh/mathfunc
+__prompt_utils_preexec() {
+    __prompt_utils_start=$EPOCHREALTIME
+}
+__prompt_utils_precmd() {
+    local exit_code=$?
+    local -a args=(--shell zsh --exit-code $exit_code)
+    if [[ -n $__prompt_utils_start ]]; then
+        args+=(--duration-ms $(( int((EPOCHREALTIME - __prompt_utils_start) * 1000) )))
+        unset __prompt_utils_start
+    fi
+    PROMPT="$({exe} render $args)"
+    RPROMPT="$({exe} render --right $args)"
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook preexec __prompt_utils_preexec
+add-zsh-hook precmd __prompt_utils_precmd
+"#;
+
+/// Leaves the right part of the prompt to `fish_right_prompt`.
+const FISH_INIT: &str = r#"function fish_prompt
+    set -l exit_code $status
+    {exe} render --shell fish --exit-code $exit_code --duration-ms $CMD_DURATION
+end
+function fish_right_prompt
+    set -l exit_code $status
+    {exe} render --right --shell fish --exit-code $exit_code --duration-ms $CMD_DURATION
+end
+"#;
+
+/// Only reports the duration of a history entry once,
+/// so that it is not repeated when an empty line is entered.
+const POWERSHELL_INIT: &str = r#"function global:prompt {
+    $success = $?
+    $exitCode = if ($success) { 0 } elseif ($global:LASTEXITCODE) { $global:LASTEXITCODE } else { 1 }
+    $arguments = @('render', '--shell', 'powershell', '--exit-code', $exitCode, '--width', $Host.UI.RawUI.WindowSize.Width)
+    $last = Get-History -Count 1
+    if ($last -and $last.Id -ne $global:__prompt_utils_last_id) {
+        $global:__prompt_utils_last_id = $last.Id
+        $duration = $last.EndExecutionTime - $last.StartExecutionTime
+        $arguments += @('--duration-ms', [int64]$duration.TotalMilliseconds)
+    }
+    (& {exe} @arguments) -join "`n"
+}
+"#;
@@ -0,0 +1,231 @@
+//! Composable prompt segments.
+//!
+//! A [`Segment`] is a piece of a prompt that knows how to render itself.
+//! Segments are joined into a [`Prompt`] by a [`PromptBuilder`].
+//!
+//! The submodules provide segments for the [`fmt`][`crate::fmt`] functions.
+
+#[cfg(test)]
+mod test;
+
+#[cfg(feature = "fmt-command_result")]
+pub mod command_result;
+
+#[cfg(feature = "fmt-duration")]
+pub mod duration;
+
+#[cfg(feature = "fmt-git")]
+pub mod git;
+
+#[cfg(feature = "fmt-jj")]
+pub mod jj;
+
+#[cfg(feature = "fmt-path")]
+pub mod path;
+
+#[cfg(feature = "powerline")]
+pub mod powerline;
+
+use crate::styling::{StyleChange, StyledWrite};
+use std::io;
+
+/// A piece of a prompt.
+pub trait Segment {
+    /// Writes the segment.
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()>;
+
+    /// Tells whether the segment has nothing to show and should be skipped.
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+impl<F> Segment for F
+where
+    F: Fn(&mut dyn StyledWrite) -> io::Result<()>,
+{
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        self(writer)
+    }
+}
+
+/// Text with a [style change][`StyleChange`] to be applied while it is written.
+///
+/// As a [`Segment`], it is [empty][`Segment::is_empty`] when the text is empty.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct StyledText {
+    pub text: String,
+    pub style_change: StyleChange,
+}
+impl StyledText {
+    pub fn new(text: impl Into<String>, style_change: StyleChange) -> Self {
+        Self {
+            text: text.into(),
+            style_change,
+        }
+    }
+}
+impl From<&str> for StyledText {
+    fn from(text: &str) -> Self {
+        Self::new(text, StyleChange::KEEP)
+    }
+}
+impl From<String> for StyledText {
+    fn from(text: String) -> Self {
+        Self::new(text, StyleChange::KEEP)
+    }
+}
+impl Segment for StyledText {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        if self.style_change.any() {
+            styled_write!(writer, self.style_change.clone(); "{}", self.text)
+        } else {
+            write!(writer, "{}", self.text)
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// A sequence of [`Segment`]s joined by a separator, with a prefix and a suffix.
+///
+/// Built by a [`PromptBuilder`].
+/// A [`Prompt`] is itself a [`Segment`],
+/// which is [empty][`Segment::is_empty`] when all of its segments are.
+pub struct Prompt {
+    prefix: StyledText,
+    separator: StyledText,
+    suffix: StyledText,
+    entries: Vec<Entry>,
+}
+impl Prompt {
+    /// Writes the prefix, the non-[empty][`Segment::is_empty`] segments
+    /// joined by the separator, and the suffix.
+    ///
+    /// The style change of each segment is reverted after it is written.
+    pub fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        self.prefix.render(writer)?;
+        let mut is_preceded = false;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| !entry.segment.is_empty())
+        {
+            if is_preceded {
+                self.separator.render(writer)?;
+            }
+            if entry.style_change.any() {
+                with_style!(writer, entry.style_change.clone(); entry.segment.render(writer))??;
+            } else {
+                entry.segment.render(writer)?;
+            }
+            is_preceded = true;
+        }
+        self.suffix.render(writer)
+    }
+}
+impl Segment for Prompt {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        Prompt::render(self, writer)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.iter().all(|entry| entry.segment.is_empty())
+    }
+}
+
+struct Entry {
+    segment: Box<dyn Segment>,
+    style_change: StyleChange,
+}
+
+/// Builder of [`Prompt`]s.
+///
+/// # Examples
+///
+/// ```rust
+/// # use prompt_utils::{
+/// #     segments::{PromptBuilder, StyledText},
+/// #     style_change,
+/// #     styling::StyledWrite,
+/// # };
+/// #
+/// let prompt = PromptBuilder::new()
+///     .separator(" ")
+///     .suffix(StyledText::new(" ❯ ", style_change! { bold: true }))
+///     .segment(StyledText::from("~/src"))
+///     .styled_segment(
+///         |writer: &mut dyn StyledWrite| write!(writer, "main"),
+///         style_change! { italic: true },
+///     )
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct PromptBuilder {
+    prefix: StyledText,
+    separator: StyledText,
+    suffix: StyledText,
+    entries: Vec<Entry>,
+}
+impl PromptBuilder {
+    /// Creates a builder with no segments and empty prefix, separator and suffix.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text written before the segments.
+    pub fn prefix(mut self, prefix: impl Into<StyledText>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the text written between consecutive segments.
+    pub fn separator(mut self, separator: impl Into<StyledText>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets the text written after the segments.
+    pub fn suffix(mut self, suffix: impl Into<StyledText>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Appends a segment.
+    pub fn segment(self, segment: impl Segment + 'static) -> Self {
+        self.styled_segment(segment, StyleChange::KEEP)
+    }
+
+    /// Appends a segment to be written with `style_change` applied.
+    pub fn styled_segment(
+        mut self,
+        segment: impl Segment + 'static,
+        style_change: StyleChange,
+    ) -> Self {
+        self.entries.push(Entry {
+            segment: Box::new(segment),
+            style_change,
+        });
+        self
+    }
+
+    /// Appends an already boxed segment to be written with `style_change` applied.
+    pub fn boxed_segment(mut self, segment: Box<dyn Segment>, style_change: StyleChange) -> Self {
+        self.entries.push(Entry {
+            segment,
+            style_change,
+        });
+        self
+    }
+
+    /// Builds the [`Prompt`].
+    pub fn build(self) -> Prompt {
+        Prompt {
+            prefix: self.prefix,
+            separator: self.separator,
+            suffix: self.suffix,
+            entries: self.entries,
+        }
+    }
+}
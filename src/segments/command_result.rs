@@ -0,0 +1,93 @@
+//! [`Segment`]s for results of commands or processes.
+
+use crate::{
+    env::command_result::{CommandResult, ExitCode},
+    fmt::command_result::{
+        write_command_result, write_exit_code_symbol, When, DEFAULT_ERROR_STYLE_CHANGE,
+        DEFAULT_ERROR_SYMBOL, DEFAULT_SUCCESS_STYLE_CHANGE, DEFAULT_SUCCESS_SYMBOL,
+    },
+    segments::Segment,
+    styling::{StyleChange, StyledWrite},
+};
+use std::io;
+
+/// The symbols of a success and failure, and their styles.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbols {
+    pub success_symbol: String,
+    pub success_style_change: StyleChange,
+    pub error_symbol: String,
+    pub error_style_change: StyleChange,
+}
+impl Default for Symbols {
+    /// The symbols and styles used by the `_with_defaults` functions of
+    /// [`fmt::command_result`][`crate::fmt::command_result`].
+    fn default() -> Self {
+        Self {
+            success_symbol: DEFAULT_SUCCESS_SYMBOL.to_string(),
+            success_style_change: DEFAULT_SUCCESS_STYLE_CHANGE,
+            error_symbol: DEFAULT_ERROR_SYMBOL.to_string(),
+            error_style_change: DEFAULT_ERROR_STYLE_CHANGE,
+        }
+    }
+}
+
+/// Segment written by [`write_exit_code_symbol`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExitCodeSegment {
+    pub exit_code: ExitCode,
+    pub symbols: Symbols,
+    pub show_code_when: When,
+}
+impl ExitCodeSegment {
+    /// Creates an [`ExitCodeSegment`] with the [default symbols][`Symbols::default`]
+    /// that shows the exit code [on error][`When::OnError`].
+    pub fn new(exit_code: ExitCode) -> Self {
+        Self {
+            exit_code,
+            symbols: Default::default(),
+            show_code_when: When::OnError,
+        }
+    }
+}
+impl Segment for ExitCodeSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_exit_code_symbol(
+            writer,
+            self.exit_code,
+            &self.symbols.success_symbol,
+            self.symbols.success_style_change.clone(),
+            &self.symbols.error_symbol,
+            self.symbols.error_style_change.clone(),
+            self.show_code_when.clone(),
+        )
+    }
+}
+
+/// Segment written by [`write_command_result`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandResultSegment {
+    pub command_result: CommandResult,
+    pub symbols: Symbols,
+}
+impl CommandResultSegment {
+    /// Creates a [`CommandResultSegment`] with the [default symbols][`Symbols::default`].
+    pub fn new(command_result: CommandResult) -> Self {
+        Self {
+            command_result,
+            symbols: Default::default(),
+        }
+    }
+}
+impl Segment for CommandResultSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_command_result(
+            writer,
+            self.command_result,
+            &self.symbols.success_symbol,
+            self.symbols.success_style_change.clone(),
+            &self.symbols.error_symbol,
+            self.symbols.error_style_change.clone(),
+        )
+    }
+}
@@ -0,0 +1,116 @@
+//! [`Segment`]s for durations.
+
+use crate::{
+    fmt::duration::{
+        write_all, write_nonzero, write_skip_high_and_low_zeros, write_skip_high_zeros,
+        write_skip_low_zeros, HumanDuration,
+    },
+    segments::Segment,
+    styling::StyledWrite,
+};
+use std::{io, time::Duration};
+
+/// Which of the [`fmt::duration`][`crate::fmt::duration`] functions writes a duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DurationFormat {
+    /// [`write_all`]
+    All,
+    /// [`write_nonzero`]
+    Nonzero,
+    /// [`write_skip_high_zeros`]
+    SkipHighZeros,
+    /// [`write_skip_low_zeros`]
+    SkipLowZeros,
+    /// [`write_skip_high_and_low_zeros`]
+    SkipHighAndLowZeros,
+}
+impl DurationFormat {
+    /// Writes `duration` in this format.
+    pub fn write(
+        &self,
+        writer: &mut (impl io::Write + ?Sized),
+        duration: HumanDuration,
+    ) -> io::Result<()> {
+        match self {
+            DurationFormat::All => write_all(writer, duration),
+            DurationFormat::Nonzero => write_nonzero(writer, duration),
+            DurationFormat::SkipHighZeros => write_skip_high_zeros(writer, duration),
+            DurationFormat::SkipLowZeros => write_skip_low_zeros(writer, duration),
+            DurationFormat::SkipHighAndLowZeros => write_skip_high_and_low_zeros(writer, duration),
+        }
+    }
+}
+
+/// The smallest unit of a written duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Precision {
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+impl Precision {
+    /// The truncation of `duration` to this precision.
+    pub fn truncate(&self, duration: HumanDuration) -> HumanDuration {
+        match self {
+            Precision::Days => duration.truncated_to_days(),
+            Precision::Hours => duration.truncated_to_hours(),
+            Precision::Minutes => duration.truncated_to_minutes(),
+            Precision::Seconds => duration.truncated_to_seconds(),
+            Precision::Milliseconds => duration.truncated_to_milliseconds(),
+            Precision::Microseconds => duration.truncated_to_microseconds(),
+            Precision::Nanoseconds => duration,
+        }
+    }
+}
+
+/// Segment showing a duration, such as how long the last command took.
+///
+/// It is [empty][`Segment::is_empty`] when the duration is shorter than [`Self::threshold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DurationSegment {
+    pub duration: Duration,
+    pub format: DurationFormat,
+    pub precision: Precision,
+    pub threshold: Duration,
+}
+impl DurationSegment {
+    /// Creates a [`DurationSegment`] written in the [`SkipHighAndLowZeros`] format,
+    /// with [milliseconds precision][`Precision::Milliseconds`] and no threshold.
+    ///
+    /// [`SkipHighAndLowZeros`]: `DurationFormat::SkipHighAndLowZeros`
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            format: DurationFormat::SkipHighAndLowZeros,
+            precision: Precision::Milliseconds,
+            threshold: Duration::from_secs(0),
+        }
+    }
+}
+impl Segment for DurationSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        self.format.write(
+            writer,
+            self.precision
+                .truncate(HumanDuration::from_duration(self.duration)),
+        )
+    }
+
+    fn is_empty(&self) -> bool {
+        self.duration < self.threshold
+    }
+}
@@ -0,0 +1,144 @@
+//! [`Segment`]s for information about a [git] repository.
+//!
+//! [git]: https://git-scm.com/
+
+use crate::{
+    env::git::{
+        AheadBehind, ChangeSummary, CommitDescription, DiffStats, Head, Operation, StatusSummary,
+    },
+    fmt::git::{
+        write_ahead_behind, write_change_summary, write_commit_description, write_diff_stats,
+        write_head, write_operation, write_status_summary,
+    },
+    segments::Segment,
+    styling::StyledWrite,
+};
+use std::io;
+
+/// Segment written by [`write_head`].
+///
+/// When the head is a [`Commit`][`Head::Commit`] and a
+/// [`commit_description`][`Self::commit_description`] is present,
+/// it is written by [`write_commit_description`] instead.
+#[derive(Debug, PartialEq)]
+pub struct HeadSegment {
+    pub head: Head,
+    pub commit_description: Option<CommitDescription>,
+}
+impl HeadSegment {
+    pub fn new(head: Head) -> Self {
+        Self {
+            head,
+            commit_description: None,
+        }
+    }
+}
+impl Segment for HeadSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        match (&self.head, &self.commit_description) {
+            (Head::Commit(_), Some(description)) => write_commit_description(writer, description),
+            _ => write_head(writer, &self.head),
+        }
+    }
+}
+
+/// Segment written by [`write_operation`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OperationSegment {
+    pub operation: Operation,
+}
+impl OperationSegment {
+    pub fn new(operation: Operation) -> Self {
+        Self { operation }
+    }
+}
+impl Segment for OperationSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_operation(writer, &self.operation)
+    }
+}
+
+/// Segment written by [`write_ahead_behind`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AheadBehindSegment {
+    pub ahead_behind: AheadBehind,
+}
+impl AheadBehindSegment {
+    pub fn new(ahead_behind: AheadBehind) -> Self {
+        Self { ahead_behind }
+    }
+}
+impl Segment for AheadBehindSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_ahead_behind(writer, &self.ahead_behind)
+    }
+}
+
+/// Segment written by [`write_change_summary`].
+///
+/// It is [empty][`Segment::is_empty`] when the change summary
+/// [has no changes][`ChangeSummary::any_changes`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChangeSummarySegment {
+    pub changes: ChangeSummary,
+}
+impl ChangeSummarySegment {
+    pub fn new(changes: ChangeSummary) -> Self {
+        Self { changes }
+    }
+}
+impl Segment for ChangeSummarySegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_change_summary(writer, &self.changes)
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.changes.any_changes()
+    }
+}
+
+/// Segment written by [`write_status_summary`].
+///
+/// It is [empty][`Segment::is_empty`] when the status summary
+/// [has no changes][`StatusSummary::any_changes`] and is not [incomplete][`StatusSummary::incomplete`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatusSummarySegment {
+    pub status: StatusSummary,
+}
+impl StatusSummarySegment {
+    pub fn new(status: StatusSummary) -> Self {
+        Self { status }
+    }
+}
+impl Segment for StatusSummarySegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_status_summary(writer, &self.status)
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.status.any_changes() && !self.status.incomplete
+    }
+}
+
+/// Segment written by [`write_diff_stats`].
+///
+/// It is [empty][`Segment::is_empty`] when the diff stats
+/// [have no changes][`DiffStats::any_changes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiffStatsSegment {
+    pub stats: DiffStats,
+}
+impl DiffStatsSegment {
+    pub fn new(stats: DiffStats) -> Self {
+        Self { stats }
+    }
+}
+impl Segment for DiffStatsSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_diff_stats(writer, &self.stats)
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.stats.any_changes()
+    }
+}
@@ -0,0 +1,24 @@
+//! [`Segment`]s for information about a [Jujutsu] workspace.
+//!
+//! [Jujutsu]: https://jj-vcs.github.io/jj/
+
+use crate::{
+    env::jj::WorkingCopy, fmt::jj::write_working_copy, segments::Segment, styling::StyledWrite,
+};
+use std::io;
+
+/// Segment written by [`write_working_copy`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WorkingCopySegment {
+    pub working_copy: WorkingCopy,
+}
+impl WorkingCopySegment {
+    pub fn new(working_copy: WorkingCopy) -> Self {
+        Self { working_copy }
+    }
+}
+impl Segment for WorkingCopySegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        write_working_copy(writer, &self.working_copy)
+    }
+}
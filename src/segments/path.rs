@@ -0,0 +1,81 @@
+//! [`Segment`]s for [`Path`][`std::path::Path`]s.
+
+use crate::{
+    fmt::path::{write_full, write_short, write_with_middle_hidden},
+    segments::Segment,
+    styling::StyledWrite,
+};
+use std::{
+    io,
+    path::{self, PathBuf},
+};
+
+/// Which of the [`fmt::path`][`crate::fmt::path`] functions writes a path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathFormat {
+    /// [`write_full`]
+    Full,
+    /// [`write_short`]
+    Short { replacement: String },
+    /// [`write_with_middle_hidden`]
+    MiddleHidden { replacement: String },
+}
+
+/// Segment showing a path, such as the current directory.
+///
+/// See [`fmt::path`][`crate::fmt::path`] for the meaning of the fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathSegment {
+    pub path: PathBuf,
+    pub format: PathFormat,
+    pub separator: String,
+    pub root_separator: String,
+    pub root_dir_override: Option<String>,
+}
+impl PathSegment {
+    /// Creates a [`PathSegment`] in the [full format][`PathFormat::Full`],
+    /// using the [platform's separator][`path::MAIN_SEPARATOR`] and no root separator.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: PathFormat::Full,
+            separator: path::MAIN_SEPARATOR.to_string(),
+            root_separator: String::new(),
+            root_dir_override: None,
+        }
+    }
+}
+impl Segment for PathSegment {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        let root_dir_override = self.root_dir_override.as_deref();
+        match &self.format {
+            PathFormat::Full => write_full(
+                writer,
+                &self.path,
+                &self.separator,
+                &self.root_separator,
+                root_dir_override,
+            ),
+            PathFormat::Short { replacement } => write_short(
+                writer,
+                &self.path,
+                &self.separator,
+                &self.root_separator,
+                root_dir_override,
+                replacement,
+            ),
+            PathFormat::MiddleHidden { replacement } => write_with_middle_hidden(
+                writer,
+                &self.path,
+                &self.separator,
+                &self.root_separator,
+                root_dir_override,
+                replacement,
+            ),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.path.as_os_str().is_empty()
+    }
+}
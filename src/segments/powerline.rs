@@ -0,0 +1,298 @@
+//! [Powerline]-style rendering of [`Segment`]s.
+//!
+//! [Powerline]: https://github.com/powerline/powerline
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    segments::Segment,
+    styling::{Change, Color, Style, StyleChange, StyledWrite},
+};
+use std::{borrow::Cow, io};
+
+/// The direction in which a [`Powerline`]'s segments are chained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The separators point right, as in a left prompt.
+    LeftToRight,
+    /// The separators point left, as in a right prompt.
+    RightToLeft,
+}
+
+/// The glyphs written between the segments of a [`Powerline`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Separators {
+    /// Written between segments of different backgrounds.
+    ///
+    /// Its foreground is the background of the segment before it
+    /// and its background is the background of the segment after it
+    /// (the other way around when chaining [right to left][`Direction::RightToLeft`]).
+    pub hard: Cow<'static, str>,
+    /// Written between segments sharing a background,
+    /// in the colors of the segment it points away from.
+    pub thin: Cow<'static, str>,
+}
+impl Separators {
+    /// The Powerline glyphs pointing right, `` and ``.
+    ///
+    /// They are only displayed correctly with a font patched for Powerline.
+    pub const POWERLINE_LEFT_TO_RIGHT: Self = Self {
+        hard: Cow::Borrowed("\u{E0B0}"),
+        thin: Cow::Borrowed("\u{E0B1}"),
+    };
+
+    /// The Powerline glyphs pointing left, `` and ``.
+    ///
+    /// They are only displayed correctly with a font patched for Powerline.
+    pub const POWERLINE_RIGHT_TO_LEFT: Self = Self {
+        hard: Cow::Borrowed("\u{E0B2}"),
+        thin: Cow::Borrowed("\u{E0B3}"),
+    };
+
+    /// ASCII fallback pointing right, `>` and `|`.
+    pub const ASCII_LEFT_TO_RIGHT: Self = Self {
+        hard: Cow::Borrowed(">"),
+        thin: Cow::Borrowed("|"),
+    };
+
+    /// ASCII fallback pointing left, `<` and `|`.
+    pub const ASCII_RIGHT_TO_LEFT: Self = Self {
+        hard: Cow::Borrowed("<"),
+        thin: Cow::Borrowed("|"),
+    };
+
+    /// The Powerline glyphs pointing in `direction`.
+    pub const fn powerline(direction: Direction) -> Self {
+        match direction {
+            Direction::LeftToRight => Self::POWERLINE_LEFT_TO_RIGHT,
+            Direction::RightToLeft => Self::POWERLINE_RIGHT_TO_LEFT,
+        }
+    }
+
+    /// The ASCII fallback pointing in `direction`.
+    pub const fn ascii(direction: Direction) -> Self {
+        match direction {
+            Direction::LeftToRight => Self::ASCII_LEFT_TO_RIGHT,
+            Direction::RightToLeft => Self::ASCII_RIGHT_TO_LEFT,
+        }
+    }
+}
+
+/// A chain of [`Segment`]s, each with its own colors, joined by [`Separators`].
+///
+/// The separators take their colors from the adjacent segments.
+/// The first (when chaining [right to left][`Direction::RightToLeft`])
+/// or last (when chaining [left to right][`Direction::LeftToRight`])
+/// separator blends into the background the writer had before the chain was written.
+/// That style is restored after the chain is written.
+///
+/// [Empty][`Segment::is_empty`] segments are skipped,
+/// and the [`Powerline`] is itself an [empty][`Segment::is_empty`] [`Segment`]
+/// when all of its segments are.
+///
+/// # Examples
+///
+/// ```rust
+/// # use prompt_utils::{
+/// #     segments::{powerline::{Direction, Powerline}, StyledText},
+/// #     styling::{Color, Color4Bit},
+/// # };
+/// #
+/// let powerline = Powerline::new(Direction::LeftToRight)
+///     .segment(
+///         StyledText::from("~/src"),
+///         Color::Color4Bit(Color4Bit::WHITE),
+///         Color::Color4Bit(Color4Bit::DARK_BLUE),
+///     )
+///     .segment(
+///         StyledText::from("main"),
+///         Color::Color4Bit(Color4Bit::BLACK),
+///         Color::Color4Bit(Color4Bit::DARK_GREEN),
+///     );
+/// ```
+pub struct Powerline {
+    direction: Direction,
+    separators: Separators,
+    padding: Cow<'static, str>,
+    entries: Vec<Entry>,
+}
+impl Powerline {
+    /// Creates a [`Powerline`] chained in `direction`,
+    /// using the [Powerline glyphs][`Separators::powerline`]
+    /// and padding each segment with a space on each side.
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            separators: Separators::powerline(direction),
+            padding: Cow::Borrowed(" "),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets the separators.
+    pub fn separators(mut self, separators: Separators) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Sets the text written on each side of each segment, in the segment's colors.
+    pub fn padding(mut self, padding: impl Into<Cow<'static, str>>) -> Self {
+        self.padding = padding.into();
+        self
+    }
+
+    /// Appends a segment written with the given colors.
+    pub fn segment(
+        self,
+        segment: impl Segment + 'static,
+        foreground: Color,
+        background: Color,
+    ) -> Self {
+        self.boxed_segment(Box::new(segment), foreground, background)
+    }
+
+    /// Appends an already boxed segment written with the given colors.
+    pub fn boxed_segment(
+        mut self,
+        segment: Box<dyn Segment>,
+        foreground: Color,
+        background: Color,
+    ) -> Self {
+        self.entries.push(Entry {
+            segment,
+            foreground,
+            background,
+        });
+        self
+    }
+
+    /// Writes the segments and the separators between them.
+    pub fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        let base_style = writer.style().clone();
+        let base_background = base_style.background;
+
+        let entries = self
+            .entries
+            .iter()
+            .filter(|entry| !entry.segment.is_empty())
+            .collect::<Vec<_>>();
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Ok(()),
+        };
+
+        if self.direction == Direction::RightToLeft {
+            self.write_hard_separator(writer, base_background, first.background)?;
+        }
+
+        for (index, entry) in entries.iter().enumerate() {
+            if index > 0 {
+                let previous = entries[index - 1];
+                if previous.background == entry.background {
+                    let colors_from = match self.direction {
+                        Direction::LeftToRight => previous,
+                        Direction::RightToLeft => entry,
+                    };
+                    set_colors(writer, colors_from.foreground, colors_from.background)?;
+                    write!(writer, "{}", self.separators.thin)?;
+                } else {
+                    self.write_hard_separator(writer, previous.background, entry.background)?;
+                }
+            }
+
+            set_colors(writer, entry.foreground, entry.background)?;
+            write!(writer, "{}", self.padding)?;
+            entry.segment.render(writer)?;
+            set_colors(writer, entry.foreground, entry.background)?;
+            write!(writer, "{}", self.padding)?;
+        }
+
+        if self.direction == Direction::LeftToRight {
+            self.write_hard_separator(writer, last.background, base_background)?;
+        }
+
+        change_style_to(writer, &base_style)
+    }
+
+    /// Writes the hard separator between a segment with background `from`
+    /// and the following segment with background `to`.
+    ///
+    /// The left-to-right glyph is drawn in the color of `from` over `to`,
+    /// while the right-to-left glyph is drawn in the color of `to` over `from`.
+    fn write_hard_separator(
+        &self,
+        writer: &mut dyn StyledWrite,
+        from: Color,
+        to: Color,
+    ) -> io::Result<()> {
+        let style = Style {
+            foreground: from,
+            background: to,
+            ..writer.style().clone()
+        };
+        let style = match self.direction {
+            Direction::LeftToRight => style,
+            // The glyph points the other way, so it is drawn with the colors swapped.
+            Direction::RightToLeft => style.colors_swapped(),
+        };
+        change_style_to(writer, &style)?;
+        write!(writer, "{}", self.separators.hard)
+    }
+}
+impl Segment for Powerline {
+    fn render(&self, writer: &mut dyn StyledWrite) -> io::Result<()> {
+        Powerline::render(self, writer)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.iter().all(|entry| entry.segment.is_empty())
+    }
+}
+
+struct Entry {
+    segment: Box<dyn Segment>,
+    foreground: Color,
+    background: Color,
+}
+
+fn set_colors(
+    writer: &mut dyn StyledWrite,
+    foreground: Color,
+    background: Color,
+) -> io::Result<()> {
+    let style = Style {
+        foreground,
+        background,
+        ..writer.style().clone()
+    };
+    change_style_to(writer, &style)
+}
+
+/// Changes the style of `writer` to `style`, only changing the attributes that differ.
+fn change_style_to(writer: &mut dyn StyledWrite, style: &Style) -> io::Result<()> {
+    fn change<T: PartialEq>(from: T, to: T) -> Change<T> {
+        if from == to {
+            Change::Keep
+        } else {
+            Change::SetTo(to)
+        }
+    }
+
+    let current = writer.style();
+    let style_change = StyleChange {
+        foreground: change(current.foreground, style.foreground),
+        background: change(current.background, style.background),
+        bold: change(current.bold, style.bold),
+        dim: change(current.dim, style.dim),
+        underline: change(current.underline, style.underline),
+        italic: change(current.italic, style.italic),
+        blink: change(current.blink, style.blink),
+        strike: change(current.strike, style.strike),
+    };
+    if style_change.any() {
+        writer.change_style(style_change)
+    } else {
+        Ok(())
+    }
+}
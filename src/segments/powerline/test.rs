@@ -0,0 +1,94 @@
+#[cfg(all(test, feature = "ansi_styled_writer"))]
+mod powerline {
+    use crate::{
+        segments::{
+            powerline::{Direction, Powerline, Separators},
+            StyledText,
+        },
+        styling::{Color, Style, StyledWrite},
+        writers::ansi::ANSIStyledWriter,
+    };
+
+    fn render(powerline: Powerline) -> String {
+        let mut writer = ANSIStyledWriter::new(Vec::new());
+        powerline.render(&mut writer).unwrap();
+        assert_eq!(writer.style(), &Style::default());
+        String::from_utf8(writer.into_inner())
+            .unwrap()
+            .replace('\x1B', "^")
+    }
+
+    fn segments(direction: Direction) -> Powerline {
+        Powerline::new(direction)
+            .separators(Separators::ascii(direction))
+            .segment(StyledText::from("a"), Color::ANSI256(1), Color::ANSI256(2))
+            .segment(StyledText::from("b"), Color::ANSI256(3), Color::ANSI256(2))
+            .segment(StyledText::from(""), Color::ANSI256(5), Color::ANSI256(6))
+            .segment(StyledText::from("c"), Color::ANSI256(7), Color::ANSI256(8))
+    }
+
+    #[test]
+    fn left_to_right_separators_take_colors_from_adjacent_segments() {
+        assert_eq!(
+            render(segments(Direction::LeftToRight)),
+            concat!(
+                "^[38;5;1;48;5;2m a ",
+                "|",
+                "^[38;5;3m b ",
+                "^[38;5;2;48;5;8m>",
+                "^[38;5;7m c ",
+                "^[38;5;8;49m>",
+                "^[39m",
+            ),
+        );
+    }
+
+    #[test]
+    fn right_to_left_separators_take_colors_from_adjacent_segments() {
+        assert_eq!(
+            render(segments(Direction::RightToLeft)),
+            concat!(
+                "^[38;5;2m<",
+                "^[38;5;1;48;5;2m a ",
+                "^[38;5;3m|",
+                " b ",
+                "^[38;5;8m<",
+                "^[38;5;7;48;5;8m c ",
+                "^[39;49m",
+            ),
+        );
+    }
+
+    #[test]
+    fn restores_only_the_changed_colors() {
+        let base_style = Style {
+            background: Color::ANSI256(8),
+            bold: true,
+            ..Style::default()
+        };
+        let mut writer = ANSIStyledWriter::new(Vec::new());
+        writer
+            .change_style(style_change! { background: Color::ANSI256(8), bold: true })
+            .unwrap();
+        segments(Direction::LeftToRight)
+            .render(&mut writer)
+            .unwrap();
+        assert_eq!(writer.style(), &base_style);
+        assert_eq!(
+            String::from_utf8(writer.into_inner())
+                .unwrap()
+                .replace('\x1B', "^"),
+            concat!(
+                "^[22;1;48;5;8m",
+                "^[38;5;1;48;5;2m a ",
+                "|",
+                "^[38;5;3m b ",
+                "^[38;5;2;48;5;8m>",
+                "^[38;5;7m c ",
+                // The last segment's background is the one before the chain.
+                "^[38;5;8m>",
+                "^[39m",
+            ),
+        );
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(feature = "ansi_styled_writer")]
+mod prompt {
+    use crate::{
+        segments::{Prompt, PromptBuilder, Segment, StyledText},
+        styling::{Style, StyledWrite},
+        writers::ansi::ANSIStyledWriter,
+    };
+
+    fn render(prompt: &Prompt) -> String {
+        let mut writer = ANSIStyledWriter::new(Vec::new());
+        prompt.render(&mut writer).unwrap();
+        assert_eq!(writer.style(), &Style::default());
+        String::from_utf8(writer.into_inner())
+            .unwrap()
+            .replace('\x1B', "^")
+    }
+
+    #[test]
+    fn separates_the_non_empty_segments() {
+        let prompt = PromptBuilder::new()
+            .prefix("(")
+            .separator(StyledText::new("|", style_change! { bold: true }))
+            .suffix(")")
+            .segment(StyledText::from(""))
+            .segment(StyledText::from("a"))
+            .segment(StyledText::from(""))
+            .styled_segment(
+                |writer: &mut dyn StyledWrite| write!(writer, "b"),
+                style_change! { italic: true },
+            )
+            .segment(StyledText::from("c"))
+            .segment(StyledText::from(""))
+            .build();
+        assert!(!prompt.is_empty());
+        assert_eq!(render(&prompt), "(a^[22;1m|^[22m^[3mb^[23m^[22;1m|^[22mc)");
+    }
+
+    #[test]
+    fn writes_only_the_prefix_and_suffix_without_segments() {
+        let prompt = PromptBuilder::new()
+            .prefix("(")
+            .separator("|")
+            .suffix(")")
+            .segment(StyledText::from(""))
+            .segment(StyledText::from(""))
+            .build();
+        assert!(prompt.is_empty());
+        assert_eq!(render(&prompt), "()");
+
+        let prompt = PromptBuilder::new().separator("|").build();
+        assert!(prompt.is_empty());
+        assert_eq!(render(&prompt), "");
+    }
+}
+
+#[cfg(all(feature = "fmt-git", feature = "ansi_styled_writer"))]
+mod git {
+    use crate::{
+        env::git::{AheadBehind, ChangeSummary},
+        segments::{
+            git::{AheadBehindSegment, ChangeSummarySegment},
+            PromptBuilder, Segment,
+        },
+        writers::ansi::ANSIStyledWriter,
+    };
+
+    #[test]
+    fn writes_ahead_behind_and_change_summaries() {
+        let changes = ChangeSummary {
+            added: 1,
+            modified: 2,
+            deleted: 0,
+        };
+        assert!(ChangeSummarySegment::default().is_empty());
+        assert!(!ChangeSummarySegment::new(changes).is_empty());
+        assert!(!AheadBehindSegment::default().is_empty());
+
+        let prompt = PromptBuilder::new()
+            .separator(" ")
+            .segment(AheadBehindSegment::new(AheadBehind {
+                ahead: 1,
+                behind: 2,
+            }))
+            .segment(ChangeSummarySegment::default())
+            .segment(ChangeSummarySegment::new(changes))
+            .segment(AheadBehindSegment::default())
+            .build();
+        let mut writer = ANSIStyledWriter::new(Vec::new());
+        prompt.render(&mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "↑1 ↓2 +1 ~2 ≡"
+        );
+    }
+}
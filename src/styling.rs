@@ -1,5 +1,11 @@
 //! Interfaces for writing styled text.
 
+mod color_depth;
+mod parse;
+
+pub use color_depth::ColorDepth;
+pub use parse::{ParseColorError, ParseStyleError, ParseStyleErrorKind};
+
 use bitflags::bitflags;
 use std::io;
 
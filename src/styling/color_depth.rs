@@ -0,0 +1,198 @@
+//! Reduction of [`Color`]s to what a terminal can display.
+
+#[cfg(test)]
+mod test;
+
+use super::{Color, Color4Bit};
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    ops,
+};
+
+/// The colors a terminal is capable of displaying.
+///
+/// The variants are ordered from the least to the most capable.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// No colors at all.
+    None,
+    /// Only [`Color::Color4Bit`].
+    ANSI16,
+    /// [`Color::Color4Bit`] and [`Color::ANSI256`].
+    ANSI256,
+    /// Every [`Color`].
+    #[default]
+    TrueColor,
+}
+impl ColorDepth {
+    /// Guesses the [`ColorDepth`] of the terminal
+    /// from an arbitrary set of environment variables.
+    ///
+    /// A non-empty [`NO_COLOR`] disables colors.
+    /// Otherwise, a `COLORTERM` of `truecolor` or `24bit` tells that the terminal has true colors,
+    /// and then `TERM` is looked at:
+    /// `dumb` has no colors, names containing `direct` or `truecolor` have true colors,
+    /// names containing `256color` have 256 colors and anything else has 16 colors.
+    ///
+    /// Calling `get_env_var` with an environment variable name
+    /// must return that variable's value, or [`None`] if it does not exist.
+    ///
+    /// [`NO_COLOR`]: https://no-color.org
+    pub fn detect<T, S>(get_env_var: impl Fn(&str) -> Option<T>) -> Self
+    where
+        T: ops::Deref<Target = S>,
+        S: AsRef<OsStr> + ?Sized,
+    {
+        let var = |key| {
+            get_env_var(key).map(|value| value.as_ref().to_string_lossy().to_ascii_lowercase())
+        };
+
+        if var("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            return ColorDepth::None;
+        }
+        if matches!(
+            var("COLORTERM").as_deref(),
+            Some("truecolor") | Some("24bit")
+        ) {
+            return ColorDepth::TrueColor;
+        }
+        match var("TERM") {
+            Some(term) if term == "dumb" => ColorDepth::None,
+            Some(term) if term.contains("direct") || term.contains("truecolor") => {
+                ColorDepth::TrueColor
+            }
+            Some(term) if term.contains("256color") => ColorDepth::ANSI256,
+            _ => ColorDepth::ANSI16,
+        }
+    }
+
+    /// Guesses the [`ColorDepth`] of the terminal
+    /// from the [environment variables of the current process](`std::env::var_os`).
+    ///
+    /// See [`ColorDepth::detect`] for the rules.
+    pub fn detect_from_env() -> Self {
+        Self::detect(|key| {
+            env::var_os(key)
+                .map(OsString::into_string)
+                .and_then(Result::ok)
+        })
+    }
+}
+
+impl Color {
+    /// The closest color to `self` that can be displayed with `depth`.
+    ///
+    /// [`Color::RGB`]s are quantized to the nearest entry of the 256 colors palette
+    /// (excluding the 16 colors, which depend on the terminal's theme)
+    /// or to the nearest [`Color4Bit`] (as displayed by xterm),
+    /// with a [perceptual distance][redmean] between colors.
+    /// With [`ColorDepth::None`], every color becomes [`Color::Unset`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use prompt_utils::styling::{Color, Color4Bit, ColorDepth};
+    /// #
+    /// let orange = Color::RGB(0xff, 0x88, 0x00);
+    /// assert_eq!(orange.downgraded(ColorDepth::TrueColor), orange);
+    /// assert_eq!(orange.downgraded(ColorDepth::ANSI256), Color::ANSI256(208));
+    /// assert_eq!(
+    ///     orange.downgraded(ColorDepth::ANSI16),
+    ///     Color::Color4Bit(Color4Bit::DARK_YELLOW),
+    /// );
+    /// assert_eq!(orange.downgraded(ColorDepth::None), Color::Unset);
+    /// ```
+    ///
+    /// [redmean]: https://www.compuphase.com/cmetric.htm
+    pub fn downgraded(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (_, ColorDepth::TrueColor) | (Color::Unset, _) => self,
+            (_, ColorDepth::None) => Color::Unset,
+            (Color::Color4Bit(_), _) | (Color::ANSI256(_), ColorDepth::ANSI256) => self,
+            (Color::RGB(r, g, b), ColorDepth::ANSI256) => Color::ANSI256(nearest(
+                (16..=255).map(|index| (index, ansi_256_to_rgb(index))),
+                (r, g, b),
+            )),
+            (Color::ANSI256(index), ColorDepth::ANSI16) if index < 16 => {
+                Color::Color4Bit(Color4Bit::from_bits_truncate(index))
+            }
+            (Color::ANSI256(index), ColorDepth::ANSI16) => {
+                Color::Color4Bit(nearest_4_bit(ansi_256_to_rgb(index)))
+            }
+            (Color::RGB(r, g, b), ColorDepth::ANSI16) => Color::Color4Bit(nearest_4_bit((r, g, b))),
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+/// The colors of the [`Color4Bit`]s in xterm's default palette, by their codes.
+const XTERM_16_COLORS: [Rgb; 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The component values of the 6×6×6 color cube of the 256 colors palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi_256_to_rgb(index: u8) -> Rgb {
+    match index {
+        0..=15 => XTERM_16_COLORS[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_4_bit(color: Rgb) -> Color4Bit {
+    let code = nearest(
+        (0..16).map(|code| (code, XTERM_16_COLORS[code as usize])),
+        color,
+    );
+    Color4Bit::from_bits_truncate(code)
+}
+
+/// The key of the color in `candidates` with the smallest perceptual distance to `color`.
+fn nearest(candidates: impl Iterator<Item = (u8, Rgb)>, color: Rgb) -> u8 {
+    candidates
+        .min_by_key(|&(_, candidate)| redmean_distance(candidate, color))
+        .map(|(key, _)| key)
+        .unwrap_or_default()
+}
+
+/// A weighted euclidean distance that approximates the perceived difference between colors,
+/// squared and scaled by 256.
+///
+/// See <https://www.compuphase.com/cmetric.htm>.
+fn redmean_distance((r1, g1, b1): Rgb, (r2, g2, b2): Rgb) -> u32 {
+    let red_mean = (u32::from(r1) + u32::from(r2)) / 2;
+    let dr = i32::from(r1) - i32::from(r2);
+    let dg = i32::from(g1) - i32::from(g2);
+    let db = i32::from(b1) - i32::from(b2);
+    let (dr2, dg2, db2) = ((dr * dr) as u32, (dg * dg) as u32, (db * db) as u32);
+    (512 + red_mean) * dr2 + 1024 * dg2 + (767 - red_mean) * db2
+}
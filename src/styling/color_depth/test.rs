@@ -0,0 +1,70 @@
+mod detect {
+    use crate::styling::ColorDepth;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        let vars = vars.iter().copied().collect::<HashMap<_, _>>();
+        ColorDepth::detect(|key| vars.get(key).copied())
+    }
+
+    #[test]
+    fn from_env_vars() {
+        assert_eq!(detect(&[]), ColorDepth::ANSI16);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::ANSI16);
+        assert_eq!(detect(&[("TERM", "tmux-256color")]), ColorDepth::ANSI256);
+        assert_eq!(detect(&[("TERM", "xterm-direct")]), ColorDepth::TrueColor);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::None);
+        assert_eq!(
+            detect(&[("TERM", "screen"), ("COLORTERM", "truecolor")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            detect(&[("COLORTERM", "24bit"), ("NO_COLOR", "1")]),
+            ColorDepth::None
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]),
+            ColorDepth::ANSI256
+        );
+    }
+}
+
+mod downgraded {
+    use crate::styling::{Color, Color4Bit, ColorDepth};
+
+    #[test]
+    fn palette_colors_map_to_themselves() {
+        for index in 16..=255 {
+            let (r, g, b) = crate::styling::color_depth::ansi_256_to_rgb(index);
+            assert_eq!(
+                Color::RGB(r, g, b).downgraded(ColorDepth::ANSI256),
+                Color::ANSI256(index),
+            );
+        }
+    }
+
+    #[test]
+    fn to_16_colors() {
+        let downgraded = |color: Color| color.downgraded(ColorDepth::ANSI16);
+        assert_eq!(
+            downgraded(Color::RGB(250, 10, 10)),
+            Color::Color4Bit(Color4Bit::BRIGHT_RED)
+        );
+        assert_eq!(
+            downgraded(Color::RGB(10, 10, 120)),
+            Color::Color4Bit(Color4Bit::DARK_BLUE)
+        );
+        assert_eq!(
+            downgraded(Color::ANSI256(9)),
+            Color::Color4Bit(Color4Bit::BRIGHT_RED)
+        );
+        assert_eq!(
+            downgraded(Color::ANSI256(231)),
+            Color::Color4Bit(Color4Bit::WHITE)
+        );
+        assert_eq!(
+            downgraded(Color::Color4Bit(Color4Bit::DARK_CYAN)),
+            Color::Color4Bit(Color4Bit::DARK_CYAN)
+        );
+    }
+}
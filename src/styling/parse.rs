@@ -0,0 +1,331 @@
+//! [`FromStr`] implementations for [`Color`] and [`StyleChange`].
+
+#[cfg(test)]
+mod test;
+
+use super::{Change, Color, Color4Bit, StyleChange};
+use std::{error::Error, fmt, str::FromStr};
+
+/// Parses a color from one of the following forms:
+///
+/// - a name, such as `bright-green`, `dark_red` or `Magenta`,
+///   matched case-insensitively and ignoring `-`, `_` and spaces.
+///   The names are those of the [`Color4Bit`] constants,
+///   with `red`, `green`, `yellow`, `blue`, `magenta`, `cyan` and `gray`
+///   standing for their dark variants, `grey` for `gray` and `bright-black` for `bright-gray`;
+/// - `unset`, `default` or `none`, for [`Color::Unset`];
+/// - `#rrggbb` or `#rgb`, for [`Color::RGB`];
+/// - `rgb(r, g, b)`, with decimal components, for [`Color::RGB`];
+/// - an index from `0` to `255`, for [`Color::ANSI256`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use prompt_utils::styling::{Color, Color4Bit};
+/// #
+/// assert_eq!("bright-green".parse(), Ok(Color::Color4Bit(Color4Bit::BRIGHT_GREEN)));
+/// assert_eq!("#f80".parse(), Ok(Color::RGB(0xff, 0x88, 0x00)));
+/// assert_eq!("rgb(1, 2, 3)".parse(), Ok(Color::RGB(1, 2, 3)));
+/// assert_eq!("208".parse(), Ok(Color::ANSI256(208)));
+/// ```
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.is_empty() {
+            Err(ParseColorError::Empty)
+        } else if let Some(hex) = text.strip_prefix('#') {
+            parse_hex(hex).ok_or_else(|| ParseColorError::InvalidHex(text.to_owned()))
+        } else if text.bytes().all(|byte| byte.is_ascii_digit()) {
+            text.parse()
+                .map(Color::ANSI256)
+                .map_err(|_| ParseColorError::IndexOutOfRange(text.to_owned()))
+        } else if text
+            .get(..4)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("rgb("))
+        {
+            parse_rgb_function(&text[4..])
+                .ok_or_else(|| ParseColorError::InvalidRGB(text.to_owned()))
+        } else {
+            parse_name(text).ok_or_else(|| ParseColorError::UnknownName(text.to_owned()))
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        // Each digit of `#rgb` is repeated, so that `#f80` is `#ff8800`.
+        3 => Some(Color::RGB(
+            component(&hex[0..1])? * 0x11,
+            component(&hex[1..2])? * 0x11,
+            component(&hex[2..3])? * 0x11,
+        )),
+        6 => Some(Color::RGB(
+            component(&hex[0..2])?,
+            component(&hex[2..4])?,
+            component(&hex[4..6])?,
+        )),
+        _ => None,
+    }
+}
+
+/// Parses the `r, g, b)` that follows `rgb(`.
+fn parse_rgb_function(arguments: &str) -> Option<Color> {
+    let mut components = arguments
+        .strip_suffix(')')?
+        .split(',')
+        .map(|component| component.trim().parse::<u8>().ok());
+    match (
+        components.next()??,
+        components.next()??,
+        components.next()??,
+        components.next(),
+    ) {
+        (r, g, b, None) => Some(Color::RGB(r, g, b)),
+        _ => None,
+    }
+}
+
+fn parse_name(name: &str) -> Option<Color> {
+    let name = name
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>();
+    let name = name.replace("grey", "gray");
+    let color = match name.as_str() {
+        "unset" | "default" | "none" => return Some(Color::Unset),
+        "black" => Color4Bit::BLACK,
+        "red" | "darkred" => Color4Bit::DARK_RED,
+        "green" | "darkgreen" => Color4Bit::DARK_GREEN,
+        "yellow" | "darkyellow" => Color4Bit::DARK_YELLOW,
+        "blue" | "darkblue" => Color4Bit::DARK_BLUE,
+        "magenta" | "darkmagenta" => Color4Bit::DARK_MAGENTA,
+        "cyan" | "darkcyan" => Color4Bit::DARK_CYAN,
+        "gray" | "darkgray" => Color4Bit::DARK_GRAY,
+        "brightgray" | "brightblack" => Color4Bit::BRIGHT_GRAY,
+        "brightred" => Color4Bit::BRIGHT_RED,
+        "brightgreen" => Color4Bit::BRIGHT_GREEN,
+        "brightyellow" => Color4Bit::BRIGHT_YELLOW,
+        "brightblue" => Color4Bit::BRIGHT_BLUE,
+        "brightmagenta" => Color4Bit::BRIGHT_MAGENTA,
+        "brightcyan" => Color4Bit::BRIGHT_CYAN,
+        "white" | "brightwhite" => Color4Bit::WHITE,
+        _ => return None,
+    };
+    Some(Color::Color4Bit(color))
+}
+
+/// The error returned when parsing a [`Color`] fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseColorError {
+    /// The text is empty.
+    Empty,
+    /// The text is neither a color name nor any of the other forms.
+    UnknownName(String),
+    /// The text starts with `#`, but is not followed by 3 or 6 hexadecimal digits.
+    InvalidHex(String),
+    /// The text starts with `rgb(`, but is not of the form `rgb(r, g, b)`.
+    InvalidRGB(String),
+    /// The text is a number greater than 255.
+    IndexOutOfRange(String),
+}
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::Empty => write!(f, "empty color"),
+            ParseColorError::UnknownName(name) => write!(
+                f,
+                "unknown color `{}`, expected a color name, `#rrggbb`, `#rgb`, \
+                 `rgb(r, g, b)` or an index from 0 to 255",
+                name
+            ),
+            ParseColorError::InvalidHex(text) => write!(
+                f,
+                "invalid hex color `{}`, expected `#` followed by 3 or 6 hexadecimal digits",
+                text
+            ),
+            ParseColorError::InvalidRGB(text) => write!(
+                f,
+                "invalid color `{}`, expected `rgb(r, g, b)` with components from 0 to 255",
+                text
+            ),
+            ParseColorError::IndexOutOfRange(text) => {
+                write!(
+                    f,
+                    "color index `{}` is out of the range from 0 to 255",
+                    text
+                )
+            }
+        }
+    }
+}
+impl Error for ParseColorError {}
+
+/// Parses a space separated list of style properties, applied from left to right:
+///
+/// - `bold`, `dim`, `underline`, `italic`, `blink` and `strike` set the property,
+///   and the same names prefixed by `no-` unset it;
+/// - `fg:COLOR` (or `foreground:COLOR`) and `bg:COLOR` (or `background:COLOR`) set a color,
+///   where `COLOR` is any of the forms accepted by [`Color`]'s [`FromStr`] implementation;
+/// - a bare `COLOR` sets the foreground color;
+/// - `reset` [resets][`StyleChange::RESET`] all properties.
+///
+/// The properties that are not mentioned are [kept][`Change::Keep`],
+/// so that the empty string parses to [`StyleChange::KEEP`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use prompt_utils::{
+/// #     style_change,
+/// #     styling::{Color, Color4Bit, StyleChange},
+/// # };
+/// #
+/// let style_change: StyleChange = "bold italic fg:#ff8800 bg:blue".parse().unwrap();
+/// assert_eq!(
+///     style_change,
+///     style_change! {
+///         bold: true,
+///         italic: true,
+///         foreground: Color::RGB(0xff, 0x88, 0x00),
+///         background: Color::Color4Bit(Color4Bit::DARK_BLUE),
+///     }
+/// );
+/// ```
+impl FromStr for StyleChange {
+    type Err = ParseStyleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut style_change = StyleChange::KEEP;
+        for (position, token) in tokens(text) {
+            let error = |kind| ParseStyleError {
+                position,
+                token: token.to_owned(),
+                kind,
+            };
+            let color = |text: &str| {
+                text.parse()
+                    .map_err(|err| error(ParseStyleErrorKind::Color(err)))
+            };
+
+            let normalized = token.to_ascii_lowercase().replace('_', "-");
+            let (value, attribute) = match normalized.strip_prefix("no-") {
+                Some(attribute) => (false, attribute),
+                None => (true, normalized.as_str()),
+            };
+            let field = match attribute {
+                "bold" => Some(&mut style_change.bold),
+                "dim" => Some(&mut style_change.dim),
+                "underline" => Some(&mut style_change.underline),
+                "italic" => Some(&mut style_change.italic),
+                "blink" => Some(&mut style_change.blink),
+                "strike" | "strikethrough" => Some(&mut style_change.strike),
+                _ => None,
+            };
+            if let Some(field) = field {
+                *field = Change::SetTo(value);
+                continue;
+            }
+
+            match token.split_once(':') {
+                Some((key, value)) => match key.to_ascii_lowercase().as_str() {
+                    "fg" | "foreground" => style_change.foreground = Change::SetTo(color(value)?),
+                    "bg" | "background" => style_change.background = Change::SetTo(color(value)?),
+                    _ => return Err(error(ParseStyleErrorKind::UnknownKey)),
+                },
+                None if normalized == "reset" => style_change = StyleChange::RESET,
+                None => match token.parse() {
+                    Ok(color) => style_change.foreground = Change::SetTo(color),
+                    Err(ParseColorError::UnknownName(_)) => {
+                        return Err(error(ParseStyleErrorKind::UnknownProperty))
+                    }
+                    Err(err) => return Err(error(ParseStyleErrorKind::Color(err))),
+                },
+            }
+        }
+        Ok(style_change)
+    }
+}
+
+/// Splits `text` at the spaces outside of parentheses,
+/// so that `rgb(r, g, b)` stays in one token.
+///
+/// Each token is returned with its byte offset.
+fn tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut tokens = Vec::new();
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    tokens.push((start, &text[start..i]));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        tokens.push((start, &text[start..]));
+    }
+    tokens.into_iter()
+}
+
+/// The error returned when parsing a [`StyleChange`] fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseStyleError {
+    /// The byte offset of the offending token.
+    pub position: usize,
+    /// The offending token.
+    pub token: String,
+    pub kind: ParseStyleErrorKind,
+}
+impl fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at position {}: ", self.position)?;
+        match &self.kind {
+            ParseStyleErrorKind::UnknownProperty => write!(
+                f,
+                "unknown style property `{}`, expected `bold`, `dim`, `underline`, `italic`, \
+                 `blink`, `strike` (optionally prefixed by `no-`), `fg:COLOR`, `bg:COLOR`, \
+                 `reset` or a color",
+                self.token
+            ),
+            ParseStyleErrorKind::UnknownKey => write!(
+                f,
+                "unknown key in `{}`, expected `fg:` or `bg:`",
+                self.token
+            ),
+            ParseStyleErrorKind::Color(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl Error for ParseStyleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseStyleErrorKind::Color(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The reason why parsing a [`StyleChange`] failed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseStyleErrorKind {
+    /// The token is neither a property nor a color.
+    UnknownProperty,
+    /// The token is of the form `key:value`, with a `key` other than `fg` and `bg`.
+    UnknownKey,
+    /// The token contains an invalid color.
+    Color(ParseColorError),
+}
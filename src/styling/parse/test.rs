@@ -0,0 +1,103 @@
+mod color {
+    use crate::styling::{Color, Color4Bit, ParseColorError};
+
+    #[test]
+    fn names_ignore_case_and_separators() {
+        for name in [
+            "bright-green",
+            "bright_green",
+            "BrightGreen",
+            "bright green",
+        ] {
+            assert_eq!(
+                name.parse(),
+                Ok(Color::Color4Bit(Color4Bit::BRIGHT_GREEN)),
+                "{}",
+                name
+            );
+        }
+        assert_eq!("red".parse(), Ok(Color::Color4Bit(Color4Bit::DARK_RED)));
+        assert_eq!("grey".parse(), Ok(Color::Color4Bit(Color4Bit::DARK_GRAY)));
+        assert_eq!("default".parse(), Ok(Color::Unset));
+    }
+
+    #[test]
+    fn numeric_forms() {
+        assert_eq!("#ff8800".parse(), Ok(Color::RGB(0xff, 0x88, 0x00)));
+        assert_eq!("#F80".parse(), Ok(Color::RGB(0xff, 0x88, 0x00)));
+        assert_eq!("RGB( 1,2 , 3)".parse(), Ok(Color::RGB(1, 2, 3)));
+        assert_eq!("0".parse(), Ok(Color::ANSI256(0)));
+        assert_eq!("255".parse(), Ok(Color::ANSI256(255)));
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |text: &str| text.parse::<Color>().unwrap_err();
+        assert_eq!(parse(""), ParseColorError::Empty);
+        assert_eq!(parse("#ff88"), ParseColorError::InvalidHex("#ff88".into()));
+        assert_eq!(parse("#ggg"), ParseColorError::InvalidHex("#ggg".into()));
+        assert_eq!(
+            parse("rgb(1, 2)"),
+            ParseColorError::InvalidRGB("rgb(1, 2)".into())
+        );
+        assert_eq!(
+            parse("rgb(1, 2, 256)"),
+            ParseColorError::InvalidRGB("rgb(1, 2, 256)".into())
+        );
+        assert_eq!(parse("256"), ParseColorError::IndexOutOfRange("256".into()));
+        assert_eq!(
+            parse("purple"),
+            ParseColorError::UnknownName("purple".into())
+        );
+        // The fourth byte is inside `€`.
+        assert_eq!(parse("ab€"), ParseColorError::UnknownName("ab€".into()));
+        assert_eq!(parse("rgb€"), ParseColorError::UnknownName("rgb€".into()));
+    }
+}
+
+mod style_change {
+    use crate::styling::{
+        Change, Color, Color4Bit, ParseColorError, ParseStyleError, ParseStyleErrorKind,
+        StyleChange,
+    };
+
+    #[test]
+    fn properties_apply_from_left_to_right() {
+        assert_eq!("".parse(), Ok(StyleChange::KEEP));
+        assert_eq!(
+            "reset bold no-bold underline rgb(1, 2, 3) bg:bright-red".parse(),
+            Ok(StyleChange {
+                foreground: Change::SetTo(Color::RGB(1, 2, 3)),
+                background: Change::SetTo(Color::Color4Bit(Color4Bit::BRIGHT_RED)),
+                underline: Change::SetTo(true),
+                ..StyleChange::RESET
+            })
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        let parse = |text: &str| text.parse::<StyleChange>().unwrap_err();
+        assert_eq!(
+            parse("bold sparkly"),
+            ParseStyleError {
+                position: 5,
+                token: "sparkly".into(),
+                kind: ParseStyleErrorKind::UnknownProperty,
+            }
+        );
+        assert_eq!(
+            parse("bold  fg:#12"),
+            ParseStyleError {
+                position: 6,
+                token: "fg:#12".into(),
+                kind: ParseStyleErrorKind::Color(ParseColorError::InvalidHex("#12".into())),
+            }
+        );
+        assert_eq!(parse("ul:red").kind, ParseStyleErrorKind::UnknownKey);
+        assert_eq!(
+            parse("italic fg:").to_string(),
+            "at position 7: empty color"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+mod render_args {
+    use crate::{Error, RenderArgs, ShellKind};
+    use prompt_utils::env::command_result::ExitCode;
+    use std::{path::Path, time::Duration};
+
+    fn parse(args: &[&str]) -> Result<RenderArgs, Error> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        RenderArgs::parse(&args)
+    }
+
+    fn usage(args: &[&str]) -> String {
+        match parse(args) {
+            Err(Error::Usage(message)) => message,
+            result => panic!("expected a usage error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let args = parse(&[
+            "--exit-code",
+            "-1",
+            "--duration-ms",
+            "1500",
+            "--shell",
+            "pwsh",
+            "--width",
+            "80",
+            "--config",
+            "prompt.toml",
+            "--right",
+        ])
+        .unwrap();
+        assert_eq!(args.exit_code, Some(ExitCode(-1)));
+        assert_eq!(args.duration, Some(Duration::from_millis(1500)));
+        assert_eq!(args.shell, Some(ShellKind::PowerShell));
+        assert_eq!(args.width, Some(80));
+        assert_eq!(args.config.as_deref(), Some(Path::new("prompt.toml")));
+        assert!(args.right);
+
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.exit_code, None);
+        assert_eq!(args.shell, None);
+        assert!(!args.right);
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert_eq!(usage(&["--width"]), "`--width` expects a value");
+        assert_eq!(
+            usage(&["--shell", "tcsh"]),
+            "invalid value for `--shell`: `tcsh`"
+        );
+        assert_eq!(
+            usage(&["--exit-code", "one"]),
+            "invalid value for `--exit-code`: `one`"
+        );
+        assert_eq!(usage(&["--verbose"]), "unexpected argument `--verbose`");
+    }
+}
+
+mod quote {
+    use crate::{quote_fish, quote_posix, quote_powershell};
+    use std::process::Command;
+
+    #[test]
+    fn quotes_text_for_each_shell() {
+        assert_eq!(quote_posix(r"it's a \path"), r"'it'\''s a \path'");
+        assert_eq!(quote_fish(r"it's a \path"), r"'it\'s a \\path'");
+        assert_eq!(quote_powershell(r"it's a \path"), r"'it''s a \path'");
+    }
+
+    #[test]
+    fn posix_quoted_text_is_read_back_as_written() {
+        let text = "it's $HOME `id` \\ \"quoted\"";
+        let output = match Command::new("sh")
+            .args(["-c", &format!("printf %s {}", quote_posix(text))])
+            .output()
+        {
+            Ok(output) => output,
+            // sh is not installed.
+            Err(_) => return,
+        };
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), text);
+    }
+}
@@ -5,3 +5,9 @@ pub mod ansi;
 
 #[cfg(feature = "not_styled_writer")]
 pub mod not_styled;
+
+#[cfg(feature = "width_counting_writer")]
+pub mod width_counting;
+
+#[cfg(feature = "zsh_styled_writer")]
+pub mod zsh;
@@ -1,24 +1,112 @@
 //! Module containing the [`ANSIStyledWriter`].
 
-use crate::styling::{Change, Color, Color4Bit, Style, StyleChange, StyledWrite};
-use std::{fmt, io};
+#[cfg(test)]
+mod test;
+
+use crate::styling::{Change, Color, Color4Bit, ColorDepth, Style, StyleChange, StyledWrite};
+use std::io;
 
 /// A [`StyledWrite`] that only uses ANSI escape sequences.
+///
+/// Every escape sequence is wrapped in the zero-width delimiters of the writer's [`Shell`],
+/// so that the shell's line editor does not count them as visible characters.
+///
+/// Colors are [downgraded][`Color::downgraded`] to the writer's [`ColorDepth`] when written,
+/// while [`StyledWrite::style`] still reports them as they were set.
+/// The color depth is [`ColorDepth::TrueColor`] unless [changed][`Self::set_color_depth`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ANSIStyledWriter<W: io::Write> {
     writer: W,
     style: Style,
+    shell: Shell,
+    color_depth: ColorDepth,
 }
 impl<W: io::Write> ANSIStyledWriter<W> {
+    /// Creates an [`ANSIStyledWriter`] targeting [`Shell::Plain`].
     pub fn new(writer: W) -> Self {
+        Self::with_shell(writer, Shell::Plain)
+    }
+
+    /// Creates an [`ANSIStyledWriter`] whose escape sequences are wrapped
+    /// in the zero-width delimiters of `shell`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use prompt_utils::{
+    /// #     styled_write,
+    /// #     styling::{Color, Color4Bit},
+    /// #     writers::ansi::{ANSIStyledWriter, Shell},
+    /// # };
+    /// # use std::io::Write;
+    /// #
+    /// let mut writer = ANSIStyledWriter::with_shell(Vec::new(), Shell::Bash);
+    /// styled_write!(
+    ///     &mut writer, foreground: Color::Color4Bit(Color4Bit::DARK_GREEN);
+    ///     "ok",
+    /// ).unwrap();
+    /// assert_eq!(writer.into_inner(), b"\\[\x1B[32m\\]ok\\[\x1B[39m\\]");
+    /// ```
+    pub fn with_shell(writer: W, shell: Shell) -> Self {
         Self {
             writer,
             style: Default::default(),
+            shell,
+            color_depth: ColorDepth::TrueColor,
         }
     }
+
+    /// The shell targeted by this writer.
+    pub fn shell(&self) -> Shell {
+        self.shell
+    }
+
+    /// Changes the shell targeted by this writer for future writes.
+    pub fn set_shell(&mut self, shell: Shell) {
+        self.shell = shell;
+    }
+
+    /// The colors this writer is allowed to write.
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    /// Changes the colors this writer is allowed to write for future writes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use prompt_utils::{
+    /// #     styled_write,
+    /// #     styling::{Color, ColorDepth},
+    /// #     writers::ansi::ANSIStyledWriter,
+    /// # };
+    /// # use std::io::Write;
+    /// #
+    /// let mut writer = ANSIStyledWriter::new(Vec::new());
+    /// writer.set_color_depth(ColorDepth::ANSI256);
+    /// styled_write!(&mut writer, foreground: Color::RGB(0xff, 0x88, 0x00); "!").unwrap();
+    /// assert_eq!(writer.into_inner(), b"\x1B[38;5;208m!\x1B[39m");
+    /// ```
+    pub fn set_color_depth(&mut self, color_depth: ColorDepth) {
+        self.color_depth = color_depth;
+    }
+
+    /// Unwraps this [`ANSIStyledWriter`], returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn escapes_text(&self) -> bool {
+        matches!(self.shell, Shell::Bash | Shell::Zsh)
+    }
 }
 impl<W: io::Write> io::Write for ANSIStyledWriter<W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.escapes_text() {
+            self.write_all(buf)?;
+            return Ok(buf.len());
+        }
         self.writer.write(buf)
     }
 
@@ -26,16 +114,27 @@ impl<W: io::Write> io::Write for ANSIStyledWriter<W> {
         self.writer.flush()
     }
 
-    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
-        self.writer.write_vectored(bufs)
-    }
-
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.writer.write_all(buf)
-    }
-
-    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
-        self.writer.write_fmt(fmt)
+        if !self.escapes_text() {
+            return self.writer.write_all(buf);
+        }
+        let mut start = 0;
+        for (i, &byte) in buf.iter().enumerate() {
+            let escaped: &[u8] = match (self.shell, byte) {
+                // Bash decodes the backslash escapes of the prompt, then expands it as if it were
+                // in double quotes: `\\\\` becomes `\\` and then `\`, `\\$` becomes `\$` and then `$`.
+                // A single escaped `\$` would be decoded as `#` for root.
+                (Shell::Bash, b'\\') => br"\\\\",
+                (Shell::Bash, b'$') => br"\\$",
+                (Shell::Bash, b'`') => br"\\`",
+                (Shell::Zsh, b'%') => b"%%",
+                _ => continue,
+            };
+            self.writer.write_all(&buf[start..i])?;
+            self.writer.write_all(escaped)?;
+            start = i + 1;
+        }
+        self.writer.write_all(&buf[start..])
     }
 }
 impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
@@ -50,7 +149,7 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
             ($($arg:tt)*) => {
                 {
                     if is_first {
-                        write!(self.writer, "\x1B[")?;
+                        write!(self.writer, "{}\x1B[", self.shell.zero_width_start())?;
                         #[allow(unused_assignments)]
                         {
                             is_first = false;
@@ -136,7 +235,7 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
         let foreground = match change.foreground {
             Change::Keep => self.style.foreground,
             Change::SetTo(foreground) => {
-                match foreground {
+                match foreground.downgraded(self.color_depth) {
                     Color::Unset => write_component!("39"),
                     Color::Color4Bit(color) => {
                         let color_number = color.intersection(Color4Bit::COLOR_MASK).bits();
@@ -157,7 +256,7 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
         let background = match change.background {
             Change::Keep => self.style.background,
             Change::SetTo(background) => {
-                match background {
+                match background.downgraded(self.color_depth) {
                     Color::Unset => write_component!("49"),
                     Color::Color4Bit(color) => {
                         let color_number = color.intersection(Color4Bit::COLOR_MASK).bits();
@@ -177,7 +276,7 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
 
         // At this point, it is certain that at least one component was written,
         // because this function returns early when `!changes.any()`.
-        write!(self.writer, "m")?;
+        write!(self.writer, "m{}", self.shell.zero_width_end())?;
 
         self.style = Style {
             foreground,
@@ -194,7 +293,12 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
     }
 
     fn reset_style(&mut self) -> io::Result<()> {
-        write!(self.writer, "\x1B[0m")?;
+        write!(
+            self.writer,
+            "{}\x1B[0m{}",
+            self.shell.zero_width_start(),
+            self.shell.zero_width_end(),
+        )?;
         self.style = Default::default();
         Ok(())
     }
@@ -208,9 +312,9 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
             return Ok(());
         }
 
-        write!(self.writer, "\x1B[")?;
+        write!(self.writer, "{}\x1B[", self.shell.zero_width_start())?;
 
-        match self.style.background {
+        match self.style.background.downgraded(self.color_depth) {
             Color::Unset => write!(self.writer, "39")?,
             Color::Color4Bit(color) => {
                 let color_number = color.intersection(Color4Bit::COLOR_MASK).bits();
@@ -227,7 +331,7 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
 
         write!(self.writer, ";")?;
 
-        match self.style.foreground {
+        match self.style.foreground.downgraded(self.color_depth) {
             Color::Unset => write!(self.writer, "49")?,
             Color::Color4Bit(color) => {
                 let color_number = color.intersection(Color4Bit::COLOR_MASK).bits();
@@ -242,10 +346,71 @@ impl<W: io::Write> StyledWrite for ANSIStyledWriter<W> {
             Color::RGB(r, g, b) => write!(self.writer, "48;2;{};{};{}", r, g, b)?,
         };
 
-        write!(self.writer, "m")?;
+        write!(self.writer, "m{}", self.shell.zero_width_end())?;
 
         std::mem::swap(&mut self.style.foreground, &mut self.style.background);
 
         Ok(())
     }
 }
+
+/// The shell (or line editor) that will display the output of an [`ANSIStyledWriter`].
+///
+/// Line editors count every written character as visible,
+/// unless it is enclosed in the delimiters they use for marking zero-width text.
+/// Escape sequences counted as visible characters break line wrapping and cursor positioning.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    /// Escape sequences are written unwrapped.
+    #[default]
+    Plain,
+    /// Escape sequences are wrapped in `\[` and `\]`, as expected in [bash]'s `PS1`.
+    ///
+    /// The `\`, `$` and `` ` `` of the text are escaped, so that the text is displayed as written
+    /// rather than expanded when the `promptvars` option is set, as it is by default.
+    ///
+    /// [bash]: https://www.gnu.org/software/bash/manual/html_node/Controlling-the-Prompt.html
+    Bash,
+    /// Escape sequences are wrapped in `%{` and `%}`, as expected in [zsh]'s `PROMPT`.
+    ///
+    /// The `%` of the text are escaped as `%%`, so that the text is displayed as written
+    /// rather than expanded as prompt sequences.
+    ///
+    /// [zsh]: https://zsh.sourceforge.io/Doc/Release/Prompt-Expansion.html
+    Zsh,
+    /// Escape sequences are wrapped in `\x01` characters.
+    ///
+    /// [ksh] only treats `\x01` as a delimiter of zero-width text
+    /// if the prompt starts with `\x01\r`, which must be written before the rest of the prompt.
+    ///
+    /// [ksh]: http://www.kornshell.com/doc/man93.html
+    Ksh,
+    /// Escape sequences are wrapped in `\x01` and `\x02`,
+    /// the markers that [readline] itself uses for zero-width text.
+    ///
+    /// [readline]: https://tiswww.case.edu/php/chet/readline/rltop.html
+    Readline,
+}
+impl Shell {
+    /// The delimiter written before each escape sequence.
+    pub const fn zero_width_start(&self) -> &'static str {
+        match self {
+            Shell::Plain => "",
+            Shell::Bash => "\\[",
+            Shell::Zsh => "%{",
+            Shell::Ksh => "\x01",
+            Shell::Readline => "\x01",
+        }
+    }
+
+    /// The delimiter written after each escape sequence.
+    pub const fn zero_width_end(&self) -> &'static str {
+        match self {
+            Shell::Plain => "",
+            Shell::Bash => "\\]",
+            Shell::Zsh => "%}",
+            Shell::Ksh => "\x01",
+            Shell::Readline => "\x02",
+        }
+    }
+}
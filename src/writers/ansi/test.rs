@@ -0,0 +1,61 @@
+mod bash {
+    use crate::{
+        styling::{Color, Color4Bit},
+        writers::ansi::{ANSIStyledWriter, Shell},
+    };
+    use std::{io::Write, process::Command};
+
+    #[test]
+    fn text_is_not_expanded_by_bash() {
+        let path = "~/$(echo PWNED>&2)/`id`/a\\$b";
+        let mut writer = ANSIStyledWriter::with_shell(Vec::new(), Shell::Bash);
+        styled_write!(
+            &mut writer, foreground: Color::Color4Bit(Color4Bit::DARK_BLUE);
+            "{}", path,
+        )
+        .unwrap();
+        let prompt = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            prompt,
+            "\\[\x1B[34m\\]~/\\\\$(echo PWNED>&2)/\\\\`id\\\\`/a\\\\\\\\\\\\$b\\[\x1B[39m\\]"
+        );
+
+        // `${prompt@P}` expands the prompt as bash expands `PS1`,
+        // except that `\[` and `\]` are dropped outside of an interactive shell.
+        let output = match Command::new("bash")
+            .args(["-c", r#"printf %s "${1@P}""#, "bash", &prompt])
+            .output()
+        {
+            Ok(output) => output,
+            // Bash is not installed.
+            Err(_) => return,
+        };
+        assert!(output.stderr.is_empty(), "{:?}", output);
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("\x1B[34m{}\x1B[39m", path)
+        );
+    }
+}
+
+mod zsh {
+    use crate::{
+        styling::{Color, Color4Bit},
+        writers::ansi::{ANSIStyledWriter, Shell},
+    };
+    use std::io::Write;
+
+    #[test]
+    fn percent_signs_are_escaped() {
+        let mut writer = ANSIStyledWriter::with_shell(Vec::new(), Shell::Zsh);
+        styled_write!(
+            &mut writer, foreground: Color::Color4Bit(Color4Bit::DARK_BLUE);
+            "{}", "100%~%n",
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "%{\x1B[34m%}100%%~%%n%{\x1B[39m%}"
+        );
+    }
+}
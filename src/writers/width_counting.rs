@@ -0,0 +1,168 @@
+//! Module containing the [`WidthCountingWriter`] and display width measurement utilities.
+
+#[cfg(test)]
+mod test;
+
+use crate::styling::{Style, StyleChange, StyledWrite};
+use std::{io, str};
+use unicode_width::UnicodeWidthStr;
+
+/// How to measure characters whose [East Asian Width] is ambiguous.
+///
+/// Such characters, which include the `↑`, `↓`, `≡` and `○` symbols written by
+/// [`fmt::git`][`crate::fmt`], are displayed as one column wide by most terminals,
+/// but as two columns wide by terminals configured for an East Asian context.
+///
+/// [East Asian Width]: https://www.unicode.org/reports/tr11/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AmbiguousWidth {
+    /// Ambiguous characters are one column wide.
+    #[default]
+    Narrow,
+    /// Ambiguous characters are two columns wide.
+    Wide,
+}
+
+/// The number of terminal columns taken by `text` when displayed.
+///
+/// Wide characters (such as CJK ideographs and most emojis) take two columns,
+/// while combining marks and other zero-width characters take none.
+/// Control characters are also considered to take no columns,
+/// but any printable characters following them (as in escape sequences) are counted.
+pub fn display_width(text: &str, ambiguous: AmbiguousWidth) -> usize {
+    text.split(char::is_control)
+        .map(|part| match ambiguous {
+            AmbiguousWidth::Narrow => part.width(),
+            AmbiguousWidth::Wide => part.width_cjk(),
+        })
+        .sum()
+}
+
+/// A [`StyledWrite`] that counts the terminal columns of the text written through it.
+///
+/// The text is forwarded to the wrapped writer and measured as in [`display_width`].
+/// Style changes are forwarded without being counted, so the count is not affected
+/// by any escape sequences the wrapped writer emits for them.
+///
+/// Text split across writes in the middle of a UTF-8 sequence is measured once complete.
+/// Invalid UTF-8 sequences are counted as one column each.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WidthCountingWriter<W: StyledWrite> {
+    writer: W,
+    width: usize,
+    ambiguous: AmbiguousWidth,
+    /// The bytes of an incomplete UTF-8 sequence at the end of the last write.
+    pending: Vec<u8>,
+}
+impl<W: StyledWrite> WidthCountingWriter<W> {
+    /// Creates a [`WidthCountingWriter`] measuring ambiguous characters
+    /// as [narrow][`AmbiguousWidth::Narrow`].
+    pub fn new(writer: W) -> Self {
+        Self::with_ambiguous_width(writer, AmbiguousWidth::Narrow)
+    }
+
+    /// Creates a [`WidthCountingWriter`] measuring ambiguous characters as indicated.
+    pub fn with_ambiguous_width(writer: W, ambiguous: AmbiguousWidth) -> Self {
+        Self {
+            writer,
+            width: 0,
+            ambiguous,
+            pending: Vec::new(),
+        }
+    }
+
+    /// The number of terminal columns of the text written so far.
+    ///
+    /// Trailing bytes of an incomplete UTF-8 sequence are not counted.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Resets the count to zero.
+    pub fn reset_width(&mut self) {
+        self.width = 0;
+        self.pending.clear();
+    }
+
+    /// A reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// A mutable reference to the wrapped writer.
+    ///
+    /// Text written directly to the wrapped writer is not counted.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps this [`WidthCountingWriter`], returning the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn count(&mut self, buf: &[u8]) {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(buf);
+
+        let mut rest = &bytes[..];
+        loop {
+            match str::from_utf8(rest) {
+                Ok(text) => {
+                    self.width += display_width(text, self.ambiguous);
+                    break;
+                }
+                Err(err) => {
+                    let (valid, invalid) = rest.split_at(err.valid_up_to());
+                    // `valid` is known to be valid UTF-8.
+                    self.width +=
+                        display_width(str::from_utf8(valid).unwrap_or(""), self.ambiguous);
+                    match err.error_len() {
+                        Some(len) => {
+                            self.width += 1;
+                            rest = &invalid[len..];
+                        }
+                        None => {
+                            self.pending = invalid.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+impl<W: StyledWrite> io::Write for WidthCountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.count(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer.write_all(buf)?;
+        self.count(buf);
+        Ok(())
+    }
+}
+impl<W: StyledWrite> StyledWrite for WidthCountingWriter<W> {
+    fn style(&self) -> &Style {
+        self.writer.style()
+    }
+
+    fn change_style(&mut self, change: StyleChange) -> io::Result<()> {
+        self.writer.change_style(change)
+    }
+
+    fn reset_style(&mut self) -> io::Result<()> {
+        self.writer.reset_style()
+    }
+
+    fn swap_colors(&mut self) -> io::Result<()> {
+        self.writer.swap_colors()
+    }
+}
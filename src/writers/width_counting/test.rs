@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod display_width {
+
+    use crate::writers::width_counting::{display_width, AmbiguousWidth};
+
+    #[test]
+    fn git_symbols_depend_on_ambiguous_width() {
+        for symbol in &["↑", "↓", "≡", "○"] {
+            assert_eq!(display_width(symbol, AmbiguousWidth::Narrow), 1);
+            assert_eq!(display_width(symbol, AmbiguousWidth::Wide), 2);
+        }
+        assert_eq!(display_width("◉", AmbiguousWidth::Narrow), 1);
+        assert_eq!(display_width("◉", AmbiguousWidth::Wide), 1);
+    }
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("漢字", AmbiguousWidth::Narrow), 4);
+        assert_eq!(display_width("🦀", AmbiguousWidth::Narrow), 2);
+        assert_eq!(display_width("👩‍🔬", AmbiguousWidth::Narrow), 2);
+    }
+
+    #[test]
+    fn combining_marks_take_no_columns() {
+        assert_eq!(display_width("e\u{301}", AmbiguousWidth::Narrow), 1);
+        assert_eq!(display_width("a\u{200B}b", AmbiguousWidth::Narrow), 2);
+    }
+
+    #[test]
+    fn control_characters_take_no_columns() {
+        assert_eq!(display_width("a\tb\nc\x07", AmbiguousWidth::Narrow), 3);
+    }
+}
+
+#[cfg(all(test, feature = "not_styled_writer"))]
+mod width_counting_writer {
+
+    use crate::{
+        styling::{Color, Color4Bit, StyledWrite},
+        writers::{not_styled::NotStyledWriter, width_counting::WidthCountingWriter},
+    };
+    use std::io::Write;
+
+    #[test]
+    fn utf8_sequences_split_across_writes_are_counted_once_complete() {
+        let mut writer = WidthCountingWriter::new(NotStyledWriter::new(Vec::new()));
+        let bytes = "漢".as_bytes();
+        writer.write_all(&bytes[..1]).unwrap();
+        writer.write_all(&bytes[1..2]).unwrap();
+        assert_eq!(writer.width(), 0);
+        writer.write_all(&bytes[2..]).unwrap();
+        assert_eq!(writer.width(), 2);
+    }
+
+    #[test]
+    fn invalid_utf8_counts_one_column_per_sequence() {
+        let mut writer = WidthCountingWriter::new(NotStyledWriter::new(Vec::new()));
+        writer.write_all(b"a\xFFb").unwrap();
+        assert_eq!(writer.width(), 3);
+    }
+
+    #[test]
+    fn style_changes_are_not_counted() {
+        let mut writer = WidthCountingWriter::new(NotStyledWriter::new(Vec::new()));
+        writer
+            .change_style(style_change! { foreground: Color::Color4Bit(Color4Bit::WHITE) })
+            .unwrap();
+        write!(writer, "ab").unwrap();
+        writer.reset_style().unwrap();
+        assert_eq!(writer.width(), 2);
+    }
+}
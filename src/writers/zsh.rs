@@ -0,0 +1,164 @@
+//! Module containing the [`ZshStyledWriter`].
+
+#[cfg(test)]
+mod test;
+
+use crate::styling::{Change, Color, Style, StyleChange, StyledWrite};
+use std::io;
+
+/// A [`StyledWrite`] that uses [zsh prompt escapes][zsh].
+///
+/// Colors, bold and underline are written as zsh's own escapes
+/// (`%F{...}`, `%K{...}`, `%B`, `%U` and their closing counterparts),
+/// so that zsh computes the prompt's width and degrades colors the terminal cannot display.
+/// The remaining style properties have no zsh escape,
+/// so they are written as ANSI escape sequences enclosed in `%{` and `%}`.
+///
+/// Every `%` written as text is escaped as `%%`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use prompt_utils::{
+/// #     styled_write,
+/// #     styling::{Color, Color4Bit},
+/// #     writers::zsh::ZshStyledWriter,
+/// # };
+/// # use std::io::Write;
+/// #
+/// let mut writer = ZshStyledWriter::new(Vec::new());
+/// styled_write!(
+///     &mut writer, foreground: Color::Color4Bit(Color4Bit::BRIGHT_RED), bold: true;
+///     "100%",
+/// ).unwrap();
+/// assert_eq!(writer.into_inner(), b"%B%F{9}100%%%b%f");
+/// ```
+///
+/// [zsh]: https://zsh.sourceforge.io/Doc/Release/Prompt-Expansion.html#Visual-effects
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ZshStyledWriter<W: io::Write> {
+    writer: W,
+    style: Style,
+}
+impl<W: io::Write> ZshStyledWriter<W> {
+    /// Creates a [`ZshStyledWriter`] writing to `writer`, starting with the default style.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            style: Default::default(),
+        }
+    }
+
+    /// Unwraps this [`ZshStyledWriter`], returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_color(&mut self, escape: char, color: Color) -> io::Result<()> {
+        match color {
+            // `%f` and `%k` are the lowercase versions of `%F` and `%K`.
+            Color::Unset => write!(self.writer, "%{}", escape.to_ascii_lowercase()),
+            Color::Color4Bit(color) => {
+                write!(self.writer, "%{}{{{}}}", escape, color.to_ansi_256())
+            }
+            Color::ANSI256(color) => write!(self.writer, "%{}{{{}}}", escape, color),
+            Color::RGB(r, g, b) => {
+                write!(self.writer, "%{}{{#{:02x}{:02x}{:02x}}}", escape, r, g, b)
+            }
+        }
+    }
+}
+impl<W: io::Write> io::Write for ZshStyledWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut parts = buf.split(|&byte| byte == b'%');
+        if let Some(first) = parts.next() {
+            self.writer.write_all(first)?;
+        }
+        for part in parts {
+            self.writer.write_all(b"%%")?;
+            self.writer.write_all(part)?;
+        }
+        Ok(())
+    }
+}
+impl<W: io::Write> StyledWrite for ZshStyledWriter<W> {
+    fn change_style(&mut self, change: StyleChange) -> io::Result<()> {
+        let previous = self.style.clone();
+        let style = change.apply_to(&previous);
+
+        macro_rules! write_raw {
+            ($code:expr) => {
+                write!(self.writer, "%{{\x1B[{}m%}}", $code)?
+            };
+        }
+
+        // There is no way to disable dim without disabling bold, and vice versa.
+        // Moreover, `%b` may be implemented by the terminal as a reset of all attributes
+        // (zsh restores the colors on its own, but not the attributes it does not know about).
+        let clear_intensity = (previous.bold && !style.bold) || (previous.dim && !style.dim);
+        let clear_all = clear_intensity && previous.bold;
+        if clear_intensity {
+            if previous.bold {
+                write!(self.writer, "%b")?;
+            }
+            if previous.dim {
+                write_raw!(22);
+            }
+            if style.bold {
+                write!(self.writer, "%B")?;
+            }
+            if style.dim {
+                write_raw!(2);
+            }
+        } else {
+            if style.bold && !previous.bold {
+                write!(self.writer, "%B")?;
+            }
+            if style.dim && !previous.dim {
+                write_raw!(2);
+            }
+        }
+
+        match (previous.underline, style.underline) {
+            (false, true) => write!(self.writer, "%U")?,
+            (true, false) => write!(self.writer, "%u")?,
+            _ => {}
+        }
+
+        for (previous, current, on, off) in [
+            (previous.italic, style.italic, 3, 23),
+            (previous.blink, style.blink, 5, 25),
+            (previous.strike, style.strike, 9, 29),
+        ] {
+            if current && (clear_all || !previous) {
+                write_raw!(on);
+            } else if !current && previous {
+                write_raw!(off);
+            }
+        }
+
+        if let Change::SetTo(foreground) = change.foreground {
+            self.write_color('F', foreground)?;
+        }
+        if let Change::SetTo(background) = change.background {
+            self.write_color('K', background)?;
+        }
+
+        self.style = style;
+
+        Ok(())
+    }
+
+    fn style(&self) -> &Style {
+        &self.style
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    styling::{Color, Color4Bit, StyledWrite},
+    writers::zsh::ZshStyledWriter,
+};
+use std::io::Write;
+
+fn written(write: impl FnOnce(&mut ZshStyledWriter<Vec<u8>>) -> std::io::Result<()>) -> String {
+    let mut writer = ZshStyledWriter::new(Vec::new());
+    write(&mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn escapes_percent_signs() {
+    assert_eq!(written(|writer| write!(writer, "%n")), "%%n");
+    assert_eq!(written(|writer| write!(writer, "%%")), "%%%%");
+    assert_eq!(written(|writer| write!(writer, "a%b%")), "a%%b%%");
+    assert_eq!(written(|writer| write!(writer, "no escape")), "no escape");
+}
+
+#[test]
+fn maps_colors_to_zsh_escapes() {
+    assert_eq!(
+        written(|writer| {
+            writer.change_style(style_change! {
+                foreground: Color::Color4Bit(Color4Bit::DARK_GREEN),
+                background: Color::Color4Bit(Color4Bit::BRIGHT_BLUE),
+            })
+        }),
+        "%F{2}%K{12}"
+    );
+    assert_eq!(
+        written(|writer| writer.change_style(style_change! { foreground: Color::ANSI256(208) })),
+        "%F{208}"
+    );
+    assert_eq!(
+        written(|writer| {
+            writer.change_style(style_change! { background: Color::RGB(0x12, 0xab, 0xff) })
+        }),
+        "%K{#12abff}"
+    );
+}
+
+#[test]
+fn resets_every_property() {
+    assert_eq!(
+        written(|writer| {
+            writer.change_style(style_change! {
+                foreground: Color::ANSI256(1),
+                background: Color::ANSI256(2),
+                bold: true,
+                underline: true,
+                italic: true,
+            })?;
+            writer.reset_style()
+        }),
+        "%B%U%{\x1B[3m%}%F{1}%K{2}%b%u%{\x1B[23m%}%f%k"
+    );
+}